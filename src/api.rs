@@ -0,0 +1,454 @@
+// a small http server speaking json, for bots and tools that drive the
+// engine without a window. it keeps one game of its own and never touches
+// save.db, and it only listens on localhost
+//
+//   GET  /board                  the cells and the state of the game
+//   POST /new                    {"difficulty": 2, "seed": 7, "anti_knight": false,
+//                                 "anti_king": false} or {"code": "..."}
+//   POST /move                   {"action": "place", "index": 3, "number": 5}
+//                                {"action": "pencil", "index": 3, "number": 5, "corner": false}
+//                                {"action": "delete", "index": 3}
+//   POST /undo
+//   GET  /candidates[?index=N]   the numbers that still fit, for every empty cell or one
+//   GET  /hint                   the next number to place and why
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::board::{Board, BoardMode, PencilLayer};
+use crate::cell_state::CellState;
+use crate::constraints::Constraints;
+use crate::hint::{candidates, find_hint};
+use crate::is_legal_index;
+use crate::puzzle_code::PuzzleCode;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewGame {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default = "default_difficulty")]
+    difficulty: u8,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    anti_knight: bool,
+    #[serde(default)]
+    anti_king: bool,
+}
+
+fn default_difficulty() -> u8 {
+    2
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Move {
+    Place {
+        index: usize,
+        number: u8,
+    },
+    Pencil {
+        index: usize,
+        number: u8,
+        #[serde(default)]
+        corner: bool,
+    },
+    Delete {
+        index: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct BoardState<'a> {
+    code: String,
+    difficulty: Option<u8>,
+    seed: Option<u64>,
+    constraints: Constraints,
+    mistakes: u32,
+    solved: bool,
+    cells: &'a [CellState],
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+fn parse<'a, T: serde::Deserialize<'a>>(body: &'a str) -> Result<T, Response> {
+    let body = if body.trim().is_empty() { "{}" } else { body };
+    serde_json::from_str(body).map_err(|error| Response::error(400, &error.to_string()))
+}
+
+pub struct Engine {
+    pub board: Board,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine {
+            board: Board::generate(2, Default::default()),
+        }
+    }
+
+    fn state(&self) -> Value {
+        serde_json::to_value(BoardState {
+            code: PuzzleCode::from_board(&self.board).encode(),
+            difficulty: self.board.difficulty,
+            seed: self.board.seed,
+            constraints: self.board.constraints,
+            mistakes: self.board.mistakes,
+            solved: self.board.is_victory(),
+            cells: &self.board.cell_state,
+        })
+        .unwrap()
+    }
+
+    pub fn handle(&mut self, method: &str, target: &str, body: &str) -> Response {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let result = match (method, path) {
+            ("GET", "/board") => Ok(Response::ok(self.state())),
+            ("POST", "/new") => self.new_game(body),
+            ("POST", "/move") => self.make_move(body),
+            ("POST", "/undo") => {
+                self.board.undo();
+                Ok(Response::ok(self.state()))
+            }
+            ("GET", "/candidates") => self.candidates(query),
+            ("GET", "/hint") => Ok(match find_hint(&self.board) {
+                Some(hint) => Response::ok(serde_json::to_value(hint).unwrap()),
+                None => Response::error(404, "there is no hint"),
+            }),
+            (_, "/board" | "/new" | "/move" | "/undo" | "/candidates" | "/hint") => {
+                Err(Response::error(405, "method not allowed"))
+            }
+            _ => Err(Response::error(404, "not found")),
+        };
+
+        result.unwrap_or_else(|response| response)
+    }
+
+    fn new_game(&mut self, body: &str) -> Result<Response, Response> {
+        let request: NewGame = parse(body)?;
+
+        self.board = match request.code {
+            Some(code) => PuzzleCode::decode(&code)
+                .and_then(|code| code.to_board())
+                .map_err(|error| Response::error(400, &error))?,
+            None => {
+                if !(1..=3).contains(&request.difficulty) {
+                    return Err(Response::error(400, "difficulty must be 1, 2 or 3"));
+                }
+                let constraints = Constraints {
+                    anti_knight: request.anti_knight,
+                    anti_king: request.anti_king,
+                };
+                match request.seed {
+                    Some(seed) => Board::generate_seeded(request.difficulty, constraints, seed),
+                    None => Board::generate(request.difficulty, constraints),
+                }
+            }
+        };
+
+        Ok(Response::ok(self.state()))
+    }
+
+    fn make_move(&mut self, body: &str) -> Result<Response, Response> {
+        let request: Move = parse(body)?;
+        let (index, number) = match request {
+            Move::Place { index, number } | Move::Pencil { index, number, .. } => {
+                (index, Some(number))
+            }
+            Move::Delete { index } => (index, None),
+        };
+        if !is_legal_index(index) {
+            return Err(Response::error(400, "index must be from 0 to 80"));
+        }
+        if number.is_some_and(|number| !(1..=9).contains(&number)) {
+            return Err(Response::error(400, "number must be from 1 to 9"));
+        }
+        if self.board.cell_state[index].initial {
+            return Err(Response::error(409, "the cell is a given"));
+        }
+
+        let board = &mut self.board;
+        board.mode = BoardMode::Normal;
+        board.delete_mode = false;
+        board.cursor_index = Some(index);
+        let mistakes = board.mistakes;
+
+        match request {
+            Move::Place { number, .. } => {
+                board.set_selected_number(number);
+                board.enter_number(false);
+            }
+            Move::Pencil { number, corner, .. } => {
+                board.pencil_layer = if corner {
+                    PencilLayer::Corner
+                } else {
+                    PencilLayer::Center
+                };
+                board.set_selected_number(number);
+                let marks = |board: &Board| {
+                    let cell = &board.cell_state[index];
+                    (cell.pencil, cell.corner)
+                };
+                let before = marks(board);
+                board.enter_number(true);
+                // a mark only goes where the number could
+                if marks(board) == before {
+                    return Err(Response::error(409, "the number can't go in this cell"));
+                }
+            }
+            Move::Delete { .. } => board.delete_cursor_cell(),
+        }
+
+        let mut state = self.state();
+        state["conflict"] = json!(self.board.mistakes > mistakes);
+        Ok(Response::ok(state))
+    }
+
+    fn candidates(&self, query: &str) -> Result<Response, Response> {
+        let index = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("index="))
+            .map(|index| index.parse::<usize>().ok().filter(|&i| is_legal_index(i)))
+            .map(|index| index.ok_or_else(|| Response::error(400, "index must be from 0 to 80")))
+            .transpose()?;
+
+        Ok(Response::ok(match index {
+            Some(index) => json!({ "index": index, "candidates": candidates(&self.board, index) }),
+            None => json!({
+                "candidates": (0..81).map(|index| candidates(&self.board, index)).collect::<Vec<_>>()
+            }),
+        }))
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Error",
+    }
+}
+
+const MAX_BODY: usize = 64 * 1024;
+// a client that stops sending doesn't hold up the others for long
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// one request a connection, the connection is closed after the answer
+fn handle_connection(stream: TcpStream, engine: &mut Engine) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let response = if content_length > MAX_BODY {
+        Response::error(413, "the body is too large")
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        match String::from_utf8(body) {
+            Ok(body) => engine.handle(&method, &target, &body),
+            Err(_) => Response::error(400, "the body is not utf-8"),
+        }
+    };
+
+    let body = response.body.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        status_text(response.status),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// answers requests one after the other until the listener fails
+pub fn serve(listener: TcpListener, engine: &mut Engine) -> std::io::Result<()> {
+    for stream in listener.incoming() {
+        if let Err(error) = handle_connection(stream?, engine) {
+            eprintln!("{}", error);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+
+    use serde_json::{json, Value};
+
+    use super::{serve, Engine};
+    use crate::puzzle_code::test_puzzle;
+
+    fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener, &mut Engine::new()));
+        address
+    }
+
+    fn request(address: SocketAddr, method: &str, path: &str, body: Value) -> (u16, Value) {
+        let body = if body.is_null() {
+            String::new()
+        } else {
+            body.to_string()
+        };
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn code() -> String {
+        test_puzzle().encode()
+    }
+
+    #[test]
+    fn play_over_http() {
+        let address = start();
+
+        let (status, state) = request(address, "POST", "/new", json!({ "code": code() }));
+        assert_eq!(status, 200);
+        assert_eq!(state["code"], code());
+        assert_eq!(state["cells"][0]["number"], 5);
+        assert_eq!(state["cells"][0]["initial"], true);
+
+        let (_, candidates) = request(address, "GET", "/candidates?index=2", Value::Null);
+        assert_eq!(candidates["candidates"], json!([1, 2, 4]));
+
+        let (status, state) = request(
+            address,
+            "POST",
+            "/move",
+            json!({ "action": "place", "index": 2, "number": 4 }),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(state["cells"][2]["number"], 4);
+        assert_eq!(state["conflict"], false);
+
+        let (_, state) = request(
+            address,
+            "POST",
+            "/move",
+            json!({ "action": "pencil", "index": 3, "number": 6, "corner": true }),
+        );
+        assert_eq!(state["cells"][3]["corner"][5], 6);
+
+        let (_, state) = request(address, "POST", "/undo", Value::Null);
+        assert_eq!(state["cells"][3]["corner"][5], Value::Null);
+
+        let (status, hint) = request(address, "GET", "/hint", Value::Null);
+        assert_eq!(status, 200);
+        assert!(hint["reason"].is_string());
+
+        let (_, state) = request(address, "GET", "/board", Value::Null);
+        assert_eq!(state["solved"], false);
+    }
+
+    #[test]
+    fn bad_requests() {
+        let address = start();
+        request(address, "POST", "/new", json!({ "code": code() }));
+
+        let (status, _) = request(
+            address,
+            "POST",
+            "/move",
+            json!({ "action": "place", "index": 0, "number": 1 }),
+        );
+        assert_eq!(status, 409);
+
+        // 5 is in the same row as cell 2
+        let (status, _) = request(
+            address,
+            "POST",
+            "/move",
+            json!({ "action": "pencil", "index": 2, "number": 5 }),
+        );
+        assert_eq!(status, 409);
+
+        let (status, error) = request(
+            address,
+            "POST",
+            "/move",
+            json!({ "action": "place", "index": 81, "number": 1 }),
+        );
+        assert_eq!(status, 400);
+        assert!(error["error"].is_string());
+
+        let (status, _) = request(address, "POST", "/move", json!({ "action": "jump" }));
+        assert_eq!(status, 400);
+        let (status, _) = request(address, "POST", "/new", json!({ "difficulty": 4 }));
+        assert_eq!(status, 400);
+        let (status, _) = request(address, "GET", "/move", Value::Null);
+        assert_eq!(status, 405);
+        let (status, _) = request(address, "GET", "/nowhere", Value::Null);
+        assert_eq!(status, 404);
+    }
+}
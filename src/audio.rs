@@ -0,0 +1,61 @@
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+use crate::settings::Settings;
+use crate::sound::{event_wav, SoundEvent, SOUND_EVENTS};
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn sudoku_vibrate(milliseconds: u32);
+}
+
+const REJECT_VIBRATION: u32 = 80;
+
+#[derive(Default)]
+pub struct Audio {
+    sounds: Vec<(SoundEvent, Sound)>,
+}
+
+impl Audio {
+    // a sound that doesn't load is left out, the game plays on silently
+    pub async fn load() -> Self {
+        let mut sounds = vec![];
+        for event in SOUND_EVENTS {
+            if let Ok(sound) = load_sound_from_bytes(&event_wav(event)).await {
+                sounds.push((event, sound));
+            }
+        }
+
+        Audio { sounds }
+    }
+
+    // muting silences the sounds, phones still buzz on a rejected number
+    pub fn play(&self, event: SoundEvent, settings: &Settings) {
+        if let Some((_, sound)) = self
+            .sounds
+            .iter()
+            .find(|(sound_event, _)| *sound_event == event)
+            .filter(|_| settings.sound)
+        {
+            play_sound(
+                *sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume: settings.volume,
+                },
+            );
+        }
+
+        if event == SoundEvent::Reject {
+            vibrate(REJECT_VIBRATION);
+        }
+    }
+}
+
+// a buzz on phones whose browser can, through sudoku-bridge.js
+#[cfg(target_arch = "wasm32")]
+fn vibrate(milliseconds: u32) {
+    unsafe { sudoku_vibrate(milliseconds) };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn vibrate(_milliseconds: u32) {}
@@ -0,0 +1,155 @@
+// writes pictures of a board without opening a window, for the docs and ci,
+// and printable booklets of new puzzles
+//
+// sudoku-export [--new DIFFICULTY] [--pencil] [--highlight] [--size PIXELS]
+//               [--theme NAME] OUTPUT.svg|OUTPUT.png
+// sudoku-export [--new DIFFICULTY] [--count N] [--collection FILE]
+//               [--per-page N] OUTPUT.pdf
+
+#[cfg(not(target_arch = "wasm32"))]
+const USAGE: &str = "usage: sudoku-export [--new DIFFICULTY] [--pencil] [--highlight] \
+[--size PIXELS] [--theme NAME] OUTPUT.svg|OUTPUT.png
+       sudoku-export [--new DIFFICULTY] [--count N] [--collection FILE] [--per-page N] \
+OUTPUT.pdf";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run(args: &[String]) -> Result<(), String> {
+    use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use rust_sudoku::board::Board;
+    use rust_sudoku::export::booklet::{
+        booklet_pdf, parse_collection, BookletPuzzle, DEFAULT_PER_PAGE, PER_PAGE_CHOICES,
+    };
+    use rust_sudoku::export::{png::to_png, svg::to_svg, ExportOptions, Picture};
+    use rust_sudoku::locale::detect_language;
+    use rust_sudoku::settings::Settings;
+    use rust_sudoku::theme::Theme;
+
+    let settings = Settings::load();
+    let mut options = ExportOptions {
+        digit_glyphs: settings.digit_glyphs,
+        ..ExportOptions::new()
+    };
+    let mut theme_name = settings.theme;
+    let mut difficulty = None;
+    let mut count = 6;
+    let mut collection = None;
+    let mut per_page = DEFAULT_PER_PAGE;
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match arg.as_str() {
+            "--pencil" => options.pencil_marks = true,
+            "--highlight" => options.highlights = true,
+            "--new" => {
+                let value = value("--new")?;
+                match value.parse::<u8>() {
+                    Ok(level @ 1..=3) => difficulty = Some(level),
+                    _ => return Err(format!("difficulty must be 1, 2 or 3, not {}", value)),
+                }
+            }
+            "--size" => {
+                let value = value("--size")?;
+                match value.parse::<f32>() {
+                    // the range also turns away inf and nan
+                    Ok(size) if (90.0..=8192.0).contains(&size) => options.size = size,
+                    _ => {
+                        return Err(format!(
+                            "size must be between 90 and 8192 pixels, not {}",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--theme" => theme_name = value("--theme")?,
+            "--count" => {
+                let value = value("--count")?;
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => count = n,
+                    _ => return Err(format!("count must be at least 1, not {}", value)),
+                }
+            }
+            "--collection" => collection = Some(value("--collection")?),
+            "--per-page" => {
+                let value = value("--per-page")?;
+                match value.parse::<usize>() {
+                    Ok(n) if PER_PAGE_CHOICES.contains(&n) => per_page = n,
+                    _ => return Err(format!("per page must be 1, 2, 4 or 6, not {}", value)),
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => output = Some(arg.clone()),
+        }
+    }
+
+    let output = output.ok_or_else(|| USAGE.to_string())?;
+    let extension = Path::new(&output)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64);
+    macroquad::rand::srand(seed);
+
+    if extension.as_deref() == Some("pdf") {
+        let puzzles = match collection {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|error| format!("{}: {}", path, error))?;
+                parse_collection(&text).map_err(|error| format!("{}: {}", path, error))?
+            }
+            None => (0..count)
+                .map(|_| BookletPuzzle::generate(difficulty.unwrap_or(2), Default::default()))
+                .collect(),
+        };
+        if puzzles.is_empty() {
+            return Err("the collection has no puzzles".to_string());
+        }
+
+        let language = settings.language.unwrap_or_else(detect_language);
+        let data = booklet_pdf(&puzzles, per_page, language, options.digit_glyphs);
+        return std::fs::write(&output, data).map_err(|error| format!("{}: {}", output, error));
+    }
+
+    let board = match difficulty {
+        Some(difficulty) => Board::generate(difficulty, Default::default()),
+        None => Board::load().ok_or("there is no saved game, use --new")?,
+    };
+
+    let themes = Theme::all();
+    if !themes.iter().any(|theme| theme.name == theme_name) {
+        return Err(format!("unknown theme {}", theme_name));
+    }
+    let theme = Theme::find(&themes, &theme_name);
+
+    let picture = Picture::board(&board, &theme, &options);
+    let data = match extension.as_deref() {
+        Some("svg") => to_svg(&picture).into_bytes(),
+        Some("png") => to_png(&picture),
+        _ => return Err(format!("{} is not .svg, .png or .pdf", output)),
+    };
+
+    std::fs::write(&output, data).map_err(|error| format!("{}: {}", output, error))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,53 @@
+// a json api for bots and tools, see src/api.rs for the endpoints
+//
+// sudoku-server [--port PORT]
+
+#[cfg(not(target_arch = "wasm32"))]
+const USAGE: &str = "usage: sudoku-server [--port PORT]";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run(args: &[String]) -> Result<(), String> {
+    use std::net::TcpListener;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use rust_sudoku::api::{serve, Engine};
+
+    let mut port: u16 = 7878;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = args.next().ok_or("--port needs a value")?;
+                port = value
+                    .parse()
+                    .map_err(|_| format!("port must be a number, not {}", value))?;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64);
+    macroquad::rand::srand(seed);
+
+    // only this machine can reach it
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|error| format!("127.0.0.1:{}: {}", port, error))?;
+    println!("listening on http://{}", listener.local_addr().unwrap());
+
+    serve(listener, &mut Engine::new()).map_err(|error| error.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,365 @@
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+use rust_sudoku::board::Board;
+use rust_sudoku::locale::{detect_language, Language};
+use rust_sudoku::puzzle_code::startup_board;
+use rust_sudoku::settings::{InputStyle, Settings};
+use rust_sudoku::theme::Theme;
+use rust_sudoku::DIGIT_COUNT;
+
+use crate::layout::{Button, Layout, BUTTONS};
+
+const CURSOR_KEYS: [(char, i32, i32); 8] = [
+    ('w', 0, -1),
+    ('s', 0, 1),
+    ('a', -1, 0),
+    ('d', 1, 0),
+    ('k', 0, -1),
+    ('j', 0, 1),
+    ('h', -1, 0),
+    ('l', 1, 0),
+];
+
+// a modifier click adds cells to the multi selection
+fn is_multi_select_down(modifiers: KeyModifiers) -> bool {
+    modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+// ctrl/alt and a digit writes a pencil mark like in the game, shift is
+// taken by layouts that need it for the digits themselves
+fn is_pencil_modifier_down(modifiers: KeyModifiers) -> bool {
+    modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+// the digit of a key by its character, and whether it asks for a pencil mark
+fn digit_key(key: &KeyEvent) -> Option<(u8, bool)> {
+    let digit = match key.code {
+        KeyCode::Char(character @ '1'..='9') => character as u8 - b'0',
+        _ => return None,
+    };
+
+    Some((digit, is_pencil_modifier_down(key.modifiers)))
+}
+
+pub struct App {
+    pub board: Board,
+    pub settings: Settings,
+    pub theme: Theme,
+    pub language: Language,
+    pub layout: Option<Layout>,
+    pub new_game_prompt: bool,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new() -> Self {
+        let mut board = startup_board(std::env::args().nth(1).as_deref());
+
+        let settings = Settings::load();
+        board.settings = settings.clone();
+        board.highlight();
+
+        let themes = Theme::all();
+        let theme = Theme::find(&themes, &settings.theme);
+        let language = settings.language.unwrap_or_else(detect_language);
+
+        let mut app = App {
+            board,
+            settings,
+            theme,
+            language,
+            layout: None,
+            new_game_prompt: false,
+            quit: false,
+        };
+
+        app.board.save();
+
+        app
+    }
+
+    pub fn text(&self, key: &'static str) -> &'static str {
+        self.language.text(key)
+    }
+
+    pub fn button_label(&self, button: Button) -> String {
+        let label = match button {
+            Button::Number(n) => return self.settings.digit_glyphs.glyph(n).to_string(),
+            Button::Pencil => self.text("menu.pencil"),
+            Button::PencilLayer => self.text("menu.corner"),
+            Button::Color => self.text("menu.color"),
+            Button::Delete => self.text("menu.delete"),
+            Button::Undo => self.text("menu.undo"),
+            Button::New => self.text("menu.new"),
+            Button::InputStyle => match self.settings.input_style {
+                InputStyle::NumberFirst => self.text("input.number_first"),
+                InputStyle::CellFirst => self.text("input.cell_first"),
+            },
+            Button::Quit => self.text("menu.quit"),
+        };
+
+        format!(" {} ", label)
+    }
+
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        let widths: Vec<u16> = BUTTONS
+            .iter()
+            .map(|&button| self.button_label(button).chars().count() as u16)
+            .collect();
+
+        self.layout = Layout::fit(columns, rows, &widths);
+    }
+
+    // the clock only runs while the game is in front of the player
+    pub fn tick(&mut self, seconds: f64) {
+        if !self.new_game_prompt && !self.board.is_victory() {
+            self.board.elapsed += seconds;
+        }
+    }
+
+    fn apply_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.settings.save();
+
+        self.board.settings = self.settings.clone();
+        self.board.highlight();
+        self.board.save();
+    }
+
+    fn input_number(&mut self, number: u8, enter: bool, pencil: bool) {
+        if self.board.is_number_done(number) {
+            return;
+        }
+
+        self.board.disable_delete_mode();
+        self.board.set_selected_number(number);
+        self.board.highlight();
+        if self.board.has_multi_selection() {
+            self.board.toggle_multi_selection_pencil(number);
+        } else if enter {
+            self.board.enter_number(pencil);
+        }
+        self.board.save();
+    }
+
+    // the number row doubles as the palette while colouring,
+    // the last slot being the eraser
+    fn input_color(&mut self, number: u8, paint: bool) {
+        let color = if number == DIGIT_COUNT { 0 } else { number };
+
+        self.board.disable_delete_mode();
+        self.board.set_selected_color(color);
+        if self.board.has_multi_selection() {
+            self.board.paint_multi_selection();
+        } else if paint {
+            self.board.paint_cursor_cell();
+        }
+        self.board.save();
+    }
+
+    fn input_delete(&mut self) {
+        self.board.disable_delete_mode();
+        if self.board.has_multi_selection() {
+            self.board.delete_multi_selection();
+        } else {
+            self.board.delete_cursor_cell();
+        }
+        self.board.save();
+    }
+
+    fn new_game(&mut self, difficulty: u8) {
+        self.new_game_prompt = false;
+        self.board = Board::generate(difficulty, self.board.constraints);
+        self.board.settings = self.settings.clone();
+        self.board.highlight();
+        self.board.save();
+    }
+
+    fn press_button(&mut self, button: Button) {
+        let cell_first = self.settings.input_style == InputStyle::CellFirst;
+
+        match button {
+            Button::Number(n) if self.board.color_mode => self.input_color(n, cell_first),
+            Button::Number(n) => self.input_number(n, cell_first, false),
+            Button::Pencil => {
+                self.board.disable_delete_mode();
+                self.board.toggle_pencil_mode();
+                self.board.save();
+            }
+            Button::PencilLayer => {
+                self.board.disable_delete_mode();
+                self.board.toggle_pencil_layer();
+                self.board.save();
+            }
+            Button::Color => {
+                self.board.disable_delete_mode();
+                self.board.toggle_color_mode();
+                self.board.save();
+            }
+            Button::Delete => {
+                if self.board.has_multi_selection() || cell_first {
+                    self.input_delete();
+                } else {
+                    self.board.toggle_delete_mode();
+                    self.board.save();
+                }
+            }
+            Button::Undo => {
+                self.board.disable_delete_mode();
+                self.board.undo();
+                self.board.save();
+            }
+            Button::New => self.new_game_prompt = true,
+            Button::InputStyle => {
+                self.board.disable_delete_mode();
+                let mut settings = self.settings.clone();
+                settings.toggle_input_style();
+                self.apply_settings(settings);
+            }
+            Button::Quit => self.quit = true,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        // windows also reports releases
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+
+        if self.new_game_prompt {
+            match key.code {
+                KeyCode::Char(difficulty @ '1'..='3') => {
+                    self.new_game(difficulty as u8 - b'0');
+                }
+                KeyCode::Esc => self.new_game_prompt = false,
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('n') => self.new_game_prompt = true,
+            KeyCode::Char('i') => self.press_button(Button::InputStyle),
+            KeyCode::Char('u') => self.press_button(Button::Undo),
+            KeyCode::Char('m') => self.press_button(Button::PencilLayer),
+            KeyCode::Char('c') => self.press_button(Button::Color),
+            KeyCode::Char(' ') => self.press_button(Button::Pencil),
+            KeyCode::Backspace | KeyCode::Delete => self.input_delete(),
+            KeyCode::Esc => {
+                self.board.clear_multi_selection();
+                self.board.cursor_index = None;
+                self.board.save();
+            }
+            KeyCode::Up => self.move_cursor(0, -1),
+            KeyCode::Down => self.move_cursor(0, 1),
+            KeyCode::Left => self.move_cursor(-1, 0),
+            KeyCode::Right => self.move_cursor(1, 0),
+            KeyCode::Char(character) => {
+                if let Some(&(_, dx, dy)) = CURSOR_KEYS.iter().find(|(key, _, _)| *key == character)
+                {
+                    self.move_cursor(dx, dy);
+                } else if let Some((number, pencil)) = digit_key(&key) {
+                    if self.board.color_mode {
+                        self.input_color(number, true);
+                    } else {
+                        self.input_number(number, true, pencil);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        self.board.move_cursor(dx, dy);
+        self.board.save();
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let layout = match &self.layout {
+            Some(layout) => layout,
+            None => return,
+        };
+
+        let cell = layout.cell_at(mouse.column, mouse.row);
+        let button = layout.button_at(mouse.column, mouse.row);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.new_game_prompt => {
+                self.new_game_prompt = false;
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(button) = button {
+                    self.press_button(button);
+                    return;
+                }
+
+                let index = match cell {
+                    Some(index) => index,
+                    None => {
+                        self.board.selected_index = None;
+                        return;
+                    }
+                };
+
+                if is_multi_select_down(mouse.modifiers) {
+                    self.board.multi_select_cell(index);
+                } else {
+                    self.board.clear_multi_selection();
+                    self.board.click_cell(index);
+                }
+                self.board.save();
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(index) = cell {
+                    self.board.drag_cell(index);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.board.stop_drag();
+                self.board.save();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::digit_key;
+
+    #[test]
+    fn digit_keys() {
+        let key = |code, modifiers| digit_key(&KeyEvent::new(code, modifiers));
+
+        assert_eq!(
+            key(KeyCode::Char('5'), KeyModifiers::NONE),
+            Some((5, false))
+        );
+        assert_eq!(key(KeyCode::Char('5'), KeyModifiers::ALT), Some((5, true)));
+        assert_eq!(
+            key(KeyCode::Char('5'), KeyModifiers::CONTROL),
+            Some((5, true))
+        );
+        // layouts like azerty need shift for the digits
+        assert_eq!(
+            key(KeyCode::Char('5'), KeyModifiers::SHIFT),
+            Some((5, false))
+        );
+        assert_eq!(key(KeyCode::Char('%'), KeyModifiers::SHIFT), None);
+        assert_eq!(key(KeyCode::Char('('), KeyModifiers::NONE), None);
+        assert_eq!(key(KeyCode::Char('0'), KeyModifiers::NONE), None);
+        assert_eq!(key(KeyCode::Char('x'), KeyModifiers::NONE), None);
+        assert_eq!(key(KeyCode::Enter, KeyModifiers::NONE), None);
+    }
+}
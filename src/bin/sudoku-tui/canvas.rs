@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: Color,
+    pub background: Color,
+    pub bold: bool,
+    pub underline: bool,
+    pub crossed_out: bool,
+}
+
+impl Style {
+    pub fn new(foreground: Color, background: Color) -> Self {
+        Style {
+            foreground,
+            background,
+            bold: false,
+            underline: false,
+            crossed_out: false,
+        }
+    }
+}
+
+// a frame is drawn here first and written out in one go,
+// so the terminal never shows half a board
+pub struct Canvas {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<(char, Style)>,
+}
+
+impl Canvas {
+    pub fn new(width: u16, height: u16, style: Style) -> Self {
+        Canvas {
+            width,
+            height,
+            cells: vec![(' ', style); width as usize * height as usize],
+        }
+    }
+
+    // anything past the edges is dropped
+    pub fn put(&mut self, x: u16, y: u16, character: char, style: Style) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        self.cells[y as usize * self.width as usize + x as usize] = (character, style);
+    }
+
+    pub fn text(&mut self, x: u16, y: u16, text: &str, style: Style) {
+        for (i, character) in text.chars().enumerate() {
+            self.put(x + i as u16, y, character, style);
+        }
+    }
+
+    pub fn fill(&mut self, x: u16, y: u16, width: u16, height: u16, style: Style) {
+        for row in y..y + height {
+            for column in x..x + width {
+                self.put(column, row, ' ', style);
+            }
+        }
+    }
+
+    pub fn flush(&self, out: &mut impl Write) -> io::Result<()> {
+        for y in 0..self.height {
+            queue!(out, MoveTo(0, y))?;
+
+            let mut current: Option<Style> = None;
+            for x in 0..self.width {
+                // the last character would scroll some terminals
+                if y == self.height - 1 && x == self.width - 1 {
+                    break;
+                }
+
+                let (character, style) = self.cells[y as usize * self.width as usize + x as usize];
+                if current != Some(style) {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reset),
+                        SetForegroundColor(style.foreground),
+                        SetBackgroundColor(style.background)
+                    )?;
+                    if style.bold {
+                        queue!(out, SetAttribute(Attribute::Bold))?;
+                    }
+                    if style.underline {
+                        queue!(out, SetAttribute(Attribute::Underlined))?;
+                    }
+                    if style.crossed_out {
+                        queue!(out, SetAttribute(Attribute::CrossedOut))?;
+                    }
+                    current = Some(style);
+                }
+                queue!(out, Print(character))?;
+            }
+        }
+
+        queue!(out, SetAttribute(Attribute::Reset))?;
+        out.flush()
+    }
+}
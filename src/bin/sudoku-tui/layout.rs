@@ -0,0 +1,207 @@
+use rust_sudoku::index::xy_to_index;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Number(u8),
+    Pencil,
+    PencilLayer,
+    Color,
+    Delete,
+    Undo,
+    New,
+    InputStyle,
+    Quit,
+}
+
+pub const BUTTONS: [Button; 8] = [
+    Button::Pencil,
+    Button::PencilLayer,
+    Button::Color,
+    Button::Delete,
+    Button::Undo,
+    Button::New,
+    Button::InputStyle,
+    Button::Quit,
+];
+
+// the cell sizes tried in order, 7x3 leaves room for pencil marks
+// and 3x1 still fits an 80x24 terminal
+const CELL_SIZES: [(u16, u16); 2] = [(7, 3), (3, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuSpan {
+    pub button: Button,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+}
+
+// where everything goes on the terminal, in columns and rows,
+// every cell line is drawn so a cell takes its size plus one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub cell_width: u16,
+    pub cell_height: u16,
+    pub left: u16,
+    pub top: u16,
+    pub buttons: Vec<MenuSpan>,
+    pub status_y: u16,
+}
+
+impl Layout {
+    // button_widths follows BUTTONS, none when even the small board doesn't fit
+    pub fn fit(columns: u16, rows: u16, button_widths: &[u16]) -> Option<Layout> {
+        CELL_SIZES.iter().find_map(|&(cell_width, cell_height)| {
+            Self::with_cell_size(columns, rows, cell_width, cell_height, button_widths)
+        })
+    }
+
+    fn with_cell_size(
+        columns: u16,
+        rows: u16,
+        cell_width: u16,
+        cell_height: u16,
+        button_widths: &[u16],
+    ) -> Option<Layout> {
+        let grid_width = 9 * (cell_width + 1) + 1;
+        let grid_height = 9 * (cell_height + 1) + 1;
+        if grid_width > columns {
+            return None;
+        }
+
+        let left = (columns - grid_width) / 2;
+        let top = 0;
+
+        // the numbers sit right under the grid, lined up with its columns,
+        // the buttons flow below them and wrap at the edge of the terminal
+        let number_y = top + grid_height;
+        let mut buttons: Vec<MenuSpan> = (1..=9)
+            .map(|n| MenuSpan {
+                button: Button::Number(n),
+                x: left + 1 + (n as u16 - 1) * (cell_width + 1),
+                y: number_y,
+                width: cell_width,
+            })
+            .collect();
+
+        let mut x = left;
+        let mut y = number_y + 1;
+        for (&button, &width) in BUTTONS.iter().zip(button_widths) {
+            if x > left && x + width > columns {
+                x = left;
+                y += 1;
+            }
+            buttons.push(MenuSpan {
+                button,
+                x,
+                y,
+                width,
+            });
+            x += width + 1;
+        }
+
+        let status_y = y + 1;
+        if status_y >= rows {
+            return None;
+        }
+
+        Some(Layout {
+            cell_width,
+            cell_height,
+            left,
+            top,
+            buttons,
+            status_y,
+        })
+    }
+
+    // the terminal position of the top left character of a cell
+    pub fn cell_origin(&self, x: usize, y: usize) -> (u16, u16) {
+        (
+            self.left + 1 + x as u16 * (self.cell_width + 1),
+            self.top + 1 + y as u16 * (self.cell_height + 1),
+        )
+    }
+
+    pub fn cell_at(&self, column: u16, row: u16) -> Option<usize> {
+        let x = position_in_cells(column, self.left, self.cell_width)?;
+        let y = position_in_cells(row, self.top, self.cell_height)?;
+
+        Some(xy_to_index(x, y, 9))
+    }
+
+    pub fn button_at(&self, column: u16, row: u16) -> Option<Button> {
+        self.buttons
+            .iter()
+            .find(|span| span.y == row && column >= span.x && column < span.x + span.width)
+            .map(|span| span.button)
+    }
+}
+
+// which cell a column or a row falls in, lines belong to no cell
+fn position_in_cells(position: u16, start: u16, size: u16) -> Option<usize> {
+    let offset = position.checked_sub(start + 1)?;
+    let cell = (offset / (size + 1)) as usize;
+    if cell >= 9 || offset % (size + 1) == size {
+        return None;
+    }
+
+    Some(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Button, Layout, BUTTONS};
+
+    const WIDTHS: [u16; 8] = [8, 8, 8, 8, 6, 5, 14, 6];
+
+    #[test]
+    fn fit_picks_the_largest_board() {
+        let layout = Layout::fit(100, 50, &WIDTHS).unwrap();
+        assert_eq!((layout.cell_width, layout.cell_height), (7, 3));
+        assert_eq!(layout.left, (100 - 73) / 2);
+
+        let layout = Layout::fit(80, 24, &WIDTHS).unwrap();
+        assert_eq!((layout.cell_width, layout.cell_height), (3, 1));
+        assert!(layout.status_y < 24);
+
+        assert!(Layout::fit(30, 24, &WIDTHS).is_none());
+        assert!(Layout::fit(80, 18, &WIDTHS).is_none());
+    }
+
+    #[test]
+    fn cell_at() {
+        let layout = Layout::fit(80, 24, &WIDTHS).unwrap();
+        for index in [0, 8, 40, 72, 80] {
+            let (x, y) = layout.cell_origin(index % 9, index / 9);
+            assert_eq!(layout.cell_at(x, y), Some(index));
+            assert_eq!(layout.cell_at(x + 2, y), Some(index));
+            // the line right of the cell
+            assert_eq!(layout.cell_at(x + 3, y), None);
+        }
+
+        assert_eq!(layout.cell_at(layout.left, 1), None);
+        assert_eq!(layout.cell_at(0, 0), None);
+        let (right, _) = layout.cell_origin(8, 0);
+        assert_eq!(layout.cell_at(right + 3, 1), None);
+        assert_eq!(layout.cell_at(right + 4, 1), None);
+    }
+
+    #[test]
+    fn button_at() {
+        let layout = Layout::fit(100, 50, &WIDTHS).unwrap();
+        let (x, _) = layout.cell_origin(4, 0);
+        assert_eq!(layout.button_at(x, 37), Some(Button::Number(5)));
+
+        for span in layout.buttons.iter().skip(9) {
+            assert_eq!(layout.button_at(span.x, span.y), Some(span.button));
+            assert_eq!(layout.button_at(span.x + span.width, span.y), None);
+        }
+        assert_eq!(layout.buttons.len(), 9 + BUTTONS.len());
+
+        // narrow terminals wrap the buttons
+        let layout = Layout::fit(40, 30, &WIDTHS).unwrap();
+        let rows = layout.buttons.iter().skip(9).map(|span| span.y);
+        assert!(rows.clone().max() > rows.min());
+    }
+}
@@ -0,0 +1,91 @@
+// the same game in a terminal, for playing over ssh. it shares the board,
+// the generator and save.db with the window, so a game started in one
+// goes on in the other
+
+#[cfg(not(target_arch = "wasm32"))]
+mod app;
+#[cfg(not(target_arch = "wasm32"))]
+mod canvas;
+#[cfg(not(target_arch = "wasm32"))]
+mod layout;
+#[cfg(not(target_arch = "wasm32"))]
+mod render;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::io::Result<()> {
+    use std::io::{stdout, BufWriter};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    use crossterm::{
+        cursor::{Hide, Show},
+        event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+        execute,
+        terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    };
+
+    use app::App;
+    use canvas::{Canvas, Style};
+    use render::render;
+
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64);
+    macroquad::rand::srand(seed);
+
+    let mut app = App::new();
+    let mut out = BufWriter::new(stdout());
+
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, EnableMouseCapture, Hide)?;
+
+    // leave the terminal usable even when the game panics
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(stdout(), Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        default_hook(info);
+    }));
+
+    let (mut columns, mut rows) = terminal::size()?;
+    app.resize(columns, rows);
+    let mut last_tick = Instant::now();
+
+    while !app.quit {
+        let mut canvas = Canvas::new(
+            columns,
+            rows,
+            Style::new(
+                crossterm::style::Color::Reset,
+                crossterm::style::Color::Reset,
+            ),
+        );
+        render(&app, &mut canvas);
+        canvas.flush(&mut out)?;
+
+        // wake up every so often so the timer keeps moving
+        if event::poll(Duration::from_millis(250))? {
+            match event::read()? {
+                Event::Key(key) => app.handle_key(key),
+                Event::Mouse(mouse) => app.handle_mouse(mouse),
+                Event::Resize(new_columns, new_rows) => {
+                    (columns, rows) = (new_columns, new_rows);
+                }
+                _ => {}
+            }
+        }
+
+        // labels change width with the input style
+        app.resize(columns, rows);
+        app.tick(last_tick.elapsed().as_secs_f64());
+        last_tick = Instant::now();
+    }
+
+    // the elapsed time only reaches the save with the next move otherwise
+    app.board.save();
+
+    execute!(out, Show, DisableMouseCapture, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,361 @@
+use crossterm::style;
+use macroquad::color::Color;
+
+use rust_sudoku::board::{BoardMode, PencilLayer};
+use rust_sudoku::board_layout::{self, corner_slot, number_color};
+use rust_sudoku::cell_state::{CellSelection, CellState};
+use rust_sudoku::format_time;
+use rust_sudoku::index::index_to_xy;
+
+use crate::app::App;
+use crate::canvas::{Canvas, Style};
+use crate::layout::{Button, Layout};
+
+// terminals have no transparency, translucent colours are mixed
+// with the background instead
+fn rgb(color: Color, background: Color) -> style::Color {
+    let mix = |value: f32, under: f32| {
+        let mixed = value * color.a + under * (1.0 - color.a);
+        (mixed.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+
+    style::Color::Rgb {
+        r: mix(color.r, background.r),
+        g: mix(color.g, background.g),
+        b: mix(color.b, background.b),
+    }
+}
+
+// the character where a horizontal and a vertical grid line meet,
+// box lines are double and cell lines single
+fn junction(line_x: usize, line_y: usize) -> char {
+    let double_x = line_x.is_multiple_of(3);
+    let double_y = line_y.is_multiple_of(3);
+    let (left, right) = (line_x == 0, line_x == 9);
+    let (top, bottom) = (line_y == 0, line_y == 9);
+
+    match (double_y, double_x) {
+        (true, true) => match (top, bottom, left, right) {
+            (true, _, true, _) => '╔',
+            (true, _, _, true) => '╗',
+            (_, true, true, _) => '╚',
+            (_, true, _, true) => '╝',
+            (true, ..) => '╦',
+            (_, true, ..) => '╩',
+            (_, _, true, _) => '╠',
+            (_, _, _, true) => '╣',
+            _ => '╬',
+        },
+        (true, false) if top => '╤',
+        (true, false) if bottom => '╧',
+        (true, false) => '╪',
+        (false, true) if left => '╟',
+        (false, true) if right => '╢',
+        (false, true) => '╫',
+        (false, false) => '┼',
+    }
+}
+
+pub fn render(app: &App, canvas: &mut Canvas) {
+    let theme = &app.theme;
+    let background = theme.background;
+    let base = Style::new(
+        rgb(theme.status_text, background),
+        rgb(background, background),
+    );
+    canvas.fill(0, 0, canvas.width, canvas.height, base);
+
+    let layout = match &app.layout {
+        Some(layout) => layout,
+        None => {
+            canvas.text(0, 0, app.text("too_small"), base);
+            return;
+        }
+    };
+
+    for index in 0..81 {
+        render_cell(app, layout, canvas, index);
+    }
+    render_lines(app, layout, canvas);
+    render_menu(app, layout, canvas);
+    render_status(app, layout, canvas, base);
+}
+
+fn render_lines(app: &App, layout: &Layout, canvas: &mut Canvas) {
+    let theme = &app.theme;
+    let cell_background = rgb(theme.cell_normal, theme.background);
+    let box_style = Style::new(rgb(theme.box_line, theme.background), cell_background);
+    let cell_style = Style::new(rgb(theme.cell_line, theme.background), cell_background);
+
+    for line_y in 0..=9usize {
+        for line_x in 0..=9usize {
+            let x = layout.left + line_x as u16 * (layout.cell_width + 1);
+            let y = layout.top + line_y as u16 * (layout.cell_height + 1);
+            let double_x = line_x.is_multiple_of(3);
+            let double_y = line_y.is_multiple_of(3);
+
+            let style = if double_x || double_y {
+                box_style
+            } else {
+                cell_style
+            };
+            canvas.put(x, y, junction(line_x, line_y), style);
+
+            if line_x < 9 {
+                let (character, style) = if double_y {
+                    ('═', box_style)
+                } else {
+                    ('─', cell_style)
+                };
+                for i in 1..=layout.cell_width {
+                    canvas.put(x + i, y, character, style);
+                }
+            }
+
+            if line_y < 9 {
+                let (character, style) = if double_x {
+                    ('║', box_style)
+                } else {
+                    ('│', cell_style)
+                };
+                for i in 1..=layout.cell_height {
+                    canvas.put(x, y + i, character, style);
+                }
+            }
+        }
+    }
+}
+
+fn cell_background(app: &App, index: usize) -> Color {
+    let board = &app.board;
+    let theme = &app.theme;
+
+    // the cursor has no outline to draw, it takes the colour the
+    // window uses for its outline instead
+    if board.cursor_index == Some(index) {
+        return match board.mode {
+            BoardMode::Normal => theme.menu_number_background_normal,
+            BoardMode::Pencil => theme.menu_number_background_pencil,
+        };
+    }
+
+    if board.multi_selection.contains(&index) {
+        return theme.cell_multi_selected;
+    }
+
+    board_layout::cell_background(board, theme, index)
+}
+
+fn render_cell(app: &App, layout: &Layout, canvas: &mut Canvas, index: usize) {
+    let board = &app.board;
+    let theme = &app.theme;
+    let settings = &app.settings;
+    let cell_state = &board.cell_state[index];
+    let (cell_x, cell_y) = index_to_xy(index, 9);
+    let (x, y) = layout.cell_origin(cell_x, cell_y);
+    let (width, height) = (layout.cell_width, layout.cell_height);
+
+    let background = rgb(cell_background(app, index), theme.background);
+    let on_cursor = board.cursor_index == Some(index);
+    let text_color = |color| {
+        if on_cursor {
+            rgb(theme.menu_text_selected, theme.background)
+        } else {
+            rgb(color, theme.background)
+        }
+    };
+    canvas.fill(
+        x,
+        y,
+        width,
+        height,
+        Style::new(text_color(theme.cell_text), background),
+    );
+
+    let middle_x = x + width / 2;
+    let middle_y = y + height / 2;
+
+    if let Some(n) = cell_state.number {
+        let conflicting = board.is_conflicting(index);
+        let wrong = settings.show_wrong && board.is_wrong(index);
+
+        let color = number_color(board, theme, index, settings.show_wrong);
+        let mut style = Style::new(text_color(color), background);
+        style.bold = cell_state.initial;
+        style.underline = settings.cell_cues && conflicting;
+        style.crossed_out = settings.cell_cues && wrong;
+
+        canvas.text(middle_x, middle_y, settings.digit_glyphs.glyph(n), style);
+    } else if cell_state.has_pencil() {
+        let style = Style::new(text_color(theme.cell_text_pencil), background);
+        if height < 3 {
+            // no room for the marks themselves
+            canvas.put(middle_x, middle_y, '·', style);
+        } else {
+            render_pencil_marks(app, cell_state, canvas, (x, y), style);
+        }
+    }
+
+    // the colour mark takes the left edge so the selection colour stays visible
+    if let Some(mark) = cell_state.color {
+        let color = rgb(theme.cell_marks[(mark - 1) as usize], theme.background);
+        for row in y..y + height {
+            canvas.put(x, row, '▌', Style::new(color, background));
+        }
+    }
+
+    // the same shapes as the window, a notch for highlighted cells and
+    // a solid or hollow square for emphasis, in the bottom right corner
+    if settings.cell_cues && height >= 3 {
+        let cue = match cell_state.selection {
+            CellSelection::Emphasized if board.mode == BoardMode::Pencil => Some('□'),
+            CellSelection::Emphasized => Some('■'),
+            CellSelection::Highlighted => Some('◢'),
+            CellSelection::None => None,
+        };
+        if let Some(cue) = cue {
+            let style = Style::new(text_color(theme.cell_line), background);
+            canvas.put(x + width - 1, y + height - 1, cue, style);
+        }
+    }
+}
+
+// corner marks fill the corners first like in the window, centre marks
+// are one line in the middle, cut short when they don't fit
+fn render_pencil_marks(
+    app: &App,
+    cell_state: &CellState,
+    canvas: &mut Canvas,
+    (x, y): (u16, u16),
+    style: Style,
+) {
+    let glyphs = app.settings.digit_glyphs;
+
+    let count = cell_state.corner.iter().flatten().count();
+    for (slot, n) in cell_state.corner.iter().flatten().enumerate() {
+        let (slot_x, slot_y) = corner_slot(slot, count);
+        canvas.text(
+            x + 1 + (slot_x * 2.0).round() as u16,
+            y + slot_y as u16,
+            glyphs.glyph(*n),
+            style,
+        );
+    }
+
+    let marks: Vec<&str> = cell_state
+        .pencil
+        .iter()
+        .flatten()
+        .map(|&n| glyphs.glyph(n))
+        .collect();
+    if marks.is_empty() {
+        return;
+    }
+
+    let text: String = if marks.len() > 5 {
+        marks[..4].concat() + "…"
+    } else {
+        marks.concat()
+    };
+    let start = x + 1 + (5 - text.chars().count() as u16) / 2;
+    canvas.text(start, y + 1, &text, style);
+}
+
+fn render_menu(app: &App, layout: &Layout, canvas: &mut Canvas) {
+    let board = &app.board;
+    let theme = &app.theme;
+    let color = |color| rgb(color, theme.background);
+    let normal = Style::new(color(theme.menu_text), color(theme.background));
+    let selected_background = match board.mode {
+        BoardMode::Normal => theme.menu_number_background_normal,
+        BoardMode::Pencil => theme.menu_number_background_pencil,
+    };
+    let selected = Style::new(color(theme.menu_text_selected), color(selected_background));
+
+    for span in layout.buttons.iter() {
+        let label = app.button_label(span.button);
+
+        let style = match span.button {
+            // the numbers become the palette while colouring
+            Button::Number(n) if board.color_mode => {
+                let mark = if (n as usize) <= theme.cell_marks.len() {
+                    theme.cell_marks[n as usize - 1]
+                } else {
+                    theme.cell_normal
+                };
+                let mut style = Style::new(color(theme.menu_text), color(mark));
+                style.bold = board.selected_color == n
+                    || (board.selected_color == 0 && n as usize > theme.cell_marks.len());
+                style.underline = style.bold;
+                style
+            }
+            Button::Number(n) if board.selected_number == Some(n) => selected,
+            Button::Number(n) if board.is_number_done(n) => {
+                Style::new(color(theme.cell_highlighted), color(theme.background))
+            }
+            Button::Pencil if board.mode == BoardMode::Pencil => selected,
+            Button::PencilLayer if board.pencil_layer == PencilLayer::Corner => selected,
+            Button::Color if board.color_mode => selected,
+            Button::Delete if board.delete_mode => Style::new(
+                color(theme.menu_text_selected),
+                color(theme.menu_delete_background),
+            ),
+            _ => normal,
+        };
+
+        canvas.fill(span.x, span.y, span.width, 1, style);
+        let label_width = label.chars().count() as u16;
+        let start = span.x + span.width.saturating_sub(label_width) / 2;
+        canvas.text(start, span.y, &label, style);
+
+        if board.color_mode && span.button == Button::Number(9) {
+            // the last slot is the eraser
+            canvas.text(start, span.y, "×", style);
+        }
+    }
+}
+
+fn render_status(app: &App, layout: &Layout, canvas: &mut Canvas, style: Style) {
+    let mut parts = vec![];
+
+    if app.new_game_prompt {
+        parts.push(app.text("new_game.prompt").to_string());
+    } else {
+        if app.settings.show_timer {
+            parts.push(format_time(app.board.elapsed));
+        }
+        if app.settings.show_mistakes {
+            parts.push(format!(
+                "{}: {}",
+                app.text("status.mistakes"),
+                app.board.mistakes
+            ));
+        }
+        if app.board.is_victory() {
+            parts.push(app.text("status.solved").to_string());
+        }
+    }
+
+    canvas.text(layout.left, layout.status_y, &parts.join("   "), style);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::junction;
+
+    #[test]
+    fn junctions() {
+        assert_eq!(junction(0, 0), '╔');
+        assert_eq!(junction(9, 0), '╗');
+        assert_eq!(junction(0, 9), '╚');
+        assert_eq!(junction(9, 9), '╝');
+        assert_eq!(junction(3, 0), '╦');
+        assert_eq!(junction(1, 0), '╤');
+        assert_eq!(junction(0, 1), '╟');
+        assert_eq!(junction(9, 4), '╢');
+        assert_eq!(junction(3, 3), '╬');
+        assert_eq!(junction(1, 3), '╪');
+        assert_eq!(junction(3, 1), '╫');
+        assert_eq!(junction(1, 1), '┼');
+    }
+}
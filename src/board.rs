@@ -3,10 +3,17 @@ use std::collections::HashSet;
 use crate::board_history::BoardHistory;
 use crate::cell_location::CellLocation;
 use crate::cell_state::{CellSelection, CellState};
-use crate::index::index_to_xy;
+use crate::constraints::Constraints;
+use crate::generate::{create_puzzle, generate_board};
+use crate::index::{index_to_xy, xy_to_index};
+use crate::move_log::MoveLog;
+use crate::save::{load, save};
+use crate::settings::{HighlightMode, InputStyle, Settings};
 use crate::{
-    is_legal_index, is_legal_number, BOX_INDEXES, COLUMN_INDEXES, DIGIT_COUNT, ROW_INDEXES,
+    is_legal_color, is_legal_index, is_legal_number, BOX_INDEXES, COLUMN_INDEXES, DIGIT_COUNT,
+    ROW_INDEXES,
 };
+use macroquad::rand;
 use serde_big_array::BigArray;
 use serde_derive::{Deserialize, Serialize};
 
@@ -16,6 +23,112 @@ pub enum BoardMode {
     Pencil,
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum PencilLayer {
+    #[default]
+    Center,
+    Corner,
+}
+
+// a way of marking cells around the current selection, the strategy is
+// picked in the settings and draw_board only ever sees the result
+trait Highlighter {
+    fn highlight(&self, board: &Board, selection: &mut [CellSelection; 81]);
+}
+
+struct FullHighlighter;
+struct SameDigitHighlighter;
+struct HousesHighlighter;
+struct CandidatesHighlighter;
+struct NoHighlighter;
+
+fn highlighter(mode: HighlightMode) -> &'static dyn Highlighter {
+    match mode {
+        HighlightMode::Full => &FullHighlighter,
+        HighlightMode::SameDigit => &SameDigitHighlighter,
+        HighlightMode::Houses => &HousesHighlighter,
+        HighlightMode::Candidates => &CandidatesHighlighter,
+        HighlightMode::None => &NoHighlighter,
+    }
+}
+
+// only marks cells that aren't emphasized already
+fn mark(selection: &mut [CellSelection; 81], index: usize) {
+    if selection[index] == CellSelection::None {
+        selection[index] = CellSelection::Highlighted;
+    }
+}
+
+// emphasizes every cell holding the selected number, returns their indexes
+fn emphasize_selected_number(board: &Board, selection: &mut [CellSelection; 81]) -> Vec<usize> {
+    let mut emphasized = vec![];
+
+    if board.selected_number.is_some() {
+        for (i, cell) in board.cell_state.iter().enumerate() {
+            if cell.number == board.selected_number {
+                selection[i] = CellSelection::Emphasized;
+                emphasized.push(i);
+            }
+        }
+    }
+
+    emphasized
+}
+
+impl Highlighter for FullHighlighter {
+    fn highlight(&self, board: &Board, selection: &mut [CellSelection; 81]) {
+        // only highlight numbers if the selected cell has a number
+        if board.selected_number.is_some() {
+            for (i, cell) in board.cell_state.iter().enumerate() {
+                if cell.has_number() {
+                    selection[i] = CellSelection::Highlighted;
+                }
+            }
+        }
+
+        for index in emphasize_selected_number(board, selection) {
+            for peer in board.peer_indexes(index) {
+                mark(selection, peer);
+            }
+        }
+    }
+}
+
+impl Highlighter for SameDigitHighlighter {
+    fn highlight(&self, board: &Board, selection: &mut [CellSelection; 81]) {
+        emphasize_selected_number(board, selection);
+    }
+}
+
+impl Highlighter for HousesHighlighter {
+    fn highlight(&self, board: &Board, selection: &mut [CellSelection; 81]) {
+        if let Some(index) = board.selected_index {
+            selection[index] = CellSelection::Emphasized;
+            for peer in board.peer_indexes(index) {
+                mark(selection, peer);
+            }
+        }
+    }
+}
+
+impl Highlighter for CandidatesHighlighter {
+    fn highlight(&self, board: &Board, selection: &mut [CellSelection; 81]) {
+        emphasize_selected_number(board, selection);
+
+        if let Some(number) = board.selected_number {
+            for index in 0..81 {
+                if board.is_candidate(index, number) {
+                    mark(selection, index);
+                }
+            }
+        }
+    }
+}
+
+impl Highlighter for NoHighlighter {
+    fn highlight(&self, _board: &Board, _selection: &mut [CellSelection; 81]) {}
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Board {
     pub history: BoardHistory,
@@ -32,6 +145,49 @@ pub struct Board {
     pub cell_size: f32,
     pub selected_index: Option<usize>,
     pub selected_number: Option<u8>,
+    #[serde(default)]
+    pub constraints: Constraints,
+    #[serde(default)]
+    pub cursor_index: Option<usize>,
+    #[serde(default)]
+    pub multi_selection: Vec<usize>,
+    #[serde(skip)]
+    pub drag_origin: Option<usize>,
+    // a press that places, erases or paints waits to be let go,
+    // it only selects if it turns into a drag
+    #[serde(skip)]
+    pressed: Option<usize>,
+    #[serde(default)]
+    pub color_mode: bool,
+    #[serde(default)]
+    pub selected_color: u8,
+    #[serde(default)]
+    pub pencil_layer: PencilLayer,
+    #[serde(default)]
+    pub mistakes: u32,
+    #[serde(default)]
+    pub elapsed: f64,
+    // the filled grid the puzzle was made from, empty for older saves
+    #[serde(default)]
+    pub solution: Vec<u8>,
+    // what the generator was seeded with, shared puzzles carry it along
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    // where the mistakes were made, a count for each box
+    #[serde(default)]
+    pub box_mistakes: [u32; 9],
+    #[serde(default)]
+    pub move_log: MoveLog,
+    #[serde(skip)]
+    pub settings: Settings,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Board {
@@ -49,9 +205,107 @@ impl Board {
             cell_size: 0.0,
             selected_index: None,
             selected_number: None,
+            constraints: Default::default(),
+            cursor_index: None,
+            multi_selection: vec![],
+            drag_origin: None,
+            pressed: None,
+            color_mode: false,
+            selected_color: 1,
+            pencil_layer: PencilLayer::Center,
+            mistakes: 0,
+            elapsed: 0.0,
+            solution: vec![],
+            seed: None,
+            difficulty: None,
+            box_mistakes: [0; 9],
+            move_log: Default::default(),
+            settings: Default::default(),
         }
     }
 
+    pub fn generate(difficulty: u8, constraints: Constraints) -> Self {
+        let seed = ((rand::rand() as u64) << 32) | rand::rand() as u64;
+        Self::generate_seeded(difficulty, constraints, seed)
+    }
+
+    // the same seed always makes the same puzzle
+    pub fn generate_seeded(difficulty: u8, constraints: Constraints, seed: u64) -> Self {
+        let rng = rand::RandGenerator::new();
+        rng.srand(seed);
+
+        let mut board = Board::new();
+        board.constraints = constraints;
+        board.seed = Some(seed);
+        board.difficulty = Some(difficulty);
+
+        let mut board_gen = [[0; 9]; 9];
+        generate_board(&mut board_gen, &constraints, &rng);
+        board.solution = board_gen.iter().flatten().copied().collect();
+        create_puzzle(&mut board_gen, difficulty, &constraints, &rng);
+        for (y, row) in board_gen.iter().enumerate() {
+            for (x, col) in row.iter().enumerate() {
+                board.cell_state[xy_to_index(x, y, 9)].set_initial_number(*col);
+            }
+        }
+        board.update_number_count();
+        board.start_move_log();
+
+        board
+    }
+
+    // every front-end keeps the current game under the same key
+    pub fn load() -> Option<Self> {
+        load("board").and_then(|board_str| serde_json::from_str::<Board>(&board_str).ok())
+    }
+
+    // the moves made since the last save go in the log first
+    pub fn save(&mut self) {
+        self.record_moves();
+
+        let save_data = serde_json::to_string(self).unwrap();
+        save("board", save_data.as_str());
+    }
+
+    pub fn start_move_log(&mut self) {
+        let mut move_log = MoveLog::default();
+        move_log.start(self);
+        self.move_log = move_log;
+    }
+
+    pub fn record_moves(&mut self) {
+        let mut move_log = std::mem::take(&mut self.move_log);
+        move_log.record(self);
+        self.move_log = move_log;
+    }
+
+    // back to the givens, to play the same puzzle again
+    pub fn restart(&mut self) {
+        for cell in self.cell_state.iter_mut() {
+            let given = cell.number.filter(|_| cell.initial);
+            *cell = CellState::new();
+            if let Some(number) = given {
+                cell.set_initial_number(number);
+            }
+        }
+
+        self.history = BoardHistory::new();
+        self.mode = BoardMode::Normal;
+        self.delete_mode = false;
+        self.color_mode = false;
+        self.selected_index = None;
+        self.cursor_index = None;
+        self.multi_selection.clear();
+        self.drag_origin = None;
+        self.pressed = None;
+        self.mistakes = 0;
+        self.box_mistakes = [0; 9];
+        self.elapsed = 0.0;
+        self.update_number_count();
+        self.highlight();
+        self.start_move_log();
+    }
+
     pub fn toggle_pencil_mode(&mut self) {
         match self.mode {
             BoardMode::Normal => self.mode = BoardMode::Pencil,
@@ -59,6 +313,87 @@ impl Board {
         }
     }
 
+    pub fn toggle_pencil_layer(&mut self) {
+        match self.pencil_layer {
+            PencilLayer::Center => self.pencil_layer = PencilLayer::Corner,
+            PencilLayer::Corner => self.pencil_layer = PencilLayer::Center,
+        }
+    }
+
+    fn has_layer_pencil(&self, index: usize, number: u8) -> bool {
+        match self.pencil_layer {
+            PencilLayer::Center => self.cell_state[index].has_this_pencil(number),
+            PencilLayer::Corner => self.cell_state[index].has_this_corner(number),
+        }
+    }
+
+    fn set_layer_pencil(&mut self, index: usize, number: u8) {
+        match self.pencil_layer {
+            PencilLayer::Center => self.cell_state[index].set_pencil(number),
+            PencilLayer::Corner => self.cell_state[index].set_corner(number),
+        }
+    }
+
+    fn remove_layer_pencil(&mut self, index: usize, number: u8) {
+        match self.pencil_layer {
+            PencilLayer::Center => self.cell_state[index].remove_pencil(number),
+            PencilLayer::Corner => self.cell_state[index].remove_corner(number),
+        }
+    }
+
+    pub fn toggle_color_mode(&mut self) {
+        self.color_mode = !self.color_mode;
+    }
+
+    // 0 is the eraser, everything else is a colour of the palette
+    pub fn set_selected_color(&mut self, color: u8) {
+        if color == 0 || is_legal_color(color) {
+            self.selected_color = color;
+        }
+    }
+
+    fn paint(&mut self, index: usize) {
+        let cell = &mut self.cell_state[index];
+        if self.selected_color == 0 || cell.color == Some(self.selected_color) {
+            cell.clear_color();
+        } else {
+            cell.set_color(self.selected_color);
+        }
+    }
+
+    pub fn paint_cell(&mut self, index: usize) {
+        self.add_undo_point();
+        self.paint(index);
+    }
+
+    pub fn paint_cursor_cell(&mut self) {
+        if let Some(index) = self.cursor_index {
+            self.paint_cell(index);
+        }
+    }
+
+    pub fn paint_multi_selection(&mut self) {
+        if self.multi_selection.is_empty() {
+            return;
+        }
+
+        self.add_undo_point();
+        for index in self.multi_selection.clone() {
+            self.paint(index);
+        }
+    }
+
+    pub fn clear_colors(&mut self) {
+        if !self.cell_state.iter().any(|cell| cell.has_color()) {
+            return;
+        }
+
+        self.add_undo_point();
+        for cell in self.cell_state.iter_mut() {
+            cell.clear_color();
+        }
+    }
+
     pub fn toggle_delete_mode(&mut self) {
         self.delete_mode = !self.delete_mode;
     }
@@ -116,7 +451,9 @@ impl Board {
                 return false;
             }
         }
-        true
+
+        // permissive entry lets a full board still hold conflicts
+        self.is_valid()
     }
 
     pub fn is_number_done(&self, number: u8) -> bool {
@@ -161,57 +498,295 @@ impl Board {
         cell.set_number(number.unwrap())
     }
 
-    pub fn click(&mut self, x: f32, y: f32) {
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<usize> {
         if (self.portrait && y >= self.board_size + self.game_padding)
             || (!self.portrait && x >= self.board_size + self.game_padding)
         {
-            return;
+            return None;
         }
 
-        let mut clicked_index: Option<usize> = None;
-
         // perform a click on each cell to see which one
         // gets selected
         for i in 0..81 {
             let loc = &self.cell_location[i];
             let clicked = loc.click(x, y);
             if clicked {
-                clicked_index = Some(i);
-                break;
+                return Some(i);
             }
         }
 
-        // no cell was clicked
-        if clicked_index.is_none() {
-            self.selected_index = None;
+        None
+    }
+
+    pub fn click(&mut self, x: f32, y: f32) {
+        if (self.portrait && y >= self.board_size + self.game_padding)
+            || (!self.portrait && x >= self.board_size + self.game_padding)
+        {
+            return;
+        }
+
+        match self.cell_at(x, y) {
+            Some(index) => self.click_cell(index),
+            // no cell was clicked
+            None => self.selected_index = None,
+        }
+    }
+
+    // a click that already knows its cell, the terminal hit-tests on its own
+    pub fn click_cell(&mut self, index: usize) {
+        // moving on to another cell before letting go selects both
+        self.drag_origin = Some(index);
+
+        if self.settings.input_style == InputStyle::CellFirst
+            && !self.delete_mode
+            && !self.color_mode
+        {
+            self.select_cell(index);
             return;
         }
 
+        self.pressed = Some(index);
+    }
+
+    // the press let go on the cell it started on
+    fn release_cell(&mut self, index: usize) {
+        if self.color_mode {
+            self.paint_cell(index);
+            return;
+        }
+
+        // the mouse takes over from the keyboard cursor
+        self.cursor_index = None;
+
+        self.activate_cell(index, self.mode);
+    }
+
+    // cell-first input, the selected cell waits for a number
+    // and gets the same focus outline as the keyboard cursor
+    pub fn select_cell(&mut self, index: usize) {
+        self.selected_index = Some(index);
+        self.cursor_index = Some(index);
+
+        if let Some(number) = self.cell_state[index].number {
+            self.set_selected_number(number);
+        }
+        self.highlight();
+    }
+
+    // shift/ctrl click, adds the cell to the multi selection or removes it
+    pub fn multi_select_click(&mut self, x: f32, y: f32) {
+        if let Some(index) = self.cell_at(x, y) {
+            self.multi_select_cell(index);
+        }
+    }
+
+    pub fn multi_select_cell(&mut self, index: usize) {
+        if self.multi_selection.is_empty() {
+            if let Some(selected) = self.cursor_index {
+                if selected != index {
+                    self.multi_selection.push(selected);
+                }
+            }
+        }
+
+        if let Some(position) = self.multi_selection.iter().position(|&i| i == index) {
+            self.multi_selection.remove(position);
+        } else {
+            self.multi_selection.push(index);
+        }
+
+        self.drag_origin = Some(index);
+    }
+
+    pub fn drag(&mut self, x: f32, y: f32) {
+        if let Some(index) = self.cell_at(x, y) {
+            self.drag_cell(index);
+        }
+    }
+
+    pub fn drag_cell(&mut self, index: usize) {
+        let origin = match self.drag_origin {
+            Some(origin) => origin,
+            None => return,
+        };
+
+        if index == origin && self.multi_selection.is_empty() {
+            return;
+        }
+        self.pressed = None;
+
+        if !self.multi_selection.contains(&origin) {
+            self.multi_selection.push(origin);
+        }
+
+        if !self.multi_selection.contains(&index) {
+            self.multi_selection.push(index);
+        }
+    }
+
+    pub fn stop_drag(&mut self) {
+        self.drag_origin = None;
+        if let Some(index) = self.pressed.take() {
+            self.release_cell(index);
+        }
+    }
+
+    pub fn has_multi_selection(&self) -> bool {
+        !self.multi_selection.is_empty()
+    }
+
+    pub fn clear_multi_selection(&mut self) {
+        self.multi_selection.clear();
+    }
+
+    // adds the pencil mark to every selected cell that can take it, or
+    // removes it if all of those already have it, as a single undo point
+    pub fn toggle_multi_selection_pencil(&mut self, number: u8) {
+        if !is_legal_number(number) {
+            return;
+        }
+
+        // cells with a number, or where the number can't go, are left alone
+        let targets: Vec<usize> = self
+            .multi_selection
+            .iter()
+            .copied()
+            .filter(|&i| self.has_layer_pencil(i, number) || self.is_candidate(i, number))
+            .filter(|&i| !self.cell_state[i].has_number())
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        let remove = targets.iter().all(|&i| self.has_layer_pencil(i, number));
+
+        self.add_undo_point();
+        for index in targets {
+            if remove {
+                self.remove_layer_pencil(index, number);
+            } else {
+                self.set_layer_pencil(index, number);
+            }
+        }
+    }
+
+    pub fn delete_multi_selection(&mut self) {
+        let targets: Vec<usize> = self
+            .multi_selection
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let cell = &self.cell_state[i];
+                !cell.has_initial_number() && (cell.has_number() || cell.has_pencil())
+            })
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        self.add_undo_point();
+        for index in targets {
+            self.cell_state[index].clear_number();
+            self.cell_state[index].clear_pencil();
+        }
+        self.update_number_count();
+        self.highlight();
+    }
+
+    // every cell sharing a house or a constraint with this one
+    fn peer_indexes(&self, index: usize) -> Vec<usize> {
+        let houses = BOX_INDEXES
+            .iter()
+            .chain(ROW_INDEXES.iter())
+            .chain(COLUMN_INDEXES.iter())
+            .filter(|house| house.contains(&index));
+
+        houses
+            .flatten()
+            .copied()
+            .filter(|&i| i != index)
+            .chain(self.constraints.neighbour_indexes(index))
+            .collect()
+    }
+
+    // whether the number could still go in this cell without
+    // breaking a house or a constraint
+    pub fn is_candidate(&self, index: usize, number: u8) -> bool {
+        if self.cell_state[index].has_number() {
+            return false;
+        }
+
+        !self
+            .peer_indexes(index)
+            .iter()
+            .any(|&i| self.cell_state[i].is_number(number))
+    }
+
+    pub fn is_conflicting(&self, index: usize) -> bool {
+        match self.cell_state[index].number {
+            Some(number) => self
+                .peer_indexes(index)
+                .iter()
+                .any(|&i| self.cell_state[i].is_number(number)),
+            None => false,
+        }
+    }
+
+    // an entered number that doesn't match the solution, givens are never wrong
+    pub fn is_wrong(&self, index: usize) -> bool {
+        let cell = &self.cell_state[index];
+        match (cell.number, self.solution.get(index)) {
+            (Some(number), Some(&solution)) => !cell.initial && number != solution,
+            _ => false,
+        }
+    }
+
+    pub fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let cursor = match self.cursor_index {
+            Some(index) => index,
+            None => {
+                // the first key press only shows the cursor
+                self.cursor_index = Some(self.selected_index.unwrap_or(40));
+                return;
+            }
+        };
+
+        let (x, y) = index_to_xy(cursor, DIGIT_COUNT as usize);
+        let x = (x as i32 + dx).rem_euclid(DIGIT_COUNT as i32) as usize;
+        let y = (y as i32 + dy).rem_euclid(DIGIT_COUNT as i32) as usize;
+
+        self.cursor_index = Some(xy_to_index(x, y, DIGIT_COUNT as usize));
+    }
+
+    pub fn enter_number(&mut self, pencil: bool) {
+        if let Some(index) = self.cursor_index {
+            let mode = if pencil { BoardMode::Pencil } else { self.mode };
+
+            self.activate_cell(index, mode);
+        }
+    }
+
+    pub fn delete_cursor_cell(&mut self) {
+        if let Some(index) = self.cursor_index {
+            self.delete_cell(index);
+        }
+    }
+
+    fn activate_cell(&mut self, index: usize, mode: BoardMode) {
+        let clicked_index = Some(index);
+
         // you can't change initial numbers
-        let cell = &self.cell_state[clicked_index.unwrap()];
+        let cell = &self.cell_state[index];
         if cell.has_initial_number() {
             return;
         }
 
         if self.delete_mode {
-            if cell.has_number() && !cell.has_initial_number() {
-                self.add_undo_point();
-                self.selected_index = clicked_index;
-                self.selected_number = self.cell_state[self.selected_index.unwrap()].number;
-                self.cell_state[self.selected_index.unwrap()].clear_number();
-                self.update_number_count();
-                self.highlight();
-            } else if cell.has_pencil() {
-                self.add_undo_point();
-                self.selected_index = clicked_index;
-                self.cell_state[self.selected_index.unwrap()].clear_pencil();
-                self.highlight();
-            }
-
+            self.delete_cell(index);
             return;
         }
 
-        if self.mode == BoardMode::Normal {
+        if mode == BoardMode::Normal {
             if !self.can_insert(clicked_index, self.selected_number) {
                 return;
             }
@@ -223,43 +798,73 @@ impl Board {
                 return;
             }
 
-            if !self.is_valid() {
-                self.undo();
-                return;
+            if self.is_conflicting(index) {
+                self.mistakes += 1;
+                if let Some(box_index) = BOX_INDEXES.iter().position(|cells| cells.contains(&index))
+                {
+                    self.box_mistakes[box_index] += 1;
+                }
+                if self.settings.strict_entry {
+                    self.undo();
+                    return;
+                }
             }
 
             self.highlight();
-            self.clear_pencil(self.selected_number.unwrap());
+            if self.settings.auto_remove_pencil {
+                self.clear_pencil_around(index, self.selected_number.unwrap());
+            }
             self.update_number_count();
-        } else if self.mode == BoardMode::Pencil {
+        } else if mode == BoardMode::Pencil {
             if self.selected_number.is_none() || cell.has_number() {
                 return;
             }
 
             let pencil_number = self.selected_number.unwrap();
-            if cell.has_this_pencil(pencil_number) {
+            if self.has_layer_pencil(index, pencil_number) {
                 self.add_undo_point();
                 self.selected_index = clicked_index;
 
-                self.cell_state[self.selected_index.unwrap()].remove_pencil(pencil_number);
-            } else if cell.selection == CellSelection::None {
+                self.remove_layer_pencil(index, pencil_number);
+            } else if self.is_candidate(index, pencil_number) {
                 self.add_undo_point();
                 self.selected_index = clicked_index;
 
-                self.cell_state[self.selected_index.unwrap()].set_pencil(pencil_number);
+                self.set_layer_pencil(index, pencil_number);
             }
         }
     }
 
-    fn clear_pencil(&mut self, number: u8) {
-        for cell in self.cell_state.iter_mut() {
-            if cell.has_number() || cell.selection == CellSelection::Emphasized {
+    fn delete_cell(&mut self, index: usize) {
+        let cell = &self.cell_state[index];
+        if cell.has_initial_number() {
+            return;
+        }
+
+        if cell.has_number() {
+            self.add_undo_point();
+            self.selected_index = Some(index);
+            self.selected_number = self.cell_state[index].number;
+            self.cell_state[index].clear_number();
+            self.update_number_count();
+            self.highlight();
+        } else if cell.has_pencil() {
+            self.add_undo_point();
+            self.selected_index = Some(index);
+            self.cell_state[index].clear_pencil();
+            self.highlight();
+        }
+    }
+
+    fn clear_pencil_around(&mut self, index: usize, number: u8) {
+        for peer in self.peer_indexes(index) {
+            let cell = &mut self.cell_state[peer];
+            if cell.has_number() {
                 continue;
             }
 
-            if cell.selection == CellSelection::Highlighted {
-                cell.remove_pencil(number);
-            }
+            cell.remove_pencil(number);
+            cell.remove_corner(number);
         }
     }
 
@@ -282,6 +887,22 @@ impl Board {
             }
         }
 
+        self.is_constraints_valid()
+    }
+
+    fn is_constraints_valid(&self) -> bool {
+        for (index, cell) in self.cell_state.iter().enumerate() {
+            if cell.number.is_none() {
+                continue;
+            }
+
+            for neighbour in self.constraints.neighbour_indexes(index) {
+                if self.cell_state[neighbour].number == cell.number {
+                    return false;
+                }
+            }
+        }
+
         true
     }
 
@@ -301,41 +922,13 @@ impl Board {
     }
 
     pub fn highlight(&mut self) {
-        self.clear_cell_selection();
+        let mut selection = [CellSelection::None; 81];
+        highlighter(self.settings.highlight_mode).highlight(self, &mut selection);
 
-        let mut highlight_list = vec![];
-
-        // only highlight numbers if the selected cell has a number
-        if self.selected_number.is_some() {
-            for (i, cell) in self.cell_state.iter_mut().enumerate() {
-                if cell.has_number() {
-                    cell.selection = CellSelection::Highlighted;
-                }
-                if cell.number == self.selected_number {
-                    cell.selection = CellSelection::Emphasized;
-                    highlight_list.push(i);
-                }
-            }
-        }
-
-        for index in highlight_list {
-            self.highlight_areas(BOX_INDEXES, index);
-            self.highlight_areas(ROW_INDEXES, index);
-            self.highlight_areas(COLUMN_INDEXES, index);
-        }
-    }
-
-    fn highlight_areas(&mut self, area: &[[usize; 9]; 9], selected_index: usize) {
-        for index_row in area.iter() {
-            if index_row.contains(&selected_index) {
-                for index in index_row.iter() {
-                    let selection = &mut self.cell_state[*index].selection;
-                    if *selection == CellSelection::None {
-                        *selection = CellSelection::Highlighted;
-                    }
-                }
-
-                break;
+        self.clear_cell_selection();
+        for (cell, selection) in self.cell_state.iter_mut().zip(selection) {
+            if selection != CellSelection::None {
+                cell.selection = selection;
             }
         }
     }
@@ -364,6 +957,506 @@ impl Board {
 
 #[cfg(test)]
 mod tests {
+    use super::{Board, PencilLayer};
+    use crate::cell_state::CellSelection;
+    use crate::constraints::Constraints;
+    use crate::settings::{HighlightMode, InputStyle};
+
     #[test]
     fn test_new_board() {}
+
+    #[test]
+    fn test_constraints_valid() {
+        let mut board = Board::new();
+        board.cell_state[0].set_number(1);
+        board.cell_state[10].set_number(1);
+        assert!(!board.is_valid());
+
+        board.cell_state[10].clear_number();
+        board.cell_state[20].set_number(2);
+        board.cell_state[30].set_number(2);
+        assert!(board.is_valid());
+
+        board.constraints = Constraints {
+            anti_knight: false,
+            anti_king: true,
+        };
+        assert!(!board.is_valid());
+
+        board.constraints = Constraints {
+            anti_knight: true,
+            anti_king: false,
+        };
+        assert!(board.is_valid());
+
+        board.cell_state[30].clear_number();
+        board.cell_state[31].set_number(2);
+        assert!(!board.is_valid());
+
+        board.constraints = Constraints {
+            anti_knight: false,
+            anti_king: true,
+        };
+        assert!(board.is_valid());
+    }
+
+    #[test]
+    fn test_highlight_constraints() {
+        let mut board = Board::new();
+        board.constraints = Constraints {
+            anti_knight: true,
+            anti_king: false,
+        };
+        board.cell_state[40].set_number(5);
+        board.selected_number = Some(5);
+        board.highlight();
+
+        assert_eq!(board.cell_state[40].selection, CellSelection::Emphasized);
+        assert_eq!(board.cell_state[21].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[59].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[0].selection, CellSelection::None);
+    }
+
+    #[test]
+    fn test_move_cursor() {
+        let mut board = Board::new();
+        assert_eq!(board.cursor_index, None);
+
+        board.move_cursor(1, 0);
+        assert_eq!(board.cursor_index, Some(40));
+
+        board.move_cursor(1, 0);
+        assert_eq!(board.cursor_index, Some(41));
+
+        board.move_cursor(0, -1);
+        assert_eq!(board.cursor_index, Some(32));
+
+        board.cursor_index = Some(0);
+        board.move_cursor(-1, 0);
+        assert_eq!(board.cursor_index, Some(8));
+
+        board.move_cursor(0, -1);
+        assert_eq!(board.cursor_index, Some(80));
+
+        board.move_cursor(0, 1);
+        assert_eq!(board.cursor_index, Some(8));
+    }
+
+    #[test]
+    fn test_enter_number() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(5);
+        board.set_selected_number(5);
+        board.highlight();
+
+        // no cursor, nothing happens
+        board.enter_number(false);
+        assert!(board
+            .cell_state
+            .iter()
+            .skip(1)
+            .all(|cell| !cell.has_number()));
+
+        // initial numbers can't be changed
+        board.cursor_index = Some(0);
+        board.set_selected_number(4);
+        board.enter_number(false);
+        assert!(board.cell_state[0].is_number(5));
+
+        board.cursor_index = Some(40);
+        board.enter_number(false);
+        assert!(board.cell_state[40].is_number(4));
+
+        // pencil marks through the modifier
+        board.cursor_index = Some(80);
+        board.set_selected_number(1);
+        board.highlight();
+        board.enter_number(true);
+        assert!(!board.cell_state[80].has_number());
+        assert!(board.cell_state[80].has_this_pencil(1));
+
+        board.enter_number(true);
+        assert!(!board.cell_state[80].has_pencil());
+
+        // conflicting numbers are rejected
+        board.cursor_index = Some(1);
+        board.set_selected_number(5);
+        board.enter_number(false);
+        assert!(!board.cell_state[1].has_number());
+
+        board.cursor_index = Some(40);
+        board.delete_cursor_cell();
+        assert!(!board.cell_state[40].has_number());
+        assert_eq!(board.selected_number, Some(4));
+
+        board.undo();
+        assert!(board.cell_state[40].is_number(4));
+    }
+
+    #[test]
+    fn test_cell_first() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(5);
+        board.settings.toggle_input_style();
+        assert_eq!(board.settings.input_style, InputStyle::CellFirst);
+
+        board.select_cell(0);
+        assert_eq!(board.selected_index, Some(0));
+        assert_eq!(board.selected_number, Some(5));
+        assert_eq!(board.cell_state[0].selection, CellSelection::Emphasized);
+
+        board.select_cell(40);
+        assert_eq!(board.selected_index, Some(40));
+        assert_eq!(board.cursor_index, Some(40));
+
+        board.set_selected_number(3);
+        board.highlight();
+        board.enter_number(false);
+        assert!(board.cell_state[40].is_number(3));
+
+        board.select_cell(80);
+        board.toggle_pencil_mode();
+        board.set_selected_number(7);
+        board.highlight();
+        board.enter_number(false);
+        assert!(board.cell_state[80].has_this_pencil(7));
+
+        board.undo();
+        assert!(!board.cell_state[80].has_pencil());
+        assert!(board.cell_state[40].is_number(3));
+
+        board.settings.toggle_input_style();
+        assert_eq!(board.settings.input_style, InputStyle::NumberFirst);
+    }
+
+    #[test]
+    fn test_generate_seeded() {
+        let first = Board::generate_seeded(2, Default::default(), 7);
+        // other random numbers drawn in between don't change the puzzle
+        macroquad::rand::srand(99);
+        macroquad::rand::rand();
+        let second = Board::generate_seeded(2, Default::default(), 7);
+
+        assert_eq!(first.solution, second.solution);
+        let givens = |board: &Board| board.cell_state.map(|cell| cell.number);
+        assert_eq!(givens(&first), givens(&second));
+        assert_eq!(first.seed, Some(7));
+    }
+
+    #[test]
+    fn test_is_candidate() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.cell_state[80].set_initial_number(2);
+
+        assert!(!board.is_candidate(0, 3));
+        assert!(!board.is_candidate(8, 1));
+        assert!(!board.is_candidate(72, 1));
+        assert!(!board.is_candidate(20, 1));
+        assert!(board.is_candidate(40, 1));
+        assert!(!board.is_candidate(8, 2));
+
+        board.constraints = Constraints {
+            anti_knight: true,
+            anti_king: false,
+        };
+        assert!(board.is_candidate(40, 1));
+        assert!(!board.is_candidate(61, 2));
+    }
+
+    #[test]
+    fn test_multi_selection() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.cell_state[30].set_number(4);
+        board.multi_selection = vec![0, 1, 2, 40, 30];
+        assert!(board.has_multi_selection());
+
+        board.toggle_multi_selection_pencil(1);
+        assert!(!board.cell_state[1].has_this_pencil(1));
+        assert!(!board.cell_state[2].has_this_pencil(1));
+        assert!(board.cell_state[40].has_this_pencil(1));
+        assert!(!board.cell_state[30].has_pencil());
+
+        // cells 1 and 2 can't take a 1, the mark still comes off 40
+        board.toggle_multi_selection_pencil(1);
+        assert!(!board.cell_state[40].has_this_pencil(1));
+        board.undo();
+        assert!(board.cell_state[40].has_this_pencil(1));
+
+        // only numbers selected, nothing changes and there's nothing to
+        // undo but the first 1
+        board.multi_selection = vec![0, 30];
+        board.toggle_multi_selection_pencil(4);
+        board.undo();
+        assert!(!board.cell_state[40].has_this_pencil(1));
+        board.multi_selection = vec![0, 1, 2, 40, 30];
+
+        board.toggle_multi_selection_pencil(5);
+        assert!(board.cell_state[1].has_this_pencil(5));
+        assert!(board.cell_state[2].has_this_pencil(5));
+        assert!(board.cell_state[40].has_this_pencil(5));
+
+        board.toggle_multi_selection_pencil(5);
+        assert!(!board.cell_state[1].has_this_pencil(5));
+        assert!(!board.cell_state[40].has_this_pencil(5));
+
+        board.undo();
+        assert!(board.cell_state[1].has_this_pencil(5));
+        assert!(board.cell_state[40].has_this_pencil(5));
+
+        board.delete_multi_selection();
+        assert!(!board.cell_state[1].has_pencil());
+        assert!(!board.cell_state[40].has_pencil());
+        assert!(!board.cell_state[30].has_number());
+        assert!(board.cell_state[0].is_number(1));
+
+        board.undo();
+        assert!(board.cell_state[1].has_this_pencil(5));
+        assert!(board.cell_state[30].is_number(4));
+
+        board.clear_multi_selection();
+        assert!(!board.has_multi_selection());
+
+        // a press in number-first input, then a drag into the next cell
+        // selects both and places nothing
+        board.set_selected_number(7);
+        board.click_cell(50);
+        board.drag_cell(50);
+        assert!(!board.has_multi_selection());
+        board.drag_cell(51);
+        assert_eq!(board.multi_selection, vec![50, 51]);
+        board.stop_drag();
+        assert!(!board.cell_state[50].has_number());
+        board.drag_cell(52);
+        assert_eq!(board.multi_selection, vec![50, 51]);
+
+        // let go where it started, the number goes in then
+        board.clear_multi_selection();
+        board.click_cell(50);
+        assert!(!board.cell_state[50].has_number());
+        board.stop_drag();
+        assert!(board.cell_state[50].is_number(7));
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.toggle_color_mode();
+        assert!(board.color_mode);
+
+        board.set_selected_color(3);
+        board.paint_cell(0);
+        assert_eq!(board.cell_state[0].color, Some(3));
+
+        // painting with the same colour again removes it
+        board.paint_cell(0);
+        assert_eq!(board.cell_state[0].color, None);
+
+        board.undo();
+        assert_eq!(board.cell_state[0].color, Some(3));
+
+        board.set_selected_color(42);
+        assert_eq!(board.selected_color, 3);
+
+        board.multi_selection = vec![1, 2, 3];
+        board.set_selected_color(5);
+        board.paint_multi_selection();
+        assert_eq!(board.cell_state[2].color, Some(5));
+        assert_eq!(board.cell_state[3].color, Some(5));
+
+        board.set_selected_color(0);
+        board.cursor_index = Some(2);
+        board.paint_cursor_cell();
+        assert_eq!(board.cell_state[2].color, None);
+        assert_eq!(board.cell_state[3].color, Some(5));
+
+        board.clear_colors();
+        assert!(board.cell_state.iter().all(|cell| !cell.has_color()));
+
+        board.undo();
+        assert_eq!(board.cell_state[0].color, Some(3));
+        assert_eq!(board.cell_state[3].color, Some(5));
+    }
+
+    #[test]
+    fn test_pencil_layers() {
+        let mut board = Board::new();
+        board.cursor_index = Some(0);
+        board.set_selected_number(4);
+        board.highlight();
+        board.enter_number(true);
+        assert!(board.cell_state[0].has_this_pencil(4));
+        assert!(!board.cell_state[0].has_this_corner(4));
+
+        board.toggle_pencil_layer();
+        assert_eq!(board.pencil_layer, PencilLayer::Corner);
+        board.enter_number(true);
+        assert!(board.cell_state[0].has_this_pencil(4));
+        assert!(board.cell_state[0].has_this_corner(4));
+
+        board.cursor_index = Some(1);
+        board.enter_number(true);
+        assert!(board.cell_state[1].has_this_corner(4));
+        assert!(!board.cell_state[1].has_center());
+
+        board.enter_number(true);
+        assert!(!board.cell_state[1].has_corner());
+
+        board.undo();
+        assert!(board.cell_state[1].has_this_corner(4));
+
+        // placing the number clears both layers in its houses
+        board.cursor_index = Some(40);
+        board.enter_number(false);
+        assert!(board.cell_state[40].is_number(4));
+        assert!(board.cell_state[0].has_this_corner(4));
+
+        board.delete_cursor_cell();
+        board.cursor_index = Some(2);
+        board.enter_number(false);
+        assert!(!board.cell_state[0].has_pencil());
+        assert!(!board.cell_state[1].has_pencil());
+    }
+
+    #[test]
+    fn test_permissive_entry() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.cursor_index = Some(1);
+        board.set_selected_number(1);
+        board.highlight();
+
+        board.enter_number(false);
+        assert!(!board.cell_state[1].has_number());
+        assert_eq!(board.mistakes, 1);
+
+        board.settings.strict_entry = false;
+        board.enter_number(false);
+        assert!(board.cell_state[1].is_number(1));
+        assert!(board.is_conflicting(1));
+        assert!(board.is_conflicting(0));
+        assert!(!board.is_conflicting(2));
+        assert_eq!(board.mistakes, 2);
+        assert_eq!(board.box_mistakes[0], 2);
+
+        board.cursor_index = Some(1);
+        board.delete_cursor_cell();
+        assert!(!board.is_conflicting(0));
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.cell_state[1].set_number(2);
+        board.cell_state[2].set_pencil(3);
+        board.cell_state[0].set_color(4);
+        board.mistakes = 3;
+        board.box_mistakes[0] = 3;
+        board.elapsed = 60.0;
+        board.update_number_count();
+
+        board.restart();
+        assert!(board.cell_state[0].initial);
+        assert!(board.cell_state[0].is_number(1));
+        assert_eq!(board.cell_state[0].color, None);
+        assert!(!board.cell_state[1].has_number());
+        assert!(!board.cell_state[2].has_pencil());
+        assert_eq!(board.mistakes, 0);
+        assert_eq!(board.box_mistakes, [0; 9]);
+        assert_eq!(board.elapsed, 0.0);
+        assert_eq!(board.number_count[1], 0);
+    }
+
+    #[test]
+    fn test_auto_remove_pencil() {
+        let mut board = Board::new();
+        board.cell_state[1].set_pencil(3);
+        board.cell_state[2].set_pencil(3);
+        board.settings.auto_remove_pencil = false;
+        board.cursor_index = Some(0);
+        board.set_selected_number(3);
+        board.highlight();
+        board.enter_number(false);
+        assert!(board.cell_state[1].has_this_pencil(3));
+
+        board.settings.auto_remove_pencil = true;
+        board.cursor_index = Some(80);
+        board.enter_number(false);
+        assert!(board.cell_state[1].has_this_pencil(3));
+        board.cursor_index = Some(0);
+        board.delete_cursor_cell();
+        board.enter_number(false);
+        assert!(!board.cell_state[1].has_pencil());
+        assert!(!board.cell_state[2].has_pencil());
+    }
+
+    #[test]
+    fn test_highlight_modes() {
+        let mut board = Board::new();
+        board.cell_state[0].set_number(5);
+        board.cell_state[80].set_number(3);
+        board.selected_index = Some(0);
+        board.selected_number = Some(5);
+
+        board.settings.highlight_mode = HighlightMode::SameDigit;
+        board.highlight();
+        assert_eq!(board.cell_state[0].selection, CellSelection::Emphasized);
+        assert_eq!(board.cell_state[1].selection, CellSelection::None);
+        assert_eq!(board.cell_state[80].selection, CellSelection::None);
+
+        board.settings.highlight_mode = HighlightMode::Houses;
+        board.highlight();
+        assert_eq!(board.cell_state[0].selection, CellSelection::Emphasized);
+        assert_eq!(board.cell_state[8].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[72].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[20].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[40].selection, CellSelection::None);
+
+        // cells where a 5 can still go, the row, column and box of the 5 are out
+        board.settings.highlight_mode = HighlightMode::Candidates;
+        board.highlight();
+        assert_eq!(board.cell_state[0].selection, CellSelection::Emphasized);
+        assert_eq!(board.cell_state[8].selection, CellSelection::None);
+        assert_eq!(board.cell_state[40].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[80].selection, CellSelection::None);
+
+        board.settings.highlight_mode = HighlightMode::Full;
+        board.highlight();
+        assert_eq!(board.cell_state[8].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[80].selection, CellSelection::Highlighted);
+        assert_eq!(board.cell_state[40].selection, CellSelection::None);
+    }
+
+    #[test]
+    fn test_is_wrong() {
+        let mut board = Board::new();
+        board.cell_state[0].set_number(5);
+        assert!(!board.is_wrong(0));
+
+        board.solution = vec![4; 81];
+        assert!(board.is_wrong(0));
+        assert!(!board.is_wrong(1));
+
+        board.cell_state[1].set_initial_number(5);
+        assert!(!board.is_wrong(1));
+
+        board.cell_state[0].set_number(4);
+        assert!(!board.is_wrong(0));
+    }
+
+    #[test]
+    fn test_highlight_none() {
+        let mut board = Board::new();
+        board.cell_state[40].set_number(5);
+        board.selected_number = Some(5);
+        board.settings.highlight_mode = HighlightMode::None;
+        board.highlight();
+        assert!(board
+            .cell_state
+            .iter()
+            .all(|cell| cell.selection == CellSelection::None));
+    }
 }
@@ -23,6 +23,12 @@ pub struct BoardHistory {
     pub selected_number_history: Vec<Option<u8>>,
 }
 
+impl Default for BoardHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BoardHistory {
     pub fn new() -> Self {
         BoardHistory {
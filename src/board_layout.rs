@@ -0,0 +1,133 @@
+use macroquad::color::Color;
+
+use crate::board::{Board, BoardMode};
+use crate::cell_state::CellSelection;
+use crate::theme::Theme;
+
+// the rules every picture of a board follows, the window,
+// the terminal and the exporters all draw from these
+
+// top left, top right, bottom left, bottom right, top, bottom, left, right
+const CORNER_SLOTS: [(f32, f32); 8] = [
+    (0.0, 0.0),
+    (2.0, 0.0),
+    (0.0, 2.0),
+    (2.0, 2.0),
+    (1.0, 0.0),
+    (1.0, 2.0),
+    (0.0, 1.0),
+    (2.0, 1.0),
+];
+
+// where a corner mark goes in the cell's 3 by 3 grid of pencil boxes.
+// all nine marks make four on the top edge, the centre stays free
+pub fn corner_slot(slot: usize, count: usize) -> (f32, f32) {
+    match slot {
+        4 if count > 8 => (2.0 / 3.0, 0.0),
+        8 => (4.0 / 3.0, 0.0),
+        _ => CORNER_SLOTS[slot],
+    }
+}
+
+// how much of its box a digit is as wide as
+pub const DIGIT_WIDTH: f32 = 0.45;
+
+pub fn cell_line_width(board_size: f32) -> f32 {
+    let line_width = board_size * 0.0025;
+    if line_width < 0.5 {
+        0.5
+    } else {
+        line_width
+    }
+}
+
+pub fn box_line_width(board_size: f32) -> f32 {
+    let line_width = board_size * 0.005;
+    if line_width < 1.0 {
+        1.0
+    } else {
+        line_width
+    }
+}
+
+// pencil marks sit in a three by three grid inside the cell padding,
+// returns the padding and the size of one box of that grid
+pub fn pencil_box(cell_size: f32) -> (f32, f32) {
+    let padding = cell_size * 0.1;
+    (padding, (cell_size - padding) / 3.0)
+}
+
+pub fn cell_background(board: &Board, theme: &Theme, index: usize) -> Color {
+    match board.cell_state[index].selection {
+        CellSelection::Emphasized if board.mode == BoardMode::Pencil => theme.cell_pencil_emphasize,
+        CellSelection::Emphasized => theme.cell_emphasize,
+        CellSelection::Highlighted => theme.cell_highlighted,
+        CellSelection::None => theme.cell_normal,
+    }
+}
+
+// givens keep their colour, only permissive entry can leave
+// conflicting or wrong numbers on the board
+pub fn number_color(board: &Board, theme: &Theme, index: usize, show_wrong: bool) -> Color {
+    if board.cell_state[index].initial {
+        theme.cell_text_initial
+    } else if board.is_conflicting(index) || (show_wrong && board.is_wrong(index)) {
+        theme.cell_text_conflict
+    } else {
+        theme.cell_text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{box_line_width, cell_line_width, corner_slot, number_color, pencil_box};
+    use crate::board::Board;
+    use crate::theme::Theme;
+
+    #[test]
+    fn line_widths() {
+        assert_eq!(cell_line_width(100.0), 0.5);
+        assert_eq!(cell_line_width(800.0), 2.0);
+        assert_eq!(box_line_width(100.0), 1.0);
+        assert_eq!(box_line_width(800.0), 4.0);
+        assert_eq!(pencil_box(90.0), (9.0, 27.0));
+    }
+
+    #[test]
+    fn corner_slots() {
+        assert_eq!(corner_slot(4, 5), (1.0, 0.0));
+
+        // nine marks, none in the centre and none on top of another
+        let slots: Vec<(f32, f32)> = (0..9).map(|slot| corner_slot(slot, 9)).collect();
+        for (i, &(x, y)) in slots.iter().enumerate() {
+            assert!(x == 0.0 || x == 2.0 || y == 0.0 || y == 2.0);
+            for &(other_x, other_y) in &slots[i + 1..] {
+                assert!((x - other_x).abs() > 0.5 || (y - other_y).abs() > 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn number_colors() {
+        let theme = Theme::light();
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(1);
+        board.cell_state[1].set_number(2);
+        board.cell_state[2].set_number(1);
+        board.solution = vec![1; 81];
+
+        assert_eq!(
+            number_color(&board, &theme, 0, false),
+            theme.cell_text_initial
+        );
+        assert_eq!(
+            number_color(&board, &theme, 2, false),
+            theme.cell_text_conflict
+        );
+        assert_eq!(number_color(&board, &theme, 1, false), theme.cell_text);
+        assert_eq!(
+            number_color(&board, &theme, 1, true),
+            theme.cell_text_conflict
+        );
+    }
+}
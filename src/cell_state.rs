@@ -1,6 +1,6 @@
 use serde_derive::{Deserialize, Serialize};
 
-use crate::is_legal_number;
+use crate::{is_legal_color, is_legal_number};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CellSelection {
@@ -13,8 +13,12 @@ pub enum CellSelection {
 pub struct CellState {
     pub number: Option<u8>,
     pub pencil: [Option<u8>; 9],
+    #[serde(default)]
+    pub corner: [Option<u8>; 9],
     pub selection: CellSelection,
     pub initial: bool,
+    #[serde(default)]
+    pub color: Option<u8>,
 }
 
 impl Default for CellState {
@@ -28,8 +32,10 @@ impl CellState {
         CellState {
             number: None,
             pencil: [None, None, None, None, None, None, None, None, None],
+            corner: [None, None, None, None, None, None, None, None, None],
             selection: CellSelection::None,
             initial: false,
+            color: None,
         }
     }
 
@@ -38,9 +44,39 @@ impl CellState {
     }
 
     pub fn has_pencil(&self) -> bool {
+        self.has_center() || self.has_corner()
+    }
+
+    pub fn has_center(&self) -> bool {
         self.pencil.iter().any(|&number| number.is_some())
     }
 
+    pub fn has_corner(&self) -> bool {
+        self.corner.iter().any(|&number| number.is_some())
+    }
+
+    pub fn has_this_corner(&self, number: u8) -> bool {
+        self.corner[number as usize - 1].is_some()
+    }
+
+    pub fn set_corner(&mut self, number: u8) {
+        if self.initial || !is_legal_number(number) {
+            return;
+        }
+
+        self.clear_number();
+        self.corner[number as usize - 1] = Some(number);
+    }
+
+    pub fn remove_corner(&mut self, number: u8) {
+        if self.initial || !is_legal_number(number) {
+            return;
+        }
+
+        self.clear_number();
+        self.corner[number as usize - 1] = None;
+    }
+
     pub fn has_this_pencil(&self, number: u8) -> bool {
         self.pencil[number as usize - 1].is_some()
     }
@@ -65,6 +101,23 @@ impl CellState {
 
     pub fn clear_pencil(&mut self) {
         self.pencil = [None, None, None, None, None, None, None, None, None];
+        self.corner = [None, None, None, None, None, None, None, None, None];
+    }
+
+    pub fn has_color(&self) -> bool {
+        self.color.is_some()
+    }
+
+    pub fn set_color(&mut self, color: u8) {
+        if !is_legal_color(color) {
+            return;
+        }
+
+        self.color = Some(color);
+    }
+
+    pub fn clear_color(&mut self) {
+        self.color = None;
     }
 
     pub fn has_number(&self) -> bool {
@@ -248,4 +301,69 @@ mod tests {
         assert!(!cell.has_pencil());
         assert!(cell.is_number(1));
     }
+
+    #[test]
+    fn color_test() {
+        let mut cell = CellState::new();
+        assert!(!cell.has_color());
+
+        cell.set_color(1);
+        assert!(cell.has_color());
+        assert_eq!(cell.color, Some(1));
+
+        cell.set_color(0);
+        assert_eq!(cell.color, Some(1));
+
+        cell.set_color(9);
+        assert_eq!(cell.color, Some(1));
+
+        cell.set_color(8);
+        assert_eq!(cell.color, Some(8));
+
+        cell.set_number(3);
+        cell.set_pencil(4);
+        assert_eq!(cell.color, Some(8));
+
+        cell.clear_color();
+        assert!(!cell.has_color());
+
+        cell.clear_pencil();
+        cell.set_initial_number(2);
+        cell.set_color(3);
+        assert_eq!(cell.color, Some(3));
+    }
+
+    #[test]
+    fn corner_test() {
+        let mut cell = CellState::new();
+        assert!(!cell.has_corner());
+
+        cell.set_corner(3);
+        assert!(cell.has_corner());
+        assert!(cell.has_pencil());
+        assert!(!cell.has_center());
+        assert!(cell.has_this_corner(3));
+        assert!(!cell.has_this_pencil(3));
+
+        cell.set_pencil(3);
+        assert!(cell.has_center());
+        assert!(cell.has_this_pencil(3));
+
+        cell.remove_corner(3);
+        assert!(!cell.has_corner());
+        assert!(cell.has_pencil());
+
+        cell.set_corner(5);
+        cell.set_number(1);
+        assert!(!cell.has_pencil());
+        assert!(!cell.has_corner());
+
+        cell.set_corner(5);
+        cell.clear_pencil();
+        assert!(!cell.has_corner());
+
+        cell.set_initial_number(2);
+        cell.set_corner(4);
+        assert!(!cell.has_corner());
+    }
 }
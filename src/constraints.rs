@@ -0,0 +1,138 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::index::{index_to_xy, xy_to_index};
+
+pub const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+pub const KING_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Constraints {
+    pub anti_knight: bool,
+    pub anti_king: bool,
+}
+
+impl Constraints {
+    pub fn is_classic(&self) -> bool {
+        !self.anti_knight && !self.anti_king
+    }
+
+    pub fn toggle_anti_knight(&mut self) {
+        self.anti_knight = !self.anti_knight;
+    }
+
+    pub fn toggle_anti_king(&mut self) {
+        self.anti_king = !self.anti_king;
+    }
+
+    // every cell which can't hold the same digit as the cell at (row, col)
+    // because of the chess rules, rows, columns and boxes are not included
+    pub fn neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut neighbours = vec![];
+
+        if self.anti_knight {
+            push_offsets(&mut neighbours, &KNIGHT_OFFSETS, row, col);
+        }
+
+        if self.anti_king {
+            push_offsets(&mut neighbours, &KING_OFFSETS, row, col);
+        }
+
+        neighbours
+    }
+
+    pub fn neighbour_indexes(&self, index: usize) -> Vec<usize> {
+        let (col, row) = index_to_xy(index, 9);
+        self.neighbours(row, col)
+            .into_iter()
+            .map(|(row, col)| xy_to_index(col, row, 9))
+            .collect()
+    }
+}
+
+fn push_offsets(
+    neighbours: &mut Vec<(usize, usize)>,
+    offsets: &[(i32, i32)],
+    row: usize,
+    col: usize,
+) {
+    for (row_offset, col_offset) in offsets {
+        let neighbour_row = row as i32 + row_offset;
+        let neighbour_col = col as i32 + col_offset;
+        if !(0..9).contains(&neighbour_row) || !(0..9).contains(&neighbour_col) {
+            continue;
+        }
+
+        let neighbour = (neighbour_row as usize, neighbour_col as usize);
+        if !neighbours.contains(&neighbour) {
+            neighbours.push(neighbour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Constraints;
+
+    #[test]
+    fn classic_has_no_neighbours() {
+        let constraints = Constraints::default();
+        assert!(constraints.is_classic());
+        assert!(constraints.neighbours(4, 4).is_empty());
+        assert!(constraints.neighbour_indexes(40).is_empty());
+    }
+
+    #[test]
+    fn anti_knight_neighbours() {
+        let constraints = Constraints {
+            anti_knight: true,
+            anti_king: false,
+        };
+        assert!(!constraints.is_classic());
+        assert_eq!(constraints.neighbours(4, 4).len(), 8);
+        assert_eq!(constraints.neighbours(0, 0), vec![(1, 2), (2, 1)]);
+        assert_eq!(constraints.neighbour_indexes(0), vec![11, 19]);
+    }
+
+    #[test]
+    fn anti_king_neighbours() {
+        let constraints = Constraints {
+            anti_knight: false,
+            anti_king: true,
+        };
+        assert_eq!(constraints.neighbours(4, 4).len(), 8);
+        assert_eq!(constraints.neighbours(8, 8), vec![(7, 7), (7, 8), (8, 7)]);
+        assert_eq!(constraints.neighbour_indexes(80), vec![70, 71, 79]);
+    }
+
+    #[test]
+    fn combined_neighbours() {
+        let mut constraints = Constraints::default();
+        constraints.toggle_anti_knight();
+        constraints.toggle_anti_king();
+        assert_eq!(constraints.neighbours(4, 4).len(), 16);
+        assert_eq!(constraints.neighbours(0, 0).len(), 5);
+
+        constraints.toggle_anti_knight();
+        constraints.toggle_anti_king();
+        assert!(constraints.is_classic());
+    }
+}
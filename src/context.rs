@@ -1,42 +1,160 @@
 use std::cmp::min;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
 
+use crate::audio::Audio;
 use crate::board::Board;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::coop::DEFAULT_COOP_PORT;
+use crate::coop::{CoopEvent, CoopSession};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::booklet::{booklet_pdf, BookletPuzzle, DEFAULT_PER_PAGE};
 use crate::fonts::font_context::FontContext;
-use crate::generate::{create_puzzle, generate_board};
-use crate::index::xy_to_index;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+use crate::gamepad::{Gamepad, PadAction};
+use crate::locale::{detect_language, difficulty_key, Language};
 use crate::menu::{is_menu_action_number, Menu, MenuActions};
+use crate::move_log::{Replay, ReplayFile};
 use crate::new_game_modal::NewGameModal;
-use crate::save::{load, save};
-use crate::victory_modal::VictoryModal;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::options_modal::{NetworkMode, NetworkRequest};
+use crate::options_modal::{OptionsModal, ReplayRequest};
+use crate::puzzle_code::{startup_board, PuzzleCode};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::race::DEFAULT_RACE_PORT;
+use crate::race::{Race, RaceEvent, RaceOutcome};
+use crate::replay_modal::ReplayModal;
+use crate::settings::{InputStyle, Settings};
+use crate::sound::SoundCues;
+use crate::speech::Announcer;
+use crate::summary::GameSummary;
+use crate::theme::Theme;
+use crate::touch::{Gesture, Gestures, Zoom};
+use crate::victory_modal::{VictoryAction, VictoryModal};
+use crate::DIGIT_COUNT;
 
 use macroquad::prelude::*;
 
-fn load_board_from_save() -> Option<Board> {
-    let loaded_board_str = load("board");
-    if let Some(board_str) = loaded_board_str {
-        let board_result = serde_json::from_str::<Board>(&board_str);
-        if let Ok(board) = board_result {
-            return Some(board);
+const CURSOR_KEYS: [(KeyCode, i32, i32); 12] = [
+    (KeyCode::Up, 0, -1),
+    (KeyCode::Down, 0, 1),
+    (KeyCode::Left, -1, 0),
+    (KeyCode::Right, 1, 0),
+    (KeyCode::W, 0, -1),
+    (KeyCode::S, 0, 1),
+    (KeyCode::A, -1, 0),
+    (KeyCode::D, 1, 0),
+    (KeyCode::K, 0, -1),
+    (KeyCode::J, 0, 1),
+    (KeyCode::H, -1, 0),
+    (KeyCode::L, 1, 0),
+];
+
+const DIGIT_KEYS: [(KeyCode, KeyCode); 9] = [
+    (KeyCode::Key1, KeyCode::Kp1),
+    (KeyCode::Key2, KeyCode::Kp2),
+    (KeyCode::Key3, KeyCode::Kp3),
+    (KeyCode::Key4, KeyCode::Kp4),
+    (KeyCode::Key5, KeyCode::Kp5),
+    (KeyCode::Key6, KeyCode::Kp6),
+    (KeyCode::Key7, KeyCode::Kp7),
+    (KeyCode::Key8, KeyCode::Kp8),
+    (KeyCode::Key9, KeyCode::Kp9),
+];
+
+// shift/ctrl click adds cells to the multi selection
+fn is_multi_select_down() -> bool {
+    is_key_down(KeyCode::LeftShift)
+        || is_key_down(KeyCode::RightShift)
+        || is_key_down(KeyCode::LeftControl)
+        || is_key_down(KeyCode::RightControl)
+}
+
+// ctrl/alt and a digit writes a pencil mark, shift is taken by
+// layouts that need it for the digits themselves
+fn is_pencil_modifier_down() -> bool {
+    is_key_down(KeyCode::LeftControl)
+        || is_key_down(KeyCode::RightControl)
+        || is_key_down(KeyCode::LeftAlt)
+        || is_key_down(KeyCode::RightAlt)
+}
+
+// the digit typed since the last frame, by the character so every keyboard
+// layout works. macroquad keeps the characters until they're taken, so this
+// takes them all once a frame
+fn typed_digit() -> Option<u8> {
+    let mut digit = None;
+    while let Some(character) = get_char_pressed() {
+        if let '1'..='9' = character {
+            digit = Some(character as u8 - b'0');
         }
     }
+    digit
+}
 
-    None
+// a puzzle someone shared, passed as the first argument
+#[cfg(not(target_arch = "wasm32"))]
+fn shared_code() -> Option<String> {
+    std::env::args().nth(1).filter(|arg| !arg.starts_with("--"))
 }
 
-fn generate_new_board(difficulty: u8) -> Board {
-    let mut board = Board::new();
+// --host or --join ADDRESS start a race without going through the options,
+// --coop-host and --coop-join ADDRESS a shared game
+#[cfg(not(target_arch = "wasm32"))]
+fn network_args() -> Option<NetworkRequest> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let join = |mode| {
+        args.get(1)
+            .cloned()
+            .map(|address| NetworkRequest::Join(mode, address))
+    };
+    match args.first().map(String::as_str) {
+        Some("--host") => Some(NetworkRequest::Host(NetworkMode::Race)),
+        Some("--join") => join(NetworkMode::Race),
+        Some("--coop-host") => Some(NetworkRequest::Host(NetworkMode::Coop)),
+        Some("--coop-join") => join(NetworkMode::Coop),
+        _ => None,
+    }
+}
 
-    let mut board_gen = [[0; 9]; 9];
-    generate_board(&mut board_gen);
-    create_puzzle(&mut board_gen, difficulty);
-    for (y, row) in board_gen.iter().enumerate() {
-        for (x, col) in row.iter().enumerate() {
-            board.cell_state[xy_to_index(x, y, 9)].set_initial_number(*col);
-        }
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn sudoku_location() -> sapp_jsutils::JsObject;
+    fn sudoku_announce(text: sapp_jsutils::JsObject);
+}
+
+// the query and fragment of the page, through sudoku-bridge.js
+#[cfg(target_arch = "wasm32")]
+fn shared_code() -> Option<String> {
+    let mut location = String::new();
+    unsafe { sudoku_location() }.to_string(&mut location);
+    Some(location)
+}
+
+// read out by a screen reader, through the live region in index.html
+#[cfg(target_arch = "wasm32")]
+fn announce(text: &str) {
+    unsafe { sudoku_announce(sapp_jsutils::JsObject::string(text)) };
+}
+
+// SUDOKU_ANNOUNCE prints them instead, for a screen reader following the terminal
+#[cfg(not(target_arch = "wasm32"))]
+fn announce(text: &str) {
+    if std::env::var_os("SUDOKU_ANNOUNCE").is_some() {
+        println!("{}", text);
     }
-    board.update_number_count();
+}
 
-    board
+fn victory_key() -> Option<VictoryAction> {
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::N) {
+        Some(VictoryAction::NewGame)
+    } else if is_key_pressed(KeyCode::R) {
+        Some(VictoryAction::Replay)
+    } else if is_key_pressed(KeyCode::C) {
+        Some(VictoryAction::Copy)
+    } else {
+        None
+    }
 }
 
 pub struct Context {
@@ -55,19 +173,43 @@ pub struct Context {
     pub portrait: bool,
     pub new_game_modal: NewGameModal,
     pub victory_modal: VictoryModal,
+    pub options_modal: OptionsModal,
+    pub replay_modal: ReplayModal,
+    pub race: Option<Race>,
+    pub coop: Option<CoopSession>,
+    #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+    pub gamepad: Gamepad,
+    pub gestures: Gestures,
+    pub zoom: Zoom,
+    pub announcer: Announcer,
+    pub audio: Audio,
+    pub sound_cues: SoundCues,
+    pub settings: Settings,
+    pub themes: Vec<Theme>,
+    pub theme: Theme,
+    pub language: Language,
+    typed_digit: Option<u8>,
+    #[cfg(not(target_arch = "wasm32"))]
+    booklet_job: Option<Receiver<String>>,
 }
 
 impl Context {
-    pub async fn new(font_path: &str, icon_font_path: &str) -> Self {
-        let board_save = load_board_from_save();
-        let board = if let Some(board) = board_save {
-            board
-        } else {
-            generate_new_board(2)
-        };
+    pub fn new() -> Self {
+        let mut board = startup_board(shared_code().as_deref());
 
-        let context = Context {
-            font_context: FontContext::new(font_path, icon_font_path).await,
+        let settings = Settings::load();
+        board.settings = settings.clone();
+        board.highlight();
+
+        let themes = Theme::all();
+        let theme = Theme::find(&themes, &settings.theme);
+
+        let mut font_context = FontContext::new(&theme);
+        font_context.set_digit_glyphs(settings.digit_glyphs);
+        let language = settings.language.unwrap_or_else(detect_language);
+
+        let mut context = Context {
+            font_context,
             board,
             menu: Menu::new(),
             width_padding: 0.0,
@@ -82,93 +224,845 @@ impl Context {
             portrait: true,
             new_game_modal: Default::default(),
             victory_modal: Default::default(),
+            options_modal: Default::default(),
+            replay_modal: Default::default(),
+            race: None,
+            coop: None,
+            #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+            gamepad: Gamepad::new(),
+            gestures: Default::default(),
+            zoom: Default::default(),
+            announcer: Default::default(),
+            audio: Default::default(),
+            sound_cues: Default::default(),
+            settings,
+            themes,
+            theme,
+            language,
+            typed_digit: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            booklet_job: None,
         };
 
         context.save_board();
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(request) = network_args() {
+            context.handle_network_request(request);
+        }
+
         context
     }
 
-    fn save_board(&self) {
-        let save_data = serde_json::to_string(&self.board).unwrap();
-        save("board", save_data.as_str());
+    fn apply_settings(&mut self, settings: Settings) {
+        if settings.theme != self.theme.name {
+            self.theme = Theme::find(&self.themes, &settings.theme);
+            self.font_context.set_theme(&self.theme);
+        }
+
+        if settings.digit_glyphs != self.font_context.digit_glyphs {
+            self.font_context.set_digit_glyphs(settings.digit_glyphs);
+        }
+
+        self.language = settings.language.unwrap_or_else(detect_language);
+
+        self.settings = settings;
+        self.settings.save();
+
+        self.board.settings = self.settings.clone();
+        self.board.highlight();
+        self.save_board();
+    }
+
+    // the booklet goes next to the save file. making the puzzles takes
+    // seconds, it happens on a thread and the frames go on
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_booklet(&mut self, count: usize, difficulty: u8) {
+        if self.booklet_job.is_some() {
+            return;
+        }
+
+        let language = self.language;
+        let digit_glyphs = self.settings.digit_glyphs;
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            let puzzles: Vec<BookletPuzzle> = (0..count)
+                .map(|_| BookletPuzzle::generate(difficulty, Default::default()))
+                .collect();
+            let pdf = booklet_pdf(&puzzles, DEFAULT_PER_PAGE, language, digit_glyphs);
+
+            let path = "booklet.pdf";
+            let _ = sender.send(match std::fs::write(path, pdf) {
+                Ok(()) => format!("{} {}", language.text("booklet.saved"), path),
+                Err(error) => format!("{}: {}", path, error),
+            });
+        });
+
+        self.booklet_job = Some(receiver);
+        self.options_modal.message = Some(self.language.text("booklet.working").to_string());
+    }
+
+    // checked once a frame until the thread sends its message
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_booklet(&mut self) {
+        let message = match self.booklet_job.as_ref().map(Receiver::try_recv) {
+            Some(Ok(message)) => Some(message),
+            Some(Err(TryRecvError::Disconnected)) => None,
+            Some(Err(TryRecvError::Empty)) | None => return,
+        };
+        self.booklet_job = None;
+        self.options_modal.message = message;
+    }
+
+    // replays are read and written next to the save file
+    fn handle_replay_request(&mut self, request: ReplayRequest) {
+        #[cfg(not(target_arch = "wasm32"))]
+        const REPLAY_PATH: &str = "replay.json";
+
+        self.save_board();
+        let file = match request {
+            ReplayRequest::Watch => Ok(ReplayFile::from_board(&self.board)),
+            #[cfg(not(target_arch = "wasm32"))]
+            ReplayRequest::Export => {
+                let json = ReplayFile::from_board(&self.board).to_json();
+                self.options_modal.message = Some(match std::fs::write(REPLAY_PATH, json) {
+                    Ok(()) => format!("{} {}", self.language.text("replay.saved"), REPLAY_PATH),
+                    Err(error) => format!("{}: {}", REPLAY_PATH, error),
+                });
+                return;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            ReplayRequest::Open => std::fs::read_to_string(REPLAY_PATH)
+                .map_err(|error| format!("{}: {}", REPLAY_PATH, error))
+                .and_then(|json| ReplayFile::from_json(&json)),
+        };
+
+        match file.and_then(Replay::new) {
+            Ok(replay) => {
+                let mut board = replay.board();
+                board.settings = self.settings.clone();
+                board.update(self.board_size, self.game_padding, self.portrait);
+
+                let game = std::mem::replace(&mut self.board, board);
+                self.replay_modal.open(replay, game);
+                self.options_modal.hide();
+            }
+            Err(error) => self.options_modal.message = Some(error),
+        }
     }
 
+    fn update_replay(&mut self) {
+        let replay_modal = &mut self.replay_modal;
+        let mut close = false;
+        egui_macroquad::ui(|egui_ctx| {
+            close = replay_modal.ui(egui_ctx, self.theme.dark, self.language);
+        });
+
+        if let Some(replay) = &mut self.replay_modal.replay {
+            if is_key_pressed(KeyCode::Space) {
+                replay.toggle_playing();
+            }
+            if is_key_pressed(KeyCode::Left) {
+                replay.seek(replay.time - 5.0);
+            }
+            if is_key_pressed(KeyCode::Right) {
+                replay.seek(replay.time + 5.0);
+            }
+            replay.advance(get_frame_time() as f64);
+
+            self.board.cell_state = replay.cells();
+            self.board.elapsed = replay.time;
+            self.board.update_number_count();
+        }
+
+        if close || is_key_pressed(KeyCode::Escape) {
+            if let Some(game) = self.replay_modal.close() {
+                self.board = game;
+                self.board
+                    .update(self.board_size, self.game_padding, self.portrait);
+            }
+        }
+    }
+
+    // one race or shared game at a time, starting one leaves the other
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_network_request(&mut self, request: NetworkRequest) {
+        self.race = None;
+        self.coop = None;
+        self.victory_modal.set_headline(None);
+
+        let text = |key| self.language.text(key);
+        let result = match request {
+            NetworkRequest::Host(NetworkMode::Race) => Race::host(DEFAULT_RACE_PORT)
+                .map(|race| self.race = Some(race))
+                .map(|_| format!("{} {}", text("network.hosting"), DEFAULT_RACE_PORT)),
+            NetworkRequest::Host(NetworkMode::Coop) => {
+                CoopSession::host(DEFAULT_COOP_PORT, &self.board)
+                    .map(|coop| self.coop = Some(coop))
+                    .map(|_| format!("{} {}", text("network.hosting"), DEFAULT_COOP_PORT))
+            }
+            NetworkRequest::Join(NetworkMode::Race, address) => Race::join(&address)
+                .map(|race| self.race = Some(race))
+                .map(|_| format!("{} {}", text("network.connected"), address)),
+            NetworkRequest::Join(NetworkMode::Coop, address) => CoopSession::join(&address)
+                .map(|coop| self.coop = Some(coop))
+                .map(|_| format!("{} {}", text("network.connected"), address)),
+            NetworkRequest::Leave => {
+                self.options_modal.message = None;
+                return;
+            }
+        };
+
+        self.options_modal.message = Some(result.unwrap_or_else(|error| error.to_string()));
+    }
+
+    // a puzzle from the network replaces whatever was being played
+    fn start_network_board(&mut self, board: Board) {
+        self.replay_modal.close();
+        self.victory_modal.hide();
+        self.new_game_modal.hide();
+
+        self.board = board;
+        self.board.settings = self.settings.clone();
+        self.board
+            .update(self.board_size, self.game_padding, self.portrait);
+        self.save_board();
+    }
+
+    fn update_race(&mut self) {
+        let race = match &mut self.race {
+            Some(race) => race,
+            None => return,
+        };
+
+        let mut new_board = None;
+        for event in race.poll() {
+            match event {
+                RaceEvent::Joined => {
+                    let difficulty = self.board.difficulty.unwrap_or(2);
+                    let board = Board::generate(difficulty, self.board.constraints);
+                    race.start(&board);
+                    new_board = Some(board);
+                }
+                RaceEvent::Start(board) => new_board = Some(*board),
+                RaceEvent::OpponentSolved => {}
+                RaceEvent::Disconnected => {
+                    self.options_modal.message = Some(self.language.text("race.left").to_string());
+                }
+            }
+        }
+        if let Some(board) = new_board {
+            self.start_network_board(board);
+        }
+
+        // the board on screen is the replay's while one plays
+        let race = match &mut self.race {
+            Some(race) if !self.replay_modal.is_open() => race,
+            _ => return,
+        };
+        if self.board.is_victory() {
+            race.send_solved(self.board.elapsed);
+        }
+        race.send_progress(&self.board);
+
+        // the race is decided when either side finishes, and can change
+        // hands when both finish at nearly the same time
+        let headline = race.outcome().map(|outcome| match outcome {
+            RaceOutcome::Won => "race.won",
+            RaceOutcome::Lost => "race.lost",
+        });
+        if headline != self.victory_modal.headline {
+            if headline.is_some() && !self.victory_modal.show {
+                self.victory_modal
+                    .show(GameSummary::from_board(&self.board));
+            }
+            self.victory_modal.set_headline(headline);
+        }
+    }
+
+    fn update_coop(&mut self) {
+        let coop = match &mut self.coop {
+            // the board on screen is the replay's while one plays
+            Some(coop) if !self.replay_modal.is_open() => coop,
+            _ => return,
+        };
+
+        let mut changed = false;
+        for event in coop.update(&mut self.board) {
+            let message = match event {
+                CoopEvent::Started => {
+                    let board = std::mem::take(&mut self.board);
+                    self.start_network_board(board);
+                    None
+                }
+                CoopEvent::Changed => {
+                    changed = true;
+                    None
+                }
+                CoopEvent::Joined(_) => Some("coop.joined"),
+                CoopEvent::Left(_) => Some("coop.left"),
+                CoopEvent::Disconnected => Some("coop.disconnected"),
+            };
+            if let Some(key) = message {
+                self.options_modal.message = Some(self.language.text(key).to_string());
+            }
+        }
+
+        if changed {
+            self.board.update_number_count();
+            self.board.highlight();
+            if self.board.is_victory() && !self.victory_modal.show {
+                self.victory_modal
+                    .show(GameSummary::from_board(&self.board));
+            }
+            self.save_board();
+        }
+    }
+
+    // in a shared game only the player's own changes are taken back
+    fn undo(&mut self) {
+        self.board.disable_delete_mode();
+        match &mut self.coop {
+            Some(coop) => coop.undo(&mut self.board),
+            None => self.board.undo(),
+        }
+        self.save_board();
+    }
+
+    fn show_new_game_modal(&mut self) {
+        self.new_game_modal.constraints = self.board.constraints;
+        self.new_game_modal.show();
+        announce(self.language.text("new_game.prompt"));
+    }
+
+    fn announce_constraint(&self, key: &'static str, on: bool) {
+        let state = if on { "speech.on" } else { "speech.off" };
+        announce(&format!(
+            "{} {}",
+            self.language.text(key),
+            self.language.text(state)
+        ));
+    }
+
+    fn victory_action(&mut self, action: VictoryAction) {
+        match action {
+            VictoryAction::NewGame => {
+                self.victory_modal.hide();
+                self.show_new_game_modal();
+            }
+            VictoryAction::Replay => {
+                self.victory_modal.hide();
+                self.board.restart();
+                self.save_board();
+            }
+            VictoryAction::Copy => {
+                if let Some(summary) = &self.victory_modal.summary {
+                    let text = summary.share_text(self.language);
+                    unsafe { get_internal_gl() }
+                        .quad_context
+                        .clipboard_set(&text);
+                    self.victory_modal.copied = true;
+                    announce(self.language.text("victory.copied"));
+                }
+            }
+        }
+    }
+
+    fn new_game(&mut self, difficulty: u8) {
+        self.new_game_modal.hide();
+
+        self.board = Board::generate(difficulty, self.new_game_modal.constraints);
+        self.board.settings = self.settings.clone();
+        self.save_board();
+
+        // the host's new game is the next round of the race, or
+        // the next board everyone fills in
+        if let Some(race) = self.race.as_mut().filter(|race| race.host) {
+            race.start(&self.board);
+        }
+        if let Some(coop) = &mut self.coop {
+            if coop.is_host() {
+                coop.restart(&self.board);
+            } else {
+                self.coop = None;
+            }
+        }
+    }
+
+    // returns whether a new game was started, so the layout is redone
+    #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+    fn handle_gamepad(&mut self) -> bool {
+        let mut started = false;
+        for action in self.gamepad.poll(get_frame_time()) {
+            if self.replay_modal.is_open() {
+                if let (PadAction::Place, Some(replay)) = (action, &mut self.replay_modal.replay) {
+                    replay.toggle_playing();
+                }
+            } else if self.victory_modal.show {
+                match action {
+                    PadAction::Place => {
+                        self.victory_modal.hide();
+                        self.show_new_game_modal();
+                    }
+                    PadAction::Back => self.victory_modal.hide(),
+                    _ => {}
+                }
+            } else if self.new_game_modal.show {
+                match action {
+                    PadAction::Move(dx, _) => self.new_game_modal.move_focus(dx),
+                    PadAction::Place => {
+                        if let Some(difficulty) = self.new_game_modal.focus {
+                            self.new_game(difficulty);
+                            started = true;
+                        }
+                    }
+                    PadAction::Back | PadAction::NewGame => self.new_game_modal.hide(),
+                    _ => {}
+                }
+            } else if self.options_modal.show {
+                if let PadAction::Back | PadAction::Options = action {
+                    self.options_modal.hide();
+                }
+            } else {
+                self.pad_action(action);
+            }
+        }
+
+        started
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+    fn pad_action(&mut self, action: PadAction) {
+        match action {
+            PadAction::Move(dx, dy) => {
+                self.board.move_cursor(dx, dy);
+                self.save_board();
+            }
+            // the first press only brings the cursor back
+            PadAction::Place | PadAction::Pencil if self.board.cursor_index.is_none() => {
+                self.board.move_cursor(0, 0);
+                self.save_board();
+            }
+            PadAction::Place if self.board.color_mode => {
+                self.board.disable_delete_mode();
+                self.board.paint_cursor_cell();
+                self.save_board();
+            }
+            PadAction::Place | PadAction::Pencil => {
+                if let Some(number) = self.board.selected_number {
+                    self.input_number(number, true, action == PadAction::Pencil);
+                }
+            }
+            PadAction::Undo => self.undo(),
+            PadAction::Back => self.input_delete(),
+            PadAction::PreviousNumber => self.cycle_number(-1),
+            PadAction::NextNumber => self.cycle_number(1),
+            PadAction::Color => {
+                self.board.disable_delete_mode();
+                self.board.toggle_color_mode();
+                self.save_board();
+            }
+            PadAction::Options => {
+                self.board.disable_delete_mode();
+                self.options_modal.show();
+            }
+            PadAction::NewGame => {
+                self.show_new_game_modal();
+                self.new_game_modal.move_focus(0);
+            }
+        }
+    }
+
+    // steps through the number row, or the palette while colouring,
+    // skipping numbers that are all placed
+    fn cycle_number(&mut self, step: i32) {
+        let start = if step > 0 { 0 } else { DIGIT_COUNT as i32 + 1 };
+        let current = if self.board.color_mode {
+            match self.board.selected_color {
+                0 => DIGIT_COUNT as i32,
+                color => color as i32,
+            }
+        } else {
+            self.board
+                .selected_number
+                .map_or(start, |number| number as i32)
+        };
+
+        let next = (1..=DIGIT_COUNT as i32)
+            .map(|i| ((current - 1 + (step * i)).rem_euclid(DIGIT_COUNT as i32) + 1) as u8)
+            .find(|&number| self.board.color_mode || !self.board.is_number_done(number));
+
+        match next {
+            Some(number) if self.board.color_mode => self.input_color(number, false),
+            Some(number) => self.input_number(number, false, false),
+            None => {}
+        }
+    }
+
+    fn save_board(&mut self) {
+        self.board.save();
+    }
+
+    fn input_number(&mut self, number: u8, enter: bool, pencil: bool) {
+        if self.board.is_number_done(number) {
+            return;
+        }
+
+        self.board.disable_delete_mode();
+        self.board.set_selected_number(number);
+        self.board.highlight();
+        if self.board.has_multi_selection() {
+            self.board.toggle_multi_selection_pencil(number);
+        } else if enter {
+            self.board.enter_number(pencil);
+            if self.board.is_victory() {
+                self.victory_modal
+                    .show(GameSummary::from_board(&self.board));
+            }
+        }
+        self.save_board();
+    }
+
+    // the number row doubles as the palette while colouring,
+    // the last slot being the eraser
+    fn input_color(&mut self, number: u8, paint: bool) {
+        let color = if number == DIGIT_COUNT { 0 } else { number };
+
+        self.board.disable_delete_mode();
+        self.board.set_selected_color(color);
+        if self.board.has_multi_selection() {
+            self.board.paint_multi_selection();
+        } else if paint {
+            self.board.paint_cursor_cell();
+        }
+        self.save_board();
+    }
+
+    fn input_delete(&mut self) {
+        self.board.disable_delete_mode();
+        if self.board.has_multi_selection() {
+            self.board.delete_multi_selection();
+        } else {
+            self.board.delete_cursor_cell();
+        }
+        self.save_board();
+    }
+
+    // touches also move the mouse, while a finger is down they go
+    // through the gestures instead so a long press doesn't place a number
     fn handle_input(&mut self) {
+        let touches = touches();
+        for gesture in self.gestures.update(&touches, get_frame_time()) {
+            self.handle_gesture(gesture);
+        }
+        if !touches.is_empty() {
+            self.handle_keyboard();
+            return;
+        }
+
         if is_mouse_button_pressed(MouseButton::Left) {
             let (mouse_x, mouse_y) = mouse_position();
+            self.click(mouse_x, mouse_y);
+        }
 
-            if let Some(menu_action) = self.menu.click(mouse_x, mouse_y) {
-                if is_menu_action_number(menu_action) {
-                    let number = menu_action as u8;
-                    if self.board.is_number_done(number) {
-                        return;
+        if is_mouse_button_down(MouseButton::Left) {
+            let point = self.zoom.to_board(mouse_position().into());
+            self.board.drag(point.x, point.y);
+        }
+
+        if is_mouse_button_released(MouseButton::Left) {
+            self.release();
+        }
+
+        self.handle_keyboard();
+    }
+
+    fn handle_gesture(&mut self, gesture: Gesture) {
+        match gesture {
+            // the finger is already up, the tap doesn't start a drag
+            Gesture::Tap(point) => {
+                self.click(point.x, point.y);
+                self.release();
+            }
+            // a pencil mark of the selected number
+            Gesture::LongPress(point) => {
+                let point = self.zoom.to_board(point);
+                let index = match self.board.cell_at(point.x, point.y) {
+                    Some(index) if !self.board.color_mode => index,
+                    _ => return,
+                };
+                if let Some(number) = self.board.selected_number {
+                    self.board.clear_multi_selection();
+                    self.board.cursor_index = Some(index);
+                    self.input_number(number, true, true);
+                }
+            }
+            // dragging over the board selects cells, like the mouse
+            Gesture::Drag { start, position } => {
+                let start = self.zoom.to_board(start);
+                let position = self.zoom.to_board(position);
+                if self.board.drag_origin.is_none() {
+                    if let Some(index) = self.board.cell_at(start.x, start.y) {
+                        self.board.clear_multi_selection();
+                        self.board.cursor_index = Some(index);
+                        self.board.multi_select_cell(index);
                     }
+                }
+                self.board.drag(position.x, position.y);
+            }
+            // along the number bar steps through the numbers
+            Gesture::Swipe { start, end } => {
+                if self.board.drag_origin.is_some() {
+                    self.release();
+                    return;
+                }
 
-                    self.board.disable_delete_mode();
-                    self.board.set_selected_number(number);
-                    self.board.highlight();
-                    self.save_board();
-                } else if menu_action == MenuActions::Pencil {
-                    self.board.disable_delete_mode();
-                    self.board.toggle_pencil_mode();
-                    self.save_board();
-                } else if menu_action == MenuActions::Delete {
+                let on_numbers = self
+                    .menu
+                    .click(start.x, start.y)
+                    .is_some_and(is_menu_action_number);
+                let (along, across) = if self.portrait {
+                    (end.x - start.x, end.y - start.y)
+                } else {
+                    (end.y - start.y, end.x - start.x)
+                };
+                if on_numbers && along.abs() > across.abs() {
+                    self.cycle_number(along.signum() as i32);
+                }
+            }
+            Gesture::TwoFingerTap => self.undo(),
+            Gesture::Pinch { center, scale, pan } => {
+                if self.zoom.area.contains(center) {
+                    self.zoom.pinch(center, scale, pan);
+                }
+            }
+        }
+    }
+
+    fn click(&mut self, mouse_x: f32, mouse_y: f32) {
+        if let Some(menu_action) = self.menu.click(mouse_x, mouse_y) {
+            if is_menu_action_number(menu_action) && self.board.color_mode {
+                let paint = self.settings.input_style == InputStyle::CellFirst;
+                self.input_color(menu_action as u8, paint);
+            } else if is_menu_action_number(menu_action) {
+                let enter = self.settings.input_style == InputStyle::CellFirst;
+                self.input_number(menu_action as u8, enter, false);
+            } else if menu_action == MenuActions::Pencil {
+                self.board.disable_delete_mode();
+                self.board.toggle_pencil_mode();
+                self.save_board();
+            } else if menu_action == MenuActions::Delete {
+                if self.board.has_multi_selection()
+                    || self.settings.input_style == InputStyle::CellFirst
+                {
+                    self.input_delete();
+                } else {
                     self.board.toggle_delete_mode();
                     self.save_board();
-                } else if menu_action == MenuActions::Undo {
-                    self.board.disable_delete_mode();
-                    self.board.undo();
+                }
+            } else if menu_action == MenuActions::InputStyle {
+                self.board.disable_delete_mode();
+                let mut settings = self.settings.clone();
+                settings.toggle_input_style();
+                self.apply_settings(settings);
+            } else if menu_action == MenuActions::Options {
+                self.board.disable_delete_mode();
+                self.options_modal.show();
+            } else if menu_action == MenuActions::PencilLayer {
+                self.board.disable_delete_mode();
+                self.board.toggle_pencil_layer();
+                self.save_board();
+            } else if menu_action == MenuActions::Color {
+                self.board.disable_delete_mode();
+                self.board.toggle_color_mode();
+                self.save_board();
+            } else if menu_action == MenuActions::ClearColors {
+                if self.board.color_mode {
+                    self.board.clear_colors();
                     self.save_board();
-                } else if menu_action == MenuActions::New {
-                    self.new_game_modal.show();
                 }
-                return;
+            } else if menu_action == MenuActions::Undo {
+                self.undo();
+            } else if menu_action == MenuActions::New {
+                self.show_new_game_modal();
             }
+            return;
+        }
 
-            self.board.click(mouse_x, mouse_y);
-            if self.board.is_victory() {
-                self.victory_modal.show();
-            }
+        let point = self.zoom.to_board(vec2(mouse_x, mouse_y));
+        if is_multi_select_down() {
+            self.board.multi_select_click(point.x, point.y);
             self.save_board();
+            return;
         }
 
-        let key_pressed = get_last_key_pressed();
-        if let Some(key) = key_pressed {
-            if key == KeyCode::U {
-                self.board.disable_delete_mode();
-                self.board.undo();
-                self.save_board();
-            }
+        self.board.clear_multi_selection();
+        self.board.click(point.x, point.y);
+        self.save_board();
+    }
+
+    // a number placed by a press goes in when it's let go
+    fn release(&mut self) {
+        self.board.stop_drag();
+        if self.board.is_victory() && !self.victory_modal.show {
+            self.victory_modal
+                .show(GameSummary::from_board(&self.board));
+        }
+        self.save_board();
+    }
+
+    fn handle_keyboard(&mut self) {
+        if is_key_pressed(KeyCode::U) {
+            self.undo();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.board.clear_multi_selection();
+            self.board.cursor_index = None;
+            self.save_board();
             return;
         }
 
-        let char_pressed = get_char_pressed();
-        if let Some(key @ '1'..='9') = char_pressed {
-            let number = key as u8 - 48; // 48 = '0'
-            if self.board.is_number_done(number) {
+        for (key, dx, dy) in CURSOR_KEYS {
+            if is_key_pressed(key) {
+                self.board.move_cursor(dx, dy);
+                self.save_board();
                 return;
             }
+        }
+
+        if is_key_pressed(KeyCode::O) {
+            self.options_modal.show();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::N) {
+            self.show_new_game_modal();
+            self.new_game_modal.move_focus(0);
+            return;
+        }
 
+        if is_key_pressed(KeyCode::I) {
             self.board.disable_delete_mode();
-            self.board.set_selected_number(number);
-            self.board.highlight();
+            let mut settings = self.settings.clone();
+            settings.toggle_input_style();
+            self.apply_settings(settings);
+            return;
+        }
+
+        if is_key_pressed(KeyCode::X) && self.board.color_mode {
+            self.board.clear_colors();
+            self.save_board();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::M) {
+            self.board.toggle_pencil_layer();
             self.save_board();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::C) {
+            self.board.disable_delete_mode();
+            self.board.toggle_color_mode();
+            self.save_board();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Space) {
+            self.board.disable_delete_mode();
+            self.board.toggle_pencil_mode();
+            self.save_board();
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Backspace) || is_key_pressed(KeyCode::Delete) {
+            self.input_delete();
+            return;
+        }
+
+        if let Some(number) = self.pressed_digit() {
+            if self.board.color_mode {
+                self.input_color(number, true);
+            } else {
+                self.input_number(number, true, is_pencil_modifier_down());
+            }
         }
     }
 
+    // ctrl and alt can swallow the character, then the key itself counts
+    fn pressed_digit(&self) -> Option<u8> {
+        self.typed_digit.or_else(|| {
+            DIGIT_KEYS
+                .iter()
+                .position(|&(key, keypad_key)| is_key_pressed(key) || is_key_pressed(keypad_key))
+                .map(|i| (i + 1) as u8)
+        })
+    }
+
     pub fn update(&mut self) {
         let mut force_update = false;
+        self.typed_digit = typed_digit();
 
-        if self.victory_modal.show {
+        self.update_race();
+        self.update_coop();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.poll_booklet();
+
+        #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+        {
+            // the button prompts go away once the mouse is used
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.gamepad.active = false;
+            }
+            force_update |= self.handle_gamepad();
+        }
+
+        if self.replay_modal.is_open() {
+            self.update_replay();
+        } else if self.victory_modal.show {
+            let mut action = victory_key();
             if is_mouse_button_pressed(MouseButton::Left) {
                 let (mouse_x, mouse_y) = mouse_position();
                 if self.victory_modal.click_outside(mouse_x, mouse_y) {
                     self.victory_modal.hide();
                     return;
                 }
+
+                action = action.or(self.victory_modal.click(mouse_x, mouse_y));
+            }
+
+            if is_key_pressed(KeyCode::Escape) {
+                self.victory_modal.hide();
+            } else if let Some(action) = action {
+                self.victory_action(action);
             }
         } else if self.new_game_modal.show {
+            // 1 2 3, or the arrows and enter, pick the difficulty
+            let mut difficulty = self.pressed_digit().filter(|&digit| digit <= 3);
+            if is_key_pressed(KeyCode::Enter) {
+                difficulty = difficulty.or(self.new_game_modal.focus);
+            }
+            for (key, dx) in [(KeyCode::Left, -1), (KeyCode::Right, 1)] {
+                if is_key_pressed(key) {
+                    self.new_game_modal.move_focus(dx);
+                    if let Some(focus) = self.new_game_modal.focus {
+                        announce(self.language.text(difficulty_key(focus)));
+                    }
+                }
+            }
+            if is_key_pressed(KeyCode::K) {
+                self.new_game_modal.constraints.toggle_anti_knight();
+                let on = self.new_game_modal.constraints.anti_knight;
+                self.announce_constraint("constraint.anti_knight", on);
+            }
+            if is_key_pressed(KeyCode::G) {
+                self.new_game_modal.constraints.toggle_anti_king();
+                let on = self.new_game_modal.constraints.anti_king;
+                self.announce_constraint("constraint.anti_king", on);
+            }
+
             if is_mouse_button_pressed(MouseButton::Left) {
                 let (mouse_x, mouse_y) = mouse_position();
                 if self.new_game_modal.click_outside(mouse_x, mouse_y) {
@@ -176,16 +1070,77 @@ impl Context {
                     return;
                 }
 
-                if let Some(difficulty) = self.new_game_modal.click(mouse_x, mouse_y) {
-                    force_update = true;
-                    self.new_game_modal.hide();
-
-                    self.board = generate_new_board(difficulty);
-                    self.save_board();
+                if self.new_game_modal.click_constraints(mouse_x, mouse_y) {
+                    return;
                 }
+
+                difficulty = difficulty.or(self.new_game_modal.click(mouse_x, mouse_y));
+            }
+
+            if is_key_pressed(KeyCode::Escape) {
+                self.new_game_modal.hide();
+            } else if let Some(difficulty) = difficulty {
+                force_update = true;
+                self.new_game(difficulty);
+            }
+        } else if self.options_modal.show {
+            let options_modal = &mut self.options_modal;
+            let mut settings = self.settings.clone();
+            let mut changed = false;
+            let share_code = PuzzleCode::from_board(&self.board).encode();
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                options_modal.networking = self.race.is_some() || self.coop.is_some();
+                options_modal.booklet_working = self.booklet_job.is_some();
+            }
+            egui_macroquad::ui(|egui_ctx| {
+                changed = options_modal.ui(
+                    egui_ctx,
+                    &mut settings,
+                    &self.themes,
+                    self.theme.dark,
+                    self.language,
+                    &share_code,
+                );
+            });
+
+            if is_key_pressed(KeyCode::Escape) {
+                self.options_modal.hide();
+            }
+
+            if changed {
+                self.apply_settings(settings);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some((count, difficulty)) = self.options_modal.booklet_request.take() {
+                self.export_booklet(count, difficulty);
+            }
+
+            if let Some(request) = self.options_modal.replay_request.take() {
+                self.handle_replay_request(request);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(request) = self.options_modal.network_request.take() {
+                self.handle_network_request(request);
             }
         } else {
             self.handle_input();
+
+            if !self.board.is_victory() {
+                self.board.elapsed += get_frame_time() as f64;
+            }
+        }
+
+        // a replay moves the board on its own, that isn't read out or played
+        if !self.replay_modal.is_open() {
+            if let Some(text) = self.announcer.update(&self.board, self.language) {
+                announce(&text);
+            }
+            if let Some(event) = self.sound_cues.update(&self.board) {
+                self.audio.play(event, &self.settings);
+            }
         }
 
         self.height = screen_height();
@@ -232,6 +1187,12 @@ impl Context {
 
         self.board
             .update(self.board_size, self.game_padding, self.portrait);
+        self.zoom = Zoom::new(Rect::new(
+            self.game_padding,
+            self.game_padding,
+            self.board_size,
+            self.board_size,
+        ));
 
         self.menu
             .update(self.board_size, self.game_padding, self.portrait);
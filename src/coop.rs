@@ -0,0 +1,850 @@
+// several players filling in one board together over the local network.
+// the host keeps the order: every change goes through it, gets the next
+// sequence number and is sent to everyone, so all the boards apply the
+// same changes in the same order
+//
+// a change to a cell that someone else changed since the player last
+// heard from the host loses, the first change to reach the host wins.
+// undo takes back the player's own last change, leaving alone the cells
+// someone else has changed since
+
+use std::net::{TcpListener, ToSocketAddrs};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::cell_state::CellState;
+use crate::move_log::CellRecord;
+use crate::net::{accept, listen, Connection};
+use crate::puzzle_code::PuzzleCode;
+use crate::{is_legal_index, MAX_PLAYERS};
+
+pub const DEFAULT_COOP_PORT: u16 = 7880;
+
+// a cell as it is after a change and who it belongs to now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoopCell {
+    pub record: CellRecord,
+    pub owner: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CoopMessage {
+    // host to a new player, the puzzle and everything filled in so far
+    Welcome {
+        player: u8,
+        seq: u64,
+        puzzle: String,
+        cells: Vec<CoopCell>,
+    },
+    // player to host, seen is the last change the player had
+    Move {
+        seen: u64,
+        cells: Vec<CellRecord>,
+    },
+    Undo,
+    // player to host and host to everyone else
+    Cursor {
+        player: u8,
+        index: Option<usize>,
+    },
+    // host to everyone, rejected only matters to the player who made the change
+    Applied {
+        seq: u64,
+        player: u8,
+        cells: Vec<CoopCell>,
+        rejected: Vec<CellRecord>,
+    },
+    Left {
+        player: u8,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct HistoryCell {
+    before: CellRecord,
+    before_seq: u64,
+    before_owner: Option<u8>,
+    after_index: usize,
+}
+
+// one change in the merged history, whoever made it
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    seq: u64,
+    player: u8,
+    cells: Vec<HistoryCell>,
+    undone: bool,
+}
+
+// a change the host has put in order, to be sent to everyone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Applied {
+    pub seq: u64,
+    pub player: u8,
+    pub cells: Vec<CoopCell>,
+    pub rejected: Vec<CellRecord>,
+}
+
+fn is_empty_record(record: &CellRecord) -> bool {
+    record.number.is_none()
+        && record.pencil.is_empty()
+        && record.corner.is_empty()
+        && record.color.is_none()
+}
+
+// the board everyone agrees on, with who owns each cell
+pub struct SharedBoard {
+    pub seq: u64,
+    pub owners: [Option<u8>; 81],
+    // the change each cell was last set by
+    changed: [u64; 81],
+    // the agreed cells, plus on a player's side its own changes on their way
+    cells: [CellState; 81],
+    // only the host keeps one
+    history: Vec<HistoryEntry>,
+}
+
+impl SharedBoard {
+    pub fn new(board: &Board) -> Self {
+        SharedBoard {
+            seq: 0,
+            owners: [None; 81],
+            changed: [0; 81],
+            cells: board.cell_state,
+            history: vec![],
+        }
+    }
+
+    // the cells the local player changed since the last call
+    pub fn local_changes(&self, board: &Board) -> Vec<CellRecord> {
+        (0..81)
+            .map(|index| {
+                (
+                    CellRecord::from_cell(index, &self.cells[index]),
+                    CellRecord::from_cell(index, &board.cell_state[index]),
+                )
+            })
+            .filter(|(old, new)| old != new)
+            .map(|(_, new)| new)
+            .collect()
+    }
+
+    fn set_cell(&mut self, board: &mut Board, seq: u64, cell: &CoopCell) {
+        let index = cell.record.index;
+        cell.record.apply(&mut self.cells[index]);
+        cell.record.apply(&mut board.cell_state[index]);
+        self.changed[index] = seq;
+        self.owners[index] = cell.owner;
+    }
+
+    // the host puts a change in order, it is already on the host's board
+    // when the host made it
+    pub fn resolve(
+        &mut self,
+        board: &mut Board,
+        player: u8,
+        seen: u64,
+        records: &[CellRecord],
+    ) -> Option<Applied> {
+        let seq = self.seq + 1;
+        let mut cells = vec![];
+        let mut rejected = vec![];
+        let mut history = vec![];
+
+        for record in records {
+            // a record that can't be on a board is dropped, it comes
+            // from anyone on the network
+            let index = record.index;
+            if !record.is_legal() || self.cells[index].initial {
+                continue;
+            }
+
+            let before = CellRecord::from_cell(index, &self.cells[index]);
+            // someone else got there first, the player's board goes back
+            if self.changed[index] > seen && self.owners[index] != Some(player) {
+                rejected.push(before);
+                continue;
+            }
+            if *record == before {
+                continue;
+            }
+
+            history.push(HistoryCell {
+                before,
+                before_seq: self.changed[index],
+                before_owner: self.owners[index],
+                after_index: index,
+            });
+            let cell = CoopCell {
+                record: record.clone(),
+                owner: (!is_empty_record(record)).then_some(player),
+            };
+            self.set_cell(board, seq, &cell);
+            cells.push(cell);
+        }
+
+        if cells.is_empty() && rejected.is_empty() {
+            return None;
+        }
+
+        self.seq = seq;
+        if !history.is_empty() {
+            self.history.push(HistoryEntry {
+                seq,
+                player,
+                cells: history,
+                undone: false,
+            });
+        }
+
+        Some(Applied {
+            seq,
+            player,
+            cells,
+            rejected,
+        })
+    }
+
+    // the host takes back the player's last change that still shows
+    pub fn resolve_undo(&mut self, board: &mut Board, player: u8) -> Option<Applied> {
+        let seq = self.seq + 1;
+
+        while let Some(position) = self
+            .history
+            .iter()
+            .rposition(|entry| entry.player == player && !entry.undone)
+        {
+            let entry = &mut self.history[position];
+            entry.undone = true;
+            let entry_seq = entry.seq;
+            let history = entry.cells.clone();
+
+            let mut cells = vec![];
+            for cell in history {
+                // changed by someone since, their change stays
+                if self.changed[cell.after_index] != entry_seq {
+                    continue;
+                }
+
+                let coop_cell = CoopCell {
+                    record: cell.before,
+                    owner: cell.before_owner,
+                };
+                self.set_cell(board, seq, &coop_cell);
+                self.changed[cell.after_index] = cell.before_seq;
+                cells.push(coop_cell);
+            }
+
+            if !cells.is_empty() {
+                self.seq = seq;
+                return Some(Applied {
+                    seq,
+                    player,
+                    cells,
+                    rejected: vec![],
+                });
+            }
+        }
+
+        None
+    }
+
+    // a change the host put in order, on a player's side
+    pub fn apply(&mut self, board: &mut Board, applied: &Applied, me: u8) {
+        for cell in applied.cells.iter() {
+            let owner_legal = cell
+                .owner
+                .is_none_or(|owner| (owner as usize) < MAX_PLAYERS);
+            if cell.record.is_legal() && owner_legal {
+                self.set_cell(board, applied.seq, cell);
+            }
+        }
+        if applied.player == me {
+            for record in applied.rejected.iter() {
+                if record.is_legal() {
+                    record.apply(&mut self.cells[record.index]);
+                    record.apply(&mut board.cell_state[record.index]);
+                }
+            }
+        }
+
+        self.seq = applied.seq;
+    }
+
+    fn welcome_cells(&self) -> Vec<CoopCell> {
+        (0..81)
+            .filter(|&index| !self.cells[index].initial)
+            .map(|index| CoopCell {
+                record: CellRecord::from_cell(index, &self.cells[index]),
+                owner: self.owners[index],
+            })
+            .filter(|cell| !is_empty_record(&cell.record))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoopEvent {
+    // a player was handed the board, the whole board changed
+    Started,
+    // someone else changed cells
+    Changed,
+    Joined(u8),
+    Left(u8),
+    // the host went away
+    Disconnected,
+}
+
+struct Peer {
+    player: u8,
+    connection: Connection,
+}
+
+pub struct CoopSession {
+    pub player: u8,
+    // false until a player has been handed the board
+    pub started: bool,
+    listener: Option<TcpListener>,
+    // the players on the host, the host on a player's side
+    peers: Vec<Peer>,
+    pub shared: SharedBoard,
+    pub cursors: [Option<usize>; MAX_PLAYERS],
+}
+
+impl CoopSession {
+    // listens on every interface, the other players are on other machines
+    pub fn host(port: u16, board: &Board) -> std::io::Result<Self> {
+        Self::host_on(("0.0.0.0", port), board)
+    }
+
+    pub fn host_on(address: impl ToSocketAddrs, board: &Board) -> std::io::Result<Self> {
+        Ok(CoopSession {
+            player: 0,
+            started: true,
+            listener: Some(listen(address)?),
+            peers: vec![],
+            shared: SharedBoard::new(board),
+            cursors: [None; MAX_PLAYERS],
+        })
+    }
+
+    pub fn join(address: &str) -> std::io::Result<Self> {
+        Ok(CoopSession {
+            player: 0,
+            started: false,
+            listener: None,
+            peers: vec![Peer {
+                player: 0,
+                connection: Connection::connect(address)?,
+            }],
+            shared: SharedBoard::new(&Board::new()),
+            cursors: [None; MAX_PLAYERS],
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.listener.is_some()
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.listener
+            .as_ref()
+            .and_then(|listener| listener.local_addr().ok())
+            .map(|address| address.port())
+    }
+
+    fn broadcast(&mut self, message: &CoopMessage, except: Option<u8>) {
+        for peer in self.peers.iter_mut() {
+            if Some(peer.player) != except {
+                peer.connection.send(message);
+            }
+        }
+    }
+
+    fn send_applied(&mut self, applied: Applied) {
+        self.broadcast(
+            &CoopMessage::Applied {
+                seq: applied.seq,
+                player: applied.player,
+                cells: applied.cells,
+                rejected: applied.rejected,
+            },
+            None,
+        );
+    }
+
+    fn welcome(&self, player: u8, board: &Board) -> CoopMessage {
+        CoopMessage::Welcome {
+            player,
+            seq: self.shared.seq,
+            puzzle: PuzzleCode::from_board(board).encode(),
+            cells: self.shared.welcome_cells(),
+        }
+    }
+
+    // the host's new game is everyone's new game
+    pub fn restart(&mut self, board: &Board) {
+        if !self.is_host() {
+            return;
+        }
+
+        self.shared = SharedBoard::new(board);
+        for i in 0..self.peers.len() {
+            let message = self.welcome(self.peers[i].player, board);
+            self.peers[i].connection.send(&message);
+        }
+    }
+
+    // takes back this player's last change, on a player's side the host
+    // answers with the change
+    pub fn undo(&mut self, board: &mut Board) {
+        if self.is_host() {
+            if let Some(applied) = self.shared.resolve_undo(board, self.player) {
+                self.send_applied(applied);
+            }
+        } else if self.started {
+            self.broadcast(&CoopMessage::Undo, None);
+        }
+    }
+
+    // once a frame: takes in the other players' changes and sends this one's
+    pub fn update(&mut self, board: &mut Board) -> Vec<CoopEvent> {
+        let mut events = vec![];
+
+        if let Some(connection) = self.listener.as_ref().and_then(accept) {
+            let free = (1..MAX_PLAYERS as u8)
+                .find(|&player| !self.peers.iter().any(|peer| peer.player == player));
+            // a full game turns the player away
+            if let Some(player) = free {
+                let mut peer = Peer { player, connection };
+                peer.connection.send(&self.welcome(player, board));
+                self.peers.push(peer);
+                events.push(CoopEvent::Joined(player));
+            }
+        }
+
+        let mut received = vec![];
+        for peer in self.peers.iter_mut() {
+            let messages: Vec<CoopMessage> = peer.connection.receive();
+            received.extend(messages.into_iter().map(|message| (peer.player, message)));
+        }
+        for (from, message) in received {
+            events.extend(self.receive(board, from, message));
+        }
+
+        let closed: Vec<u8> = self
+            .peers
+            .iter()
+            .filter(|peer| peer.connection.closed)
+            .map(|peer| peer.player)
+            .collect();
+        self.peers.retain(|peer| !peer.connection.closed);
+        for player in closed {
+            self.cursors[player as usize] = None;
+            if self.is_host() {
+                self.broadcast(&CoopMessage::Left { player }, None);
+                events.push(CoopEvent::Left(player));
+            } else {
+                events.push(CoopEvent::Disconnected);
+            }
+        }
+
+        if self.started {
+            self.send_local(board);
+        }
+
+        events
+    }
+
+    fn send_local(&mut self, board: &mut Board) {
+        let records = self.shared.local_changes(board);
+        if !records.is_empty() {
+            if self.is_host() {
+                let seen = self.shared.seq;
+                if let Some(applied) = self.shared.resolve(board, self.player, seen, &records) {
+                    self.send_applied(applied);
+                }
+            } else {
+                for record in records.iter() {
+                    record.apply(&mut self.shared.cells[record.index]);
+                }
+                let seen = self.shared.seq;
+                self.broadcast(
+                    &CoopMessage::Move {
+                        seen,
+                        cells: records,
+                    },
+                    None,
+                );
+            }
+        }
+
+        let cursor = board.cursor_index.or(board.selected_index);
+        let player = self.player as usize;
+        if cursor != self.cursors[player] {
+            self.cursors[player] = cursor;
+            self.broadcast(
+                &CoopMessage::Cursor {
+                    player: self.player,
+                    index: cursor,
+                },
+                None,
+            );
+        }
+    }
+
+    fn receive(&mut self, board: &mut Board, from: u8, message: CoopMessage) -> Option<CoopEvent> {
+        match message {
+            CoopMessage::Move { seen, cells } if self.is_host() => {
+                let applied = self.shared.resolve(board, from, seen, &cells)?;
+                self.send_applied(applied);
+                Some(CoopEvent::Changed)
+            }
+            CoopMessage::Undo if self.is_host() => {
+                let applied = self.shared.resolve_undo(board, from)?;
+                self.send_applied(applied);
+                Some(CoopEvent::Changed)
+            }
+            // a player can only move its own cursor
+            CoopMessage::Cursor { index, .. } if self.is_host() => {
+                let index = index.filter(|&index| is_legal_index(index));
+                self.cursors[from as usize] = index;
+                self.broadcast(
+                    &CoopMessage::Cursor {
+                        player: from,
+                        index,
+                    },
+                    Some(from),
+                );
+                None
+            }
+            CoopMessage::Welcome {
+                player,
+                seq,
+                puzzle,
+                cells,
+            } if !self.is_host() && (player as usize) < MAX_PLAYERS => {
+                let mut welcome = PuzzleCode::decode(&puzzle)
+                    .and_then(|code| code.to_board())
+                    .ok()?;
+                welcome.settings = board.settings.clone();
+                *board = welcome;
+
+                self.player = player;
+                self.started = true;
+                self.cursors = [None; MAX_PLAYERS];
+                self.shared = SharedBoard::new(board);
+                self.shared.apply(
+                    board,
+                    &Applied {
+                        seq,
+                        player,
+                        cells,
+                        rejected: vec![],
+                    },
+                    player,
+                );
+                Some(CoopEvent::Started)
+            }
+            CoopMessage::Applied {
+                seq,
+                player,
+                cells,
+                rejected,
+            } if !self.is_host() && self.started => {
+                let applied = Applied {
+                    seq,
+                    player,
+                    cells,
+                    rejected,
+                };
+                self.shared.apply(board, &applied, self.player);
+                Some(CoopEvent::Changed)
+            }
+            CoopMessage::Cursor { player, index } if (player as usize) < MAX_PLAYERS => {
+                self.cursors[player as usize] = index.filter(|&index| is_legal_index(index));
+                None
+            }
+            CoopMessage::Left { player } if (player as usize) < MAX_PLAYERS => {
+                self.cursors[player as usize] = None;
+                Some(CoopEvent::Left(player))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CoopEvent, CoopSession, SharedBoard};
+    use crate::board::Board;
+    use crate::move_log::CellRecord;
+    use crate::puzzle_code::{test_puzzle, PuzzleCode};
+
+    fn board() -> Board {
+        test_puzzle().to_board().unwrap()
+    }
+
+    fn number(index: usize, number: u8) -> CellRecord {
+        CellRecord {
+            index,
+            number: Some(number),
+            pencil: vec![],
+            corner: vec![],
+            color: None,
+        }
+    }
+
+    #[test]
+    fn first_change_wins() {
+        let mut board = board();
+        let mut shared = SharedBoard::new(&board);
+
+        let applied = shared.resolve(&mut board, 1, 0, &[number(2, 4)]).unwrap();
+        assert_eq!(applied.seq, 1);
+        assert_eq!(board.cell_state[2].number, Some(4));
+        assert_eq!(shared.owners[2], Some(1));
+
+        // player 2 hadn't seen the change, the cell stays player 1's
+        let applied = shared.resolve(&mut board, 2, 0, &[number(2, 1)]).unwrap();
+        assert!(applied.cells.is_empty());
+        assert_eq!(applied.rejected, vec![number(2, 4)]);
+        assert_eq!(board.cell_state[2].number, Some(4));
+
+        // having seen it, player 2 can change it
+        shared.resolve(&mut board, 2, 1, &[number(2, 1)]).unwrap();
+        assert_eq!(board.cell_state[2].number, Some(1));
+        assert_eq!(shared.owners[2], Some(2));
+
+        // givens can't be changed
+        assert_eq!(shared.resolve(&mut board, 1, 3, &[number(0, 1)]), None);
+    }
+
+    #[test]
+    fn undo_own_changes() {
+        let mut board = board();
+        let mut shared = SharedBoard::new(&board);
+
+        shared.resolve(&mut board, 1, 0, &[number(2, 4)]);
+        shared.resolve(&mut board, 2, 1, &[number(3, 6)]);
+        shared.resolve(&mut board, 1, 2, &[number(2, 1)]);
+
+        // player 2's undo only takes back player 2's change
+        shared.resolve_undo(&mut board, 2).unwrap();
+        assert_eq!(board.cell_state[3].number, None);
+        assert_eq!(board.cell_state[2].number, Some(1));
+        assert_eq!(shared.resolve_undo(&mut board, 2), None);
+
+        // player 1 goes back one change at a time
+        shared.resolve_undo(&mut board, 1).unwrap();
+        assert_eq!(board.cell_state[2].number, Some(4));
+        shared.resolve_undo(&mut board, 1).unwrap();
+        assert_eq!(board.cell_state[2].number, None);
+        assert_eq!(shared.owners[2], None);
+
+        // a change someone else overwrote can't be taken back
+        let seen = shared.seq;
+        shared.resolve(&mut board, 1, seen, &[number(6, 2)]);
+        shared.resolve(&mut board, 2, seen + 1, &[number(6, 7)]);
+        assert_eq!(shared.resolve_undo(&mut board, 1), None);
+        assert_eq!(board.cell_state[6].number, Some(7));
+    }
+
+    // updates both sides until the condition holds
+    fn settle(
+        host: &mut CoopSession,
+        host_board: &mut Board,
+        guest: &mut CoopSession,
+        guest_board: &mut Board,
+        done: impl Fn(&Board, &Board) -> bool,
+    ) -> Vec<CoopEvent> {
+        let start = Instant::now();
+        let mut events = vec![];
+        while !done(host_board, guest_board) && start.elapsed() < Duration::from_secs(5) {
+            events.extend(host.update(host_board));
+            events.extend(guest.update(guest_board));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        events
+    }
+
+    #[test]
+    fn play_together_on_localhost() {
+        let mut host_board = board();
+        let mut host = CoopSession::host_on("127.0.0.1:0", &host_board).unwrap();
+        host_board.cell_state[2].set_number(4);
+        let address = format!("127.0.0.1:{}", host.port().unwrap());
+
+        let mut guest_board = Board::new();
+        let mut guest = CoopSession::join(&address).unwrap();
+        let events = settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |_, guest| guest.cell_state[2].number == Some(4),
+        );
+        assert!(events.contains(&CoopEvent::Joined(1)));
+        assert!(events.contains(&CoopEvent::Started));
+        assert_eq!(guest.player, 1);
+        assert!(guest_board.cell_state[0].initial);
+
+        // the guest's change reaches the host and comes back owned by the guest
+        guest_board.cell_state[3].set_number(6);
+        settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |host, _| host.cell_state[3].number == Some(6),
+        );
+        assert_eq!(host.shared.owners[3], Some(1));
+        assert_eq!(host.shared.owners[2], Some(0));
+
+        // the guest's undo leaves the host's number alone
+        guest.undo(&mut guest_board);
+        settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |host, guest| {
+                host.cell_state[3].number.is_none() && guest.cell_state[3].number.is_none()
+            },
+        );
+        assert_eq!(guest_board.cell_state[2].number, Some(4));
+
+        // the cursors are shared
+        guest_board.cursor_index = Some(40);
+        let start = Instant::now();
+        while host.cursors[1] != Some(40) && start.elapsed() < Duration::from_secs(5) {
+            guest.update(&mut guest_board);
+            host.update(&mut host_board);
+        }
+        assert_eq!(host.cursors[1], Some(40));
+
+        drop(guest);
+        let mut events = vec![];
+        let start = Instant::now();
+        while events.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            events = host.update(&mut host_board);
+        }
+        assert_eq!(events, vec![CoopEvent::Left(1)]);
+    }
+
+    #[test]
+    fn pencil_marks_on_both_sides() {
+        let mut host_board = board();
+        let mut host = CoopSession::host_on("127.0.0.1:0", &host_board).unwrap();
+        let address = format!("127.0.0.1:{}", host.port().unwrap());
+
+        let mut guest_board = Board::new();
+        let mut guest = CoopSession::join(&address).unwrap();
+        settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |_, guest| guest.cell_state[0].initial,
+        );
+
+        // a mark from the guest lands in its own slot on the host
+        guest_board.cell_state[5].set_pencil(7);
+        guest_board.cell_state[5].set_corner(2);
+        settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |host, _| host.cell_state[5].has_this_pencil(7),
+        );
+        assert_eq!(
+            host_board.cell_state[5].pencil,
+            guest_board.cell_state[5].pencil
+        );
+        assert!(host_board.cell_state[5].has_this_corner(2));
+
+        // so the host can take it off again, and the guest sees it go
+        host_board.cell_state[5].remove_pencil(7);
+        settle(
+            &mut host,
+            &mut host_board,
+            &mut guest,
+            &mut guest_board,
+            |_, guest| !guest.cell_state[5].has_this_pencil(7),
+        );
+        assert!(!guest_board.cell_state[5].has_center());
+        assert!(guest_board.cell_state[5].has_this_corner(2));
+        assert!(!host_board.cell_state[5].has_center());
+    }
+
+    #[test]
+    fn bad_records_are_dropped() {
+        use std::io::Write;
+        use std::net::{TcpListener, TcpStream};
+
+        // a move straight over the socket, numbers and colours out of range
+        let mut host_board = board();
+        let mut host = CoopSession::host_on("127.0.0.1:0", &host_board).unwrap();
+        let mut stream = TcpStream::connect(("127.0.0.1", host.port().unwrap())).unwrap();
+        let bad = r#"{"type":"move","seen":0,"cells":[
+            {"index":2,"number":0,"pencil":[],"corner":[],"color":null},
+            {"index":3,"number":10,"pencil":[],"corner":[],"color":null},
+            {"index":5,"number":null,"pencil":[12],"corner":[],"color":null},
+            {"index":6,"number":null,"pencil":[],"corner":[],"color":9},
+            {"index":7,"number":4,"pencil":[],"corner":[],"color":null}]}"#;
+        stream
+            .write_all(format!("{}\n", bad.replace('\n', "")).as_bytes())
+            .unwrap();
+
+        let start = Instant::now();
+        while host_board.cell_state[7].number.is_none() && start.elapsed() < Duration::from_secs(5)
+        {
+            host.update(&mut host_board);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(host_board.cell_state[7].number, Some(4));
+        for index in [2, 3, 5, 6] {
+            let cell = &host_board.cell_state[index];
+            assert!(cell.number.is_none() && !cell.has_pencil() && cell.color.is_none());
+        }
+        host_board.update_number_count();
+
+        // a welcome from a host handing out owners and colours that don't exist
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut guest_board = Board::new();
+        let mut guest = CoopSession::join(&address).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let welcome = format!(
+            r#"{{"type":"welcome","player":1,"seq":1,"puzzle":"{}","cells":[{}]}}"#,
+            PuzzleCode::from_board(&board()).encode(),
+            [
+                r#"{"record":{"index":2,"number":4,"pencil":[],"corner":[],"color":null},"owner":7}"#,
+                r#"{"record":{"index":3,"number":null,"pencil":[],"corner":[],"color":0},"owner":null}"#,
+                r#"{"record":{"index":5,"number":6,"pencil":[],"corner":[],"color":null},"owner":0}"#,
+            ]
+            .join(",")
+        );
+        let mut stream = stream;
+        stream
+            .write_all(format!("{}\n", welcome).as_bytes())
+            .unwrap();
+
+        let start = Instant::now();
+        while !guest.started && start.elapsed() < Duration::from_secs(5) {
+            guest.update(&mut guest_board);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(guest.started);
+        assert_eq!(guest_board.cell_state[5].number, Some(6));
+        assert_eq!(guest.shared.owners[5], Some(0));
+        assert_eq!(guest_board.cell_state[2].number, None);
+        assert_eq!(guest.shared.owners[2], None);
+        assert_eq!(guest_board.cell_state[3].color, None);
+    }
+}
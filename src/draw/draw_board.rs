@@ -1,51 +1,45 @@
 use macroquad::{
-    prelude::{BLACK, GRAY},
-    shapes::{draw_line, draw_rectangle},
-    text::draw_text_ex,
+    camera::{set_camera, set_default_camera},
+    prelude::vec2,
+    shapes::{draw_line, draw_rectangle, draw_rectangle_lines, draw_triangle},
+    text::{draw_text_ex, measure_text},
+    window::screen_height,
 };
 
+use crate::board_layout::{box_line_width, cell_background, cell_line_width, corner_slot};
+use crate::draw::draw_common::draw_dashed_rectangle_lines;
 use crate::{
-    board::{Board, BoardMode},
-    cell_location::CellLocation,
-    cell_state::{CellSelection, CellState},
-    context::Context,
-    fonts::font_types::{CellFont, CellPencilFont},
-    index::index_to_xy,
-    CELL_COLOR_HIGHLIGHTED, CELL_COLOR_NORMAL, CELL_COLOR_NORMAL_EMPHASIZE,
-    CELL_COLOR_PENCIL_EMPHASIZE,
+    board::BoardMode, cell_location::CellLocation, cell_state::CellSelection,
+    cell_state::CellState, context::Context, theme::PLAYER_COLORS,
 };
 
 pub fn draw_board(context: &Context) {
-    draw_board_cells(
-        &context.board,
-        &context.font_context.initial_font,
-        &context.font_context.font,
-        &context.font_context.pencil_font,
-    );
+    // a pinched board is drawn through a camera, cut to its own square
+    let camera = context.zoom.camera(screen_height());
+    if let Some(camera) = &camera {
+        set_camera(camera);
+    }
+
+    draw_board_cells(context);
 
     draw_cell_lines(context);
     draw_box_lines(context);
+    draw_player_cursors(context);
+    draw_cursor(context);
+
+    if camera.is_some() {
+        set_default_camera();
+    }
 }
 
-fn draw_cell(
-    cell_state: &CellState,
-    cell_location: &CellLocation,
-    mode: &BoardMode,
-    initial_font: &CellFont,
-    font: &CellFont,
-    pencil_font: &CellPencilFont,
-) {
-    let color = if cell_state.selection == CellSelection::Emphasized {
-        if *mode == BoardMode::Pencil {
-            CELL_COLOR_PENCIL_EMPHASIZE
-        } else {
-            CELL_COLOR_NORMAL_EMPHASIZE
-        }
-    } else if cell_state.selection == CellSelection::Highlighted {
-        CELL_COLOR_HIGHLIGHTED
-    } else {
-        CELL_COLOR_NORMAL
-    };
+fn draw_cell(context: &Context, index: usize) {
+    let board = &context.board;
+    let theme = &context.theme;
+    let fonts = &context.font_context;
+    let cell_state = &board.cell_state[index];
+    let cell_location = &board.cell_location[index];
+
+    let color = cell_background(board, theme, index);
 
     draw_rectangle(
         cell_location.x,
@@ -55,61 +49,211 @@ fn draw_cell(
         color,
     );
 
+    // the colour mark only takes a corner so the selection colour stays visible
+    if let Some(mark) = cell_state.color {
+        let corner = cell_location.size * 0.45;
+        draw_triangle(
+            vec2(cell_location.x, cell_location.y),
+            vec2(cell_location.x + corner, cell_location.y),
+            vec2(cell_location.x, cell_location.y + corner),
+            theme.cell_marks[(mark - 1) as usize],
+        );
+    }
+
+    // drawn on top of the selection colour so both stay visible
+    if board.multi_selection.contains(&index) {
+        let inset = cell_location.size * 0.08;
+        draw_rectangle(
+            cell_location.x + inset,
+            cell_location.y + inset,
+            cell_location.size - (2.0 * inset),
+            cell_location.size - (2.0 * inset),
+            theme.cell_multi_selected,
+        );
+    }
+
+    if context.settings.cell_cues {
+        draw_selection_cue(context, index);
+    }
+
+    let conflicting = board.is_conflicting(index);
+    let wrong = context.settings.show_wrong && board.is_wrong(index);
+
     if cell_state.has_number() {
+        // only permissive entry can leave conflicts on the board
+        let font = if conflicting || wrong {
+            &fonts.conflict_font
+        } else {
+            &fonts.font
+        };
+
         if let Some(n) = cell_state.number {
+            // other glyph sets don't share the width of the western digits
+            let glyph = fonts.digit(n);
+            let measure = measure_text(glyph, Some(font.font), font.params.font_size, 1.0);
+            let mut params = if cell_state.initial {
+                fonts.initial_font.params
+            } else {
+                font.params
+            };
+            // in a shared game the numbers are in the colour of who entered them
+            let owner = context
+                .coop
+                .as_ref()
+                .and_then(|coop| coop.shared.owners[index]);
+            if let (Some(player), false) = (owner, conflicting || wrong || cell_state.initial) {
+                params.color = PLAYER_COLORS[player as usize];
+            }
             draw_text_ex(
-                n.to_string().as_str(),
-                cell_location.x + font.x_offset,
+                glyph,
+                cell_location.x + (cell_location.size / 2.0) - (measure.width / 2.0),
                 cell_location.y + font.y_offset,
-                if cell_state.initial {
-                    initial_font.params
-                } else {
-                    font.params
-                },
+                params,
             );
         }
     } else if cell_state.has_pencil() {
-        for (i, pencil) in cell_state.pencil.iter().enumerate() {
-            if let Some(n) = pencil {
-                let (x, y) = index_to_xy(i, 3);
-
-                draw_text_ex(
-                    n.to_string().as_str(),
-                    cell_location.x + pencil_font.x_offset + (pencil_font.box_size * x as f32),
-                    cell_location.y + pencil_font.y_offset + (pencil_font.box_size * y as f32),
-                    pencil_font.params,
-                );
-            }
+        draw_corner_marks(context, cell_state, cell_location);
+        draw_center_marks(context, cell_state, cell_location);
+    }
+
+    if context.settings.cell_cues {
+        draw_number_cue(context, index, conflicting, wrong);
+    }
+}
+
+// shapes that tell the selection states apart without relying on colour:
+// a solid border for emphasis, a dashed one for pencil emphasis and a
+// corner notch for highlighted cells
+fn draw_selection_cue(context: &Context, index: usize) {
+    let cell_location = &context.board.cell_location[index];
+    let color = context.theme.box_line;
+    let inset = cell_location.size * 0.1;
+    let size = cell_location.size - (2.0 * inset);
+    let line_width = context.board.board_size * 0.004;
+    let line_width = if line_width < 1.0 { 1.0 } else { line_width };
+
+    match context.board.cell_state[index].selection {
+        CellSelection::Emphasized if context.board.mode == BoardMode::Pencil => {
+            draw_dashed_rectangle_lines(
+                cell_location.x + inset,
+                cell_location.y + inset,
+                size,
+                4,
+                line_width,
+                color,
+            );
+        }
+        CellSelection::Emphasized => {
+            draw_rectangle_lines(
+                cell_location.x + inset,
+                cell_location.y + inset,
+                size,
+                size,
+                line_width * 2.0,
+                color,
+            );
         }
+        CellSelection::Highlighted => {
+            let corner = cell_location.size * 0.2;
+            let right = cell_location.x + cell_location.size;
+            let bottom = cell_location.y + cell_location.size;
+            draw_triangle(
+                vec2(right, bottom),
+                vec2(right - corner, bottom),
+                vec2(right, bottom - corner),
+                context.theme.cell_line,
+            );
+        }
+        CellSelection::None => {}
     }
 }
 
-fn draw_board_cells(
-    board: &Board,
-    initial_font: &CellFont,
-    font: &CellFont,
-    pencil_font: &CellPencilFont,
-) {
-    for i in 0..81 {
-        draw_cell(
-            &board.cell_state[i],
-            &board.cell_location[i],
-            &board.mode,
-            initial_font,
-            font,
-            pencil_font,
+// conflicting numbers are underlined, wrong ones are struck through
+fn draw_number_cue(context: &Context, index: usize, conflicting: bool, wrong: bool) {
+    let cell_location = &context.board.cell_location[index];
+    let color = context.theme.cell_text_conflict;
+    let line_width = context.board.board_size * 0.005;
+    let line_width = if line_width < 1.5 { 1.5 } else { line_width };
+    let left = cell_location.x + (cell_location.size * 0.25);
+    let right = cell_location.x + (cell_location.size * 0.75);
+
+    if conflicting {
+        let y = cell_location.y + (cell_location.size * 0.85);
+        draw_line(left, y, right, y, line_width, color);
+    }
+
+    if wrong {
+        draw_line(
+            left,
+            cell_location.y + (cell_location.size * 0.75),
+            right,
+            cell_location.y + (cell_location.size * 0.25),
+            line_width,
+            color,
+        );
+    }
+}
+
+// corner marks fill the corners first, then the edges, following
+// the order of the digits and not their value
+fn draw_corner_marks(context: &Context, cell_state: &CellState, cell_location: &CellLocation) {
+    let pencil_font = &context.font_context.pencil_font;
+    let count = cell_state.corner.iter().flatten().count();
+    for (slot, n) in cell_state.corner.iter().flatten().enumerate() {
+        let (x, y) = corner_slot(slot, count);
+
+        draw_text_ex(
+            context.font_context.digit(*n),
+            cell_location.x + pencil_font.x_offset + (pencil_font.box_size * x),
+            cell_location.y + pencil_font.y_offset + (pencil_font.box_size * y),
+            pencil_font.params,
         );
     }
 }
 
+// centre marks are written as one line in the middle of the cell,
+// shrunk when there are too many of them to fit
+fn draw_center_marks(context: &Context, cell_state: &CellState, cell_location: &CellLocation) {
+    let pencil_font = &context.font_context.pencil_font;
+    let text: String = cell_state
+        .pencil
+        .iter()
+        .flatten()
+        .map(|&n| context.font_context.digit(n))
+        .collect();
+    if text.is_empty() {
+        return;
+    }
+
+    let mut params = pencil_font.params;
+    let mut measure = measure_text(&text, Some(params.font), params.font_size, 1.0);
+    let max_width = cell_location.size * 0.8;
+    if measure.width > max_width {
+        params.font_size = (params.font_size as f32 * max_width / measure.width) as u16;
+        measure = measure_text(&text, Some(params.font), params.font_size, 1.0);
+    }
+
+    draw_text_ex(
+        &text,
+        cell_location.x + (cell_location.size / 2.0) - (measure.width / 2.0),
+        cell_location.y + (cell_location.size / 2.0) + (measure.height / 2.0),
+        params,
+    );
+}
+
+fn draw_board_cells(context: &Context) {
+    for i in 0..81 {
+        draw_cell(context, i);
+    }
+}
+
 fn draw_cell_lines(context: &Context) {
     for x in 1..9 {
         if x % 3 == 0 {
             continue;
         }
 
-        let line_width = context.board.board_size * 0.0025;
-        let line_width = if line_width < 0.5 { 0.5 } else { line_width };
+        let line_width = cell_line_width(context.board.board_size);
 
         let offset =
             context.game_padding + ((x as f32 * context.board.cell_size) - (line_width / 2.0));
@@ -119,7 +263,7 @@ fn draw_cell_lines(context: &Context) {
             offset,
             context.board.board_size + context.game_padding,
             line_width,
-            GRAY,
+            context.theme.cell_line,
         );
         draw_line(
             context.game_padding,
@@ -127,15 +271,14 @@ fn draw_cell_lines(context: &Context) {
             context.board.board_size + context.game_padding,
             offset,
             line_width,
-            GRAY,
+            context.theme.cell_line,
         );
     }
 }
 
 fn draw_box_lines(context: &Context) {
     for x in 0..4 {
-        let line_width = context.board.board_size * 0.005;
-        let line_width = if line_width < 1.0 { 1.0 } else { line_width };
+        let line_width = box_line_width(context.board.board_size);
 
         let offset = context.game_padding
             + ((x as f32 * (3.0 * context.board.cell_size)) - (line_width / 2.0));
@@ -145,7 +288,7 @@ fn draw_box_lines(context: &Context) {
             offset,
             context.board.board_size + context.game_padding,
             line_width,
-            BLACK,
+            context.theme.box_line,
         );
         draw_line(
             context.game_padding - line_width,
@@ -153,7 +296,57 @@ fn draw_box_lines(context: &Context) {
             context.board.board_size + context.game_padding,
             offset,
             line_width,
-            BLACK,
+            context.theme.box_line,
         );
     }
 }
+
+// the other players of a shared game, a thinner frame in each one's colour
+fn draw_player_cursors(context: &Context) {
+    let coop = match &context.coop {
+        Some(coop) => coop,
+        None => return,
+    };
+
+    let line_width = context.board.board_size * 0.005;
+    let line_width = if line_width < 1.5 { 1.5 } else { line_width };
+
+    for (player, cursor) in coop.cursors.iter().enumerate() {
+        if let (Some(index), false) = (cursor, player == coop.player as usize) {
+            let location = &context.board.cell_location[*index];
+            let inset = line_width;
+            draw_rectangle_lines(
+                location.x + inset,
+                location.y + inset,
+                location.size - (2.0 * inset),
+                location.size - (2.0 * inset),
+                line_width,
+                PLAYER_COLORS[player],
+            );
+        }
+    }
+}
+
+fn draw_cursor(context: &Context) {
+    let cursor_index = match context.board.cursor_index {
+        Some(index) => index,
+        None => return,
+    };
+
+    let line_width = context.board.board_size * 0.008;
+    let line_width = if line_width < 2.0 { 2.0 } else { line_width };
+
+    let location = &context.board.cell_location[cursor_index];
+    draw_rectangle_lines(
+        location.x,
+        location.y,
+        location.size,
+        location.size,
+        line_width,
+        if context.board.mode == BoardMode::Normal {
+            context.theme.menu_number_background_normal
+        } else {
+            context.theme.menu_number_background_pencil
+        },
+    );
+}
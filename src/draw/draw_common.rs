@@ -1,6 +1,6 @@
 use macroquad::{
     prelude::{vec2, Color},
-    shapes::{draw_rectangle, draw_triangle},
+    shapes::{draw_line, draw_rectangle, draw_triangle},
 };
 
 fn draw_quarter_circle(center_x: f32, center_y: f32, radius: f32, angle: f32, color: Color) {
@@ -38,3 +38,24 @@ pub fn draw_rounded_rectangle(x: f32, y: f32, width: f32, height: f32, radius: f
     draw_quarter_circle(x + width - radius, y + height - radius, radius, 0.0, color);
     draw_quarter_circle(x + radius, y + height - radius, radius, DEG90, color);
 }
+
+pub fn draw_dashed_rectangle_lines(
+    x: f32,
+    y: f32,
+    size: f32,
+    dashes: u32,
+    thickness: f32,
+    color: Color,
+) {
+    let dash = size / (2 * dashes - 1) as f32;
+
+    for i in 0..dashes {
+        let start = (2 * i) as f32 * dash;
+        let end = start + dash;
+
+        draw_line(x + start, y, x + end, y, thickness, color);
+        draw_line(x + start, y + size, x + end, y + size, thickness, color);
+        draw_line(x, y + start, x, y + end, thickness, color);
+        draw_line(x + size, y + start, x + size, y + end, thickness, color);
+    }
+}
@@ -4,13 +4,21 @@ use crate::context::Context;
 use crate::draw::draw_board::draw_board;
 use crate::draw::draw_menu::draw_menu;
 use crate::draw::draw_new_game_modal::draw_new_game_modal;
+use crate::draw::draw_options_modal::draw_options_modal;
+use crate::draw::draw_race::draw_race;
+use crate::draw::draw_replay_modal::draw_replay_modal;
+use crate::draw::draw_status::draw_status;
 use crate::draw::draw_victory_modal::draw_victory_modal;
 
 pub fn draw_context(context: &Context) {
-    clear_background(WHITE);
+    clear_background(context.theme.background);
 
     draw_board(context);
     draw_menu(context);
+    draw_race(context);
+    draw_status(context);
     draw_new_game_modal(context);
     draw_victory_modal(context);
+    draw_options_modal(context);
+    draw_replay_modal(context);
 }
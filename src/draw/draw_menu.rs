@@ -1,10 +1,13 @@
+use macroquad::shapes::{draw_line, draw_rectangle, draw_rectangle_lines};
 use macroquad::text::draw_text_ex;
 
 use crate::draw::draw_common::draw_rounded_rectangle;
 use crate::{
-    board::BoardMode, context::Context, ICON_DELETE, ICON_NEW, ICON_PENCIL, ICON_PENCIL_SLASH,
-    ICON_UNDO, MENU_DELETE_BACKGROUND, MENU_NUMBER_BACKGROUND_NORMAL,
-    MENU_NUMBER_BACKGROUND_PENCIL,
+    board::{BoardMode, PencilLayer},
+    context::Context,
+    settings::InputStyle,
+    theme::Theme,
+    DIGIT_COUNT, ICON_DELETE, ICON_NEW, ICON_PENCIL, ICON_PENCIL_SLASH, ICON_UNDO,
 };
 
 fn draw_menu_pencil(context: &Context, icon_x_offset: f32, icon_y_offset: f32) {
@@ -66,7 +69,7 @@ fn draw_menu_delete(context: &Context, icon_x_offset: f32, icon_y_offset: f32) {
             context.menu.item_size,
             context.menu.item_size,
             20.0,
-            MENU_DELETE_BACKGROUND,
+            context.theme.menu_delete_background,
         );
 
         draw_text_ex(
@@ -81,18 +84,240 @@ fn draw_menu_delete(context: &Context, icon_x_offset: f32, icon_y_offset: f32) {
     }
 }
 
-fn draw_menu_numbers(context: &Context) {
+fn draw_menu_input_style(context: &Context) {
+    let item = &context.menu.input_style;
     let border_offset = context.board.board_size * 0.005;
     let font_x_offset = (context.font_context.menu_number_font.width / 2.0) + border_offset;
     let font_y_offset = context.font_context.menu_number_font.height
         + (context.font_context.menu_number_font.height / 3.0);
 
-    let selected_number = if context.board.selected_number.is_some() {
-        context.board.selected_number.unwrap()
-    } else {
-        0
+    match context.settings.input_style {
+        // the number is picked first, drawn like a selected menu number
+        InputStyle::NumberFirst => {
+            draw_rounded_rectangle(
+                item.x,
+                item.y,
+                context.menu.item_size,
+                context.menu.item_size,
+                20.0,
+                context.theme.menu_number_background_normal,
+            );
+
+            draw_text_ex(
+                context.font_context.digit(1),
+                item.x + font_x_offset,
+                item.y + font_y_offset,
+                context.font_context.menu_number_font_selected.params,
+            );
+        }
+        // the cell is picked first, drawn like the cursor outline
+        InputStyle::CellFirst => {
+            let line_width = context.board.board_size * 0.008;
+            let line_width = if line_width < 2.0 { 2.0 } else { line_width };
+
+            draw_rectangle_lines(
+                item.x,
+                item.y,
+                context.menu.item_size,
+                context.menu.item_size,
+                line_width,
+                context.theme.menu_number_background_normal,
+            );
+
+            draw_text_ex(
+                context.font_context.digit(1),
+                item.x + font_x_offset,
+                item.y + font_y_offset,
+                context.font_context.menu_number_font.params,
+            );
+        }
+    }
+}
+
+fn draw_eraser(theme: &Theme, x: f32, y: f32, size: f32, line_width: f32) {
+    draw_rectangle_lines(x, y, size, size, line_width, theme.cell_highlighted);
+    draw_line(
+        x,
+        y + size,
+        x + size,
+        y,
+        line_width,
+        theme.menu_delete_background,
+    );
+}
+
+// a 2x2 preview of the palette
+fn draw_palette_preview(theme: &Theme, x: f32, y: f32, item_size: f32) {
+    let padding = item_size * 0.2;
+    let swatch = (item_size - (2.0 * padding)) / 2.0;
+    for (i, color) in theme.cell_marks.iter().take(4).enumerate() {
+        draw_rectangle(
+            x + padding + ((i % 2) as f32 * swatch),
+            y + padding + ((i / 2) as f32 * swatch),
+            swatch,
+            swatch,
+            *color,
+        );
+    }
+}
+
+fn draw_menu_color(context: &Context) {
+    let item = &context.menu.color;
+    if context.board.color_mode {
+        draw_rounded_rectangle(
+            item.x,
+            item.y,
+            context.menu.item_size,
+            context.menu.item_size,
+            20.0,
+            context.theme.menu_number_background_normal,
+        );
+    }
+
+    draw_palette_preview(&context.theme, item.x, item.y, context.menu.item_size);
+}
+
+fn draw_menu_clear_colors(context: &Context) {
+    if !context.board.color_mode {
+        return;
+    }
+
+    let item = &context.menu.clear_colors;
+    let padding = context.menu.item_size * 0.2;
+    let line_width = context.board.board_size * 0.01;
+    let line_width = if line_width < 2.0 { 2.0 } else { line_width };
+
+    draw_palette_preview(&context.theme, item.x, item.y, context.menu.item_size);
+    draw_line(
+        item.x + padding,
+        item.y + context.menu.item_size - padding,
+        item.x + context.menu.item_size - padding,
+        item.y + padding,
+        line_width,
+        context.theme.menu_delete_background,
+    );
+}
+
+fn draw_menu_palette(context: &Context) {
+    let padding = context.menu.item_size * 0.15;
+    let size = context.menu.item_size - (2.0 * padding);
+    let line_width = context.board.board_size * 0.01;
+    let line_width = if line_width < 2.0 { 2.0 } else { line_width };
+
+    for (i, item) in context.menu.numbers.iter().enumerate() {
+        let color = if i + 1 == DIGIT_COUNT as usize {
+            0
+        } else {
+            (i + 1) as u8
+        };
+
+        if color == context.board.selected_color {
+            draw_rounded_rectangle(
+                item.x,
+                item.y,
+                context.menu.item_size,
+                context.menu.item_size,
+                20.0,
+                context.theme.menu_number_background_normal,
+            );
+        }
+
+        if color == 0 {
+            draw_eraser(
+                &context.theme,
+                item.x + padding,
+                item.y + padding,
+                size,
+                line_width,
+            );
+        } else {
+            draw_rectangle(
+                item.x + padding,
+                item.y + padding,
+                size,
+                size,
+                context.theme.cell_marks[i],
+            );
+        }
+    }
+}
+
+// a cell outline with dots where the marks of the current layer go
+fn draw_menu_pencil_layer(context: &Context) {
+    let item = &context.menu.pencil_layer;
+    let padding = context.menu.item_size * 0.2;
+    let size = context.menu.item_size - (2.0 * padding);
+    let line_width = context.board.board_size * 0.005;
+    let line_width = if line_width < 1.0 { 1.0 } else { line_width };
+
+    draw_rectangle_lines(
+        item.x + padding,
+        item.y + padding,
+        size,
+        size,
+        line_width,
+        context.theme.menu_number_background_normal,
+    );
+
+    let dot = size / 5.0;
+    let slots: &[(f32, f32)] = match context.board.pencil_layer {
+        PencilLayer::Corner => &[(0.5, 0.5), (3.5, 0.5), (0.5, 3.5), (3.5, 3.5)],
+        PencilLayer::Center => &[(1.0, 2.0), (2.0, 2.0), (3.0, 2.0)],
     };
 
+    for (x, y) in slots {
+        draw_rectangle(
+            item.x + padding + (x * dot),
+            item.y + padding + (y * dot),
+            dot,
+            dot,
+            context.theme.menu_number_background_pencil,
+        );
+    }
+}
+
+// three sliders, the usual picture for settings
+fn draw_menu_options(context: &Context) {
+    let item = &context.menu.options;
+    let padding = context.menu.item_size * 0.2;
+    let width = context.menu.item_size - (2.0 * padding);
+    let line_width = context.board.board_size * 0.005;
+    let line_width = if line_width < 1.0 { 1.0 } else { line_width };
+    let knob = context.menu.item_size * 0.12;
+
+    for (i, knob_position) in [0.7, 0.3, 0.55].iter().enumerate() {
+        let y = item.y + padding + (width * (i as f32 + 0.5) / 3.0);
+        draw_line(
+            item.x + padding,
+            y,
+            item.x + padding + width,
+            y,
+            line_width,
+            context.theme.menu_number_background_normal,
+        );
+        draw_rectangle(
+            item.x + padding + (width * knob_position) - (knob / 2.0),
+            y - (knob / 2.0),
+            knob,
+            knob,
+            context.theme.menu_number_background_normal,
+        );
+    }
+}
+
+fn draw_menu_numbers(context: &Context) {
+    if context.board.color_mode {
+        draw_menu_palette(context);
+        return;
+    }
+
+    let border_offset = context.board.board_size * 0.005;
+    let font_x_offset = (context.font_context.menu_number_font.width / 2.0) + border_offset;
+    let font_y_offset = context.font_context.menu_number_font.height
+        + (context.font_context.menu_number_font.height / 3.0);
+
+    let selected_number = context.board.selected_number.unwrap_or(0);
+
     for (i, number) in context.menu.numbers.iter().enumerate() {
         if context.board.number_count[i] == 9 {
             continue;
@@ -107,15 +332,15 @@ fn draw_menu_numbers(context: &Context) {
                 context.menu.item_size,
                 20.0,
                 if context.board.mode == BoardMode::Normal {
-                    MENU_NUMBER_BACKGROUND_NORMAL
+                    context.theme.menu_number_background_normal
                 } else {
-                    MENU_NUMBER_BACKGROUND_PENCIL
+                    context.theme.menu_number_background_pencil
                 },
             );
         }
 
         draw_text_ex(
-            digit.to_string().as_str(),
+            context.font_context.digit(digit as u8),
             number.x + font_x_offset,
             number.y + font_y_offset,
             if digit == selected_number as usize {
@@ -138,4 +363,9 @@ pub fn draw_menu(context: &Context) {
     draw_menu_undo(context, icon_x_offset, icon_y_offset);
     draw_menu_delete(context, icon_x_offset, icon_y_offset);
     draw_menu_new(context, icon_x_offset, icon_y_offset);
+    draw_menu_input_style(context);
+    draw_menu_color(context);
+    draw_menu_clear_colors(context);
+    draw_menu_pencil_layer(context);
+    draw_menu_options(context);
 }
@@ -1,36 +1,92 @@
 use macroquad::{
-    prelude::WHITE,
-    shapes::{draw_poly, draw_rectangle},
+    shapes::{draw_poly, draw_rectangle, draw_rectangle_lines},
     text::draw_text_ex,
     window::{screen_height, screen_width},
 };
 
+use crate::constraints::{KING_OFFSETS, KNIGHT_OFFSETS};
 use crate::draw::draw_common::draw_rounded_rectangle;
+use crate::menu_item::MenuItem;
+use crate::theme::Theme;
 use crate::{
     context::Context, ICON_DIFFICULTY_1, ICON_DIFFICULTY_2, ICON_DIFFICULTY_3, ICON_DIFFICULTY_NEW,
-    MODAL_BACKGROUND,
 };
 
+// draws the chess move as a small 5x5 grid of dots around the piece
+fn draw_constraint_toggle(theme: &Theme, item: &MenuItem, offsets: &[(i32, i32)], active: bool) {
+    if active {
+        draw_rounded_rectangle(
+            item.x,
+            item.y,
+            item.size,
+            item.size,
+            item.size * 0.2,
+            theme.menu_number_background_normal,
+        );
+    }
+
+    let (piece_color, move_color) = if active {
+        (theme.menu_text_selected, theme.cell_emphasize)
+    } else {
+        (theme.menu_number_background_normal, theme.cell_highlighted)
+    };
+
+    let dot_box = item.size / 5.0;
+    let dot_size = dot_box * 0.7;
+    let dot_offset = (dot_box - dot_size) / 2.0;
+
+    draw_rectangle(
+        item.x + (2.0 * dot_box) + dot_offset,
+        item.y + (2.0 * dot_box) + dot_offset,
+        dot_size,
+        dot_size,
+        piece_color,
+    );
+
+    for (row, col) in offsets {
+        draw_rectangle(
+            item.x + ((2 + col) as f32 * dot_box) + dot_offset,
+            item.y + ((2 + row) as f32 * dot_box) + dot_offset,
+            dot_size,
+            dot_size,
+            move_color,
+        );
+    }
+}
+
 pub fn draw_new_game_modal(context: &Context) {
     if !context.new_game_modal.show {
         return;
     }
 
-    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), MODAL_BACKGROUND);
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        context.theme.modal_background,
+    );
     draw_rounded_rectangle(
         context.new_game_modal.x,
         context.new_game_modal.y,
         context.new_game_modal.width,
         context.new_game_modal.height,
         20.0,
-        WHITE,
+        context.theme.modal_panel,
     );
 
     let title_width = context.new_game_modal.width / 3.0;
     let title_x = context.new_game_modal.x + (context.new_game_modal.width / 2.0);
     let title_y =
-        context.new_game_modal.y - (context.new_game_modal.height / 3.0) + (title_width / 2.0);
-    draw_poly(title_x, title_y, 80, title_width / 2.0, 0., WHITE);
+        context.new_game_modal.y - (context.new_game_modal.row_height / 3.0) + (title_width / 2.0);
+    draw_poly(
+        title_x,
+        title_y,
+        80,
+        title_width / 2.0,
+        0.,
+        context.theme.modal_panel,
+    );
 
     draw_text_ex(
         ICON_DIFFICULTY_NEW,
@@ -40,9 +96,22 @@ pub fn draw_new_game_modal(context: &Context) {
         context.font_context.modal_difficulty_title_font.params,
     );
 
-    let extra_y_offset = (context.new_game_modal.height / 3.0)
+    let extra_y_offset = (context.new_game_modal.row_height / 3.0)
         + (context.font_context.modal_difficulty_font_1.height / 2.0);
 
+    // a frame around the difficulty a controller is on
+    if let Some(item) = context.new_game_modal.focused_item() {
+        let padding = item.size * 0.15;
+        draw_rectangle_lines(
+            item.x - padding,
+            item.y - padding,
+            item.size + (2.0 * padding),
+            item.size + (2.0 * padding),
+            item.size * 0.06,
+            context.theme.menu_number_background_normal,
+        );
+    }
+
     draw_text_ex(
         ICON_DIFFICULTY_1,
         context.new_game_modal.difficulty_1.x,
@@ -61,4 +130,17 @@ pub fn draw_new_game_modal(context: &Context) {
         context.new_game_modal.difficulty_3.y + extra_y_offset,
         context.font_context.modal_difficulty_font_3.params,
     );
+
+    draw_constraint_toggle(
+        &context.theme,
+        &context.new_game_modal.anti_knight,
+        &KNIGHT_OFFSETS,
+        context.new_game_modal.constraints.anti_knight,
+    );
+    draw_constraint_toggle(
+        &context.theme,
+        &context.new_game_modal.anti_king,
+        &KING_OFFSETS,
+        context.new_game_modal.constraints.anti_king,
+    );
 }
@@ -0,0 +1,23 @@
+use macroquad::{
+    shapes::draw_rectangle,
+    window::{screen_height, screen_width},
+};
+
+use crate::context::Context;
+
+pub fn draw_options_modal(context: &Context) {
+    if !context.options_modal.show {
+        return;
+    }
+
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        context.theme.modal_background,
+    );
+
+    // the window itself was laid out by egui during the update
+    egui_macroquad::draw();
+}
@@ -0,0 +1,31 @@
+use macroquad::shapes::draw_rectangle;
+
+use crate::context::Context;
+
+// a thin bar along the bottom of each number, as full as the
+// opponent's count of it
+pub fn draw_race(context: &Context) {
+    let opponent = match context.race.as_ref().and_then(|race| race.opponent) {
+        Some(opponent) => opponent,
+        None => return,
+    };
+
+    let item_size = context.menu.item_size;
+    let height = item_size * 0.06;
+    let inset = item_size * 0.15;
+    let width = item_size - (2.0 * inset);
+
+    for (number, count) in context.menu.numbers.iter().zip(opponent.number_count) {
+        let y = number.y + item_size - (2.0 * height);
+        let mut track = context.theme.status_text;
+        track.a *= 0.25;
+        draw_rectangle(number.x + inset, y, width, height, track);
+        draw_rectangle(
+            number.x + inset,
+            y,
+            width * (count as f32 / 9.0),
+            height,
+            context.theme.status_text,
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use crate::context::Context;
+
+pub fn draw_replay_modal(context: &Context) {
+    if !context.replay_modal.is_open() {
+        return;
+    }
+
+    // the board stays visible, only the controls are drawn on top
+    egui_macroquad::draw();
+}
@@ -0,0 +1,59 @@
+use macroquad::text::draw_text_ex;
+
+use crate::{context::Context, format_time};
+
+pub fn draw_status(context: &Context) {
+    let mut parts = vec![];
+    if context.settings.show_timer {
+        parts.push(format_time(context.board.elapsed));
+    }
+    if context.settings.show_mistakes {
+        parts.push(format!(
+            "{}: {}",
+            context.language.text("status.mistakes"),
+            context.board.mistakes
+        ));
+    }
+
+    if let Some(race) = &context.race {
+        let text = |key| context.language.text(key);
+        match race.opponent {
+            Some(opponent) => parts.push(format!(
+                "{}: {}/81  {}: {}",
+                text("race.opponent"),
+                opponent.filled(),
+                text("status.mistakes"),
+                opponent.mistakes
+            )),
+            None if !race.is_connected() => parts.push(text("race.waiting").to_string()),
+            None => {}
+        }
+    }
+
+    // which button does what, while a controller is in use
+    #[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+    if context.gamepad.active {
+        parts.push(context.language.text("gamepad.prompts").to_string());
+    }
+
+    if parts.is_empty() {
+        return;
+    }
+
+    let text = parts.join("   ");
+    let status = &context.menu.status;
+    let mut params = context.font_context.status_font.params;
+
+    if context.portrait {
+        draw_text_ex(
+            &text,
+            status.x,
+            status.y + context.font_context.status_font.height,
+            params,
+        );
+    } else {
+        // the landscape menu only has room for a column, the text runs downwards
+        params.rotation = std::f32::consts::FRAC_PI_2;
+        draw_text_ex(&text, status.x, status.y, params);
+    }
+}
@@ -1,29 +1,68 @@
 use macroquad::{
-    prelude::WHITE,
     shapes::draw_rectangle,
-    text::draw_text_ex,
+    text::{draw_text_ex, measure_text, TextParams},
     window::{screen_height, screen_width},
 };
 
 use crate::draw::draw_common::draw_rounded_rectangle;
-use crate::{context::Context, ICON_VICTORY_HEART, ICON_VICTORY_STAR, MODAL_BACKGROUND};
+use crate::victory_modal::ModalButton;
+use crate::{context::Context, ICON_VICTORY_HEART, ICON_VICTORY_STAR};
+
+// centred on x, shrunk when it doesn't fit in the width
+fn draw_centered_text(text: &str, x: f32, baseline: f32, max_width: f32, params: TextParams) {
+    let mut params = params;
+    let measure = measure_text(text, Some(params.font), params.font_size, 1.0);
+    if measure.width > max_width {
+        params.font_scale = max_width / measure.width;
+    }
+
+    let width = measure.width * params.font_scale;
+    draw_text_ex(text, x - (width / 2.0), baseline, params);
+}
+
+fn draw_button(context: &Context, button: &ModalButton, label: &str) {
+    draw_rounded_rectangle(
+        button.x,
+        button.y,
+        button.width,
+        button.height,
+        button.height * 0.25,
+        context.theme.menu_number_background_normal,
+    );
+
+    let mut params = context.font_context.status_font.params;
+    params.color = context.theme.menu_text_selected;
+    draw_centered_text(
+        label,
+        button.x + (button.width / 2.0),
+        button.y + (button.height / 2.0) + (context.font_context.status_font.height / 2.0),
+        button.width * 0.9,
+        params,
+    );
+}
 
 pub fn draw_victory_modal(context: &Context) {
     if !context.victory_modal.show {
         return;
     }
 
-    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), MODAL_BACKGROUND);
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        context.theme.modal_background,
+    );
     draw_rounded_rectangle(
         context.victory_modal.x,
         context.victory_modal.y,
         context.victory_modal.width,
         context.victory_modal.height,
         20.0,
-        WHITE,
+        context.theme.modal_panel,
     );
 
-    let extra_y_offset = (context.victory_modal.height / 3.0)
+    let extra_y_offset = (context.victory_modal.star_row_height / 3.0)
         + (context.font_context.modal_victory_star_font.height / 2.0);
 
     draw_text_ex(
@@ -44,4 +83,36 @@ pub fn draw_victory_modal(context: &Context) {
         context.victory_modal.star_2.y + extra_y_offset,
         context.font_context.modal_victory_star_font.params,
     );
+
+    let modal = &context.victory_modal;
+    if let Some(summary) = &modal.summary {
+        let mut params = context.font_context.status_font.params;
+        params.color = context.theme.modal_text;
+        let headline = modal
+            .headline
+            .map(|key| context.language.text(key).to_string());
+        let lines = headline.into_iter().chain(summary.lines(context.language));
+        for (i, line) in lines.enumerate() {
+            draw_centered_text(
+                &line,
+                modal.x + (modal.width / 2.0),
+                modal.y
+                    + modal.star_row_height
+                    + (i as f32 * modal.line_height)
+                    + context.font_context.status_font.height,
+                modal.width * 0.9,
+                params,
+            );
+        }
+    }
+
+    let text = |key| context.language.text(key);
+    draw_button(context, &modal.new_game, text("victory.new"));
+    draw_button(context, &modal.replay, text("victory.replay"));
+    let copy_label = if modal.copied {
+        text("victory.copied")
+    } else {
+        text("victory.copy")
+    };
+    draw_button(context, &modal.copy, copy_label);
 }
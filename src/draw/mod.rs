@@ -3,4 +3,8 @@ pub mod draw_common;
 pub mod draw_context;
 pub mod draw_menu;
 pub mod draw_new_game_modal;
+pub mod draw_options_modal;
+pub mod draw_race;
+pub mod draw_replay_modal;
+pub mod draw_status;
 pub mod draw_victory_modal;
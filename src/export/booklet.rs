@@ -0,0 +1,331 @@
+use macroquad::color::Color;
+
+use super::pdf::to_pdf;
+use super::{ExportOptions, Picture, TextFont};
+use crate::board::Board;
+use crate::constraints::Constraints;
+use crate::generate::solve_puzzle;
+use crate::locale::{difficulty_key, DigitGlyphs, Language};
+use crate::theme::Theme;
+
+// a4 in points
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+const MARGIN: f32 = 36.0;
+const GAP: f32 = 24.0;
+const TITLE_SIZE: f32 = 16.0;
+const TITLE_HEIGHT: f32 = 32.0;
+const LABEL_SIZE: f32 = 10.0;
+const LABEL_HEIGHT: f32 = 18.0;
+const FOOTER_SIZE: f32 = 9.0;
+
+pub const PER_PAGE_CHOICES: [usize; 4] = [1, 2, 4, 6];
+pub const DEFAULT_PER_PAGE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookletPuzzle {
+    pub givens: [u8; 81],
+    pub solution: [u8; 81],
+    pub difficulty: Option<u8>,
+}
+
+impl BookletPuzzle {
+    pub fn generate(difficulty: u8, constraints: Constraints) -> Self {
+        let board = Board::generate(difficulty, constraints);
+
+        let mut givens = [0; 81];
+        let mut solution = [0; 81];
+        for (i, cell) in board.cell_state.iter().enumerate() {
+            givens[i] = cell.number.unwrap_or(0);
+            solution[i] = board.solution[i];
+        }
+
+        BookletPuzzle {
+            givens,
+            solution,
+            difficulty: Some(difficulty),
+        }
+    }
+
+    // 81 characters, a digit for each given and 0 or . for each blank,
+    // then optionally the difficulty from 1 to 3
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut parts = line.split_whitespace();
+        let cells = parts.next().unwrap_or_default();
+        if cells.chars().count() != 81 {
+            return Err(format!(
+                "expected 81 cells, found {}",
+                cells.chars().count()
+            ));
+        }
+
+        let mut grid = [[0; 9]; 9];
+        for (i, character) in cells.chars().enumerate() {
+            grid[i / 9][i % 9] = match character {
+                '.' | '0' => 0,
+                '1'..='9' => character as u8 - b'0',
+                _ => return Err(format!("{} is not a cell", character)),
+            };
+        }
+
+        let difficulty = match parts.next() {
+            Some(difficulty) => match difficulty.parse::<u8>() {
+                Ok(level @ 1..=3) => Some(level),
+                _ => return Err(format!("{} is not a difficulty", difficulty)),
+            },
+            None => None,
+        };
+
+        let solved = solve_puzzle(&grid, &Constraints::default())
+            .ok_or_else(|| "the puzzle has no solution".to_string())?;
+
+        Ok(BookletPuzzle {
+            givens: flatten(&grid),
+            solution: flatten(&solved),
+            difficulty,
+        })
+    }
+
+    // the same givens always make the same id, so a solution
+    // can be matched to its puzzle whatever the order of the pages
+    pub fn id(&self) -> String {
+        let hash = self.givens.iter().fold(0x811c9dc5_u32, |hash, &given| {
+            (hash ^ given as u32).wrapping_mul(0x01000193)
+        });
+        format!("{:08x}", hash)
+    }
+
+    fn board(&self, solved: bool) -> Board {
+        let mut board = Board::new();
+        for i in 0..81 {
+            board.cell_state[i].set_initial_number(self.givens[i]);
+            if solved {
+                board.cell_state[i].set_number(self.solution[i]);
+            }
+        }
+
+        board
+    }
+}
+
+fn flatten(grid: &[[u8; 9]; 9]) -> [u8; 81] {
+    let mut cells = [0; 81];
+    for (i, value) in grid.iter().flatten().enumerate() {
+        cells[i] = *value;
+    }
+
+    cells
+}
+
+// one puzzle a line, blank lines and lines starting with # are skipped
+pub fn parse_collection(text: &str) -> Result<Vec<BookletPuzzle>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            BookletPuzzle::parse(line).map_err(|error| format!("line {}: {}", i + 1, error))
+        })
+        .collect()
+}
+
+fn page_grid(per_page: usize) -> (usize, usize) {
+    match per_page {
+        1 => (1, 1),
+        2 => (1, 2),
+        4 => (2, 2),
+        _ => (2, 3),
+    }
+}
+
+fn label(puzzle: &BookletPuzzle, number: usize, language: Language) -> String {
+    match puzzle.difficulty {
+        Some(difficulty) => format!(
+            "{}  \u{b7}  {}  \u{b7}  {}",
+            number,
+            language.text(difficulty_key(difficulty)),
+            puzzle.id()
+        ),
+        None => format!("{}  \u{b7}  {}", number, puzzle.id()),
+    }
+}
+
+fn add_pages(
+    pages: &mut Vec<Picture>,
+    puzzles: &[BookletPuzzle],
+    per_page: usize,
+    title: &str,
+    solved: bool,
+    options: &ExportOptions,
+    language: Language,
+) {
+    let theme = Theme::light();
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+    let (columns, rows) = page_grid(per_page);
+    let slot_width = (PAGE_WIDTH - (2.0 * MARGIN) - (GAP * (columns - 1) as f32)) / columns as f32;
+    let slot_height =
+        (PAGE_HEIGHT - (2.0 * MARGIN) - TITLE_HEIGHT - (GAP * (rows - 1) as f32)) / rows as f32;
+    let board_size = slot_width.min(slot_height - LABEL_HEIGHT);
+
+    for (page_number, chunk) in puzzles.chunks(columns * rows).enumerate() {
+        let mut page = Picture {
+            width: PAGE_WIDTH,
+            height: PAGE_HEIGHT,
+            shapes: vec![],
+        };
+        page.rect(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT, theme.background);
+        page.text(
+            PAGE_WIDTH / 2.0,
+            MARGIN + (TITLE_HEIGHT / 2.0),
+            TITLE_SIZE,
+            title,
+            black,
+            TextFont::Label,
+        );
+
+        for (i, puzzle) in chunk.iter().enumerate() {
+            let number = (page_number * columns * rows) + i + 1;
+            let slot_x = MARGIN + ((i % columns) as f32 * (slot_width + GAP));
+            let slot_y = MARGIN + TITLE_HEIGHT + ((i / columns) as f32 * (slot_height + GAP));
+            let left = slot_x + ((slot_width - board_size) / 2.0);
+
+            page.text(
+                slot_x + (slot_width / 2.0),
+                slot_y + (LABEL_HEIGHT / 2.0),
+                LABEL_SIZE,
+                &label(puzzle, number, language),
+                black,
+                TextFont::Label,
+            );
+            page.add_board(
+                &puzzle.board(solved),
+                &theme,
+                options,
+                left,
+                slot_y + LABEL_HEIGHT,
+                board_size,
+            );
+        }
+
+        pages.push(page);
+    }
+}
+
+// the puzzles first, several to a page, then their solutions in the same order
+pub fn booklet_pages(
+    puzzles: &[BookletPuzzle],
+    per_page: usize,
+    language: Language,
+    digit_glyphs: DigitGlyphs,
+) -> Vec<Picture> {
+    // helvetica has no arabic-indic digits
+    let digit_glyphs = match digit_glyphs {
+        DigitGlyphs::ArabicIndic => DigitGlyphs::Western,
+        other => other,
+    };
+    let options = ExportOptions {
+        digit_glyphs,
+        ..ExportOptions::new()
+    };
+
+    let mut pages = vec![];
+    add_pages(
+        &mut pages, puzzles, per_page, "Sudoku", false, &options, language,
+    );
+    add_pages(
+        &mut pages,
+        puzzles,
+        per_page,
+        language.text("booklet.solutions"),
+        true,
+        &options,
+        language,
+    );
+
+    let total = pages.len();
+    for (i, page) in pages.iter_mut().enumerate() {
+        page.text(
+            PAGE_WIDTH / 2.0,
+            PAGE_HEIGHT - (MARGIN / 2.0),
+            FOOTER_SIZE,
+            &format!("{} / {}", i + 1, total),
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            TextFont::Label,
+        );
+    }
+
+    pages
+}
+
+pub fn booklet_pdf(
+    puzzles: &[BookletPuzzle],
+    per_page: usize,
+    language: Language,
+    digit_glyphs: DigitGlyphs,
+) -> Vec<u8> {
+    to_pdf(&booklet_pages(puzzles, per_page, language, digit_glyphs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{booklet_pages, parse_collection, BookletPuzzle};
+    use crate::export::Shape;
+    use crate::locale::{DigitGlyphs, Language};
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn parse_puzzles() {
+        let puzzle = BookletPuzzle::parse(&format!("{} 3", PUZZLE)).unwrap();
+        assert_eq!(puzzle.difficulty, Some(3));
+        assert_eq!(puzzle.givens[0], 5);
+        assert_eq!(puzzle.givens[2], 0);
+        assert_eq!(&puzzle.solution[..9], &[5, 3, 4, 6, 7, 8, 9, 1, 2]);
+        assert_eq!(puzzle.id(), BookletPuzzle::parse(PUZZLE).unwrap().id());
+
+        assert!(BookletPuzzle::parse("123").is_err());
+        assert!(BookletPuzzle::parse(&format!("{} 4", PUZZLE)).is_err());
+        assert!(BookletPuzzle::parse(&PUZZLE.replace("53", "55")).is_err());
+
+        let collection = parse_collection(&format!("# mine\n\n{}\n{} 1\n", PUZZLE, PUZZLE));
+        assert_eq!(collection.unwrap().len(), 2);
+        let error = parse_collection(&format!("{}\nnot a puzzle\n", PUZZLE)).unwrap_err();
+        assert!(error.starts_with("line 2:"));
+    }
+
+    #[test]
+    fn pages() {
+        let puzzle = BookletPuzzle::parse(&format!("{} 2", PUZZLE)).unwrap();
+        let puzzles = vec![puzzle; 7];
+
+        // two pages of puzzles and two of solutions
+        let pages = booklet_pages(&puzzles, 6, Language::English, DigitGlyphs::Western);
+        assert_eq!(pages.len(), 4);
+
+        let has_text = |page: usize, wanted: &str| {
+            pages[page].shapes.iter().any(|shape| match shape {
+                Shape::Text { text, .. } => text == wanted,
+                _ => false,
+            })
+        };
+        assert!(has_text(0, "Sudoku"));
+        assert!(has_text(2, "Solutions"));
+        assert!(has_text(3, "4 / 4"));
+        assert!(has_text(
+            1,
+            &format!("7  \u{b7}  Medium  \u{b7}  {}", puzzles[0].id())
+        ));
+
+        let digits = |page: usize| {
+            pages[page]
+                .shapes
+                .iter()
+                .filter(|shape| matches!(shape, Shape::Text { size, .. } if *size > 10.0))
+                .count()
+        };
+        // a full solution, 81 digits, next to the title
+        assert_eq!(digits(3), 81 + 1);
+        assert_eq!(digits(1), 30 + 1);
+    }
+}
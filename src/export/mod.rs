@@ -0,0 +1,300 @@
+// pictures of a board for files rather than the screen, the board is
+// laid out once as rectangles and text and each format writes those out
+
+pub mod booklet;
+pub mod pdf;
+pub mod png;
+pub mod svg;
+
+use macroquad::color::Color;
+
+use crate::board::Board;
+use crate::board_layout::{
+    box_line_width, cell_background, cell_line_width, corner_slot, number_color, pencil_box,
+    DIGIT_WIDTH,
+};
+use crate::index::index_to_xy;
+use crate::locale::DigitGlyphs;
+use crate::theme::Theme;
+
+// the advance and the height of a digit of the number font, in ems
+pub const DIGIT_ADVANCE: f32 = 0.556;
+pub const DIGIT_HEIGHT: f32 = 0.71;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFont {
+    Number,
+    Glyph,
+    // titles and labels around the boards
+    Label,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Color,
+    },
+    // x and y are the centre of the text
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        text: String,
+        color: Color,
+        font: TextFont,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportOptions {
+    pub size: f32,
+    pub pencil_marks: bool,
+    pub highlights: bool,
+    pub digit_glyphs: DigitGlyphs,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportOptions {
+    pub fn new() -> Self {
+        ExportOptions {
+            size: 540.0,
+            pencil_marks: false,
+            highlights: false,
+            digit_glyphs: DigitGlyphs::Western,
+        }
+    }
+}
+
+// the font size that makes a digit as wide as the share of its box
+// the window gives it
+fn digit_font_size(box_size: f32) -> f32 {
+    box_size * DIGIT_WIDTH / DIGIT_ADVANCE
+}
+
+impl Picture {
+    // a square picture of the board, with the same padding the window keeps
+    pub fn board(board: &Board, theme: &Theme, options: &ExportOptions) -> Self {
+        let mut picture = Picture {
+            width: options.size,
+            height: options.size,
+            shapes: vec![],
+        };
+
+        picture.rect(0.0, 0.0, options.size, options.size, theme.background);
+
+        let padding = options.size * 0.02;
+        picture.add_board(
+            board,
+            theme,
+            options,
+            padding,
+            padding,
+            options.size - (2.0 * padding),
+        );
+
+        picture
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        self.shapes.push(Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            color,
+        });
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str, color: Color, font: TextFont) {
+        self.shapes.push(Shape::Text {
+            x,
+            y,
+            size,
+            text: text.to_string(),
+            color,
+            font,
+        });
+    }
+
+    // draws the board in a square at left, top
+    pub fn add_board(
+        &mut self,
+        board: &Board,
+        theme: &Theme,
+        options: &ExportOptions,
+        left: f32,
+        top: f32,
+        board_size: f32,
+    ) {
+        let cell_size = board_size / 9.0;
+        let font = match options.digit_glyphs {
+            DigitGlyphs::Western => TextFont::Number,
+            _ => TextFont::Glyph,
+        };
+
+        for index in 0..81 {
+            let (x, y) = index_to_xy(index, 9);
+            let cell_x = left + (x as f32 * cell_size);
+            let cell_y = top + (y as f32 * cell_size);
+            let cell_state = &board.cell_state[index];
+
+            let background = if options.highlights {
+                cell_background(board, theme, index)
+            } else {
+                theme.cell_normal
+            };
+            self.rect(cell_x, cell_y, cell_size, cell_size, background);
+
+            if let Some(n) = cell_state.number {
+                self.text(
+                    cell_x + (cell_size / 2.0),
+                    cell_y + (cell_size / 2.0),
+                    digit_font_size(cell_size),
+                    options.digit_glyphs.glyph(n),
+                    number_color(board, theme, index, false),
+                    font,
+                );
+            } else if options.pencil_marks && cell_state.has_pencil() {
+                let (padding, box_size) = pencil_box(cell_size);
+                let size = digit_font_size(box_size);
+                let color = theme.cell_text_pencil;
+
+                let count = cell_state.corner.iter().flatten().count();
+                for (slot, n) in cell_state.corner.iter().flatten().enumerate() {
+                    let (slot_x, slot_y) = corner_slot(slot, count);
+                    self.text(
+                        cell_x + (padding / 2.0) + (box_size * (slot_x + 0.5)),
+                        cell_y + (padding / 2.0) + (box_size * (slot_y + 0.5)),
+                        size,
+                        options.digit_glyphs.glyph(*n),
+                        color,
+                        font,
+                    );
+                }
+
+                // centre marks shrink when there are too many of them to fit
+                let text: String = cell_state
+                    .pencil
+                    .iter()
+                    .flatten()
+                    .map(|&n| options.digit_glyphs.glyph(n))
+                    .collect();
+                if !text.is_empty() {
+                    let width = text.chars().count() as f32 * size * DIGIT_ADVANCE;
+                    let max_width = cell_size * 0.8;
+                    let size = if width > max_width {
+                        size * max_width / width
+                    } else {
+                        size
+                    };
+                    self.text(
+                        cell_x + (cell_size / 2.0),
+                        cell_y + (cell_size / 2.0),
+                        size,
+                        &text,
+                        color,
+                        font,
+                    );
+                }
+            }
+        }
+
+        let cell_line = cell_line_width(board_size);
+        for x in 1..9 {
+            if x % 3 == 0 {
+                continue;
+            }
+
+            let offset = x as f32 * cell_size - (cell_line / 2.0);
+            self.rect(left + offset, top, cell_line, board_size, theme.cell_line);
+            self.rect(left, top + offset, board_size, cell_line, theme.cell_line);
+        }
+
+        // box lines reach past the corners so the frame is closed
+        let box_line = box_line_width(board_size);
+        for x in 0..4 {
+            let offset = x as f32 * 3.0 * cell_size - (box_line / 2.0);
+            self.rect(
+                left + offset,
+                top - (box_line / 2.0),
+                box_line,
+                board_size + box_line,
+                theme.box_line,
+            );
+            self.rect(
+                left - (box_line / 2.0),
+                top + offset,
+                board_size + box_line,
+                box_line,
+                theme.box_line,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportOptions, Picture, Shape};
+    use crate::board::Board;
+    use crate::theme::Theme;
+
+    fn texts(picture: &Picture) -> Vec<String> {
+        picture
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn board_picture() {
+        let theme = Theme::light();
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(5);
+        board.cell_state[1].set_number(3);
+        board.cell_state[2].set_pencil(1);
+        board.cell_state[2].set_pencil(2);
+        board.cell_state[3].set_corner(7);
+
+        let options = ExportOptions::new();
+        let picture = Picture::board(&board, &theme, &options);
+        assert_eq!(texts(&picture), vec!["5", "3"]);
+        assert_eq!(picture.width, options.size);
+
+        // background, 81 cells, 12 cell lines and 8 box lines
+        let rects = picture.shapes.len() - 2;
+        assert_eq!(rects, 1 + 81 + 12 + 8);
+
+        let options = ExportOptions {
+            pencil_marks: true,
+            ..ExportOptions::new()
+        };
+        let picture = Picture::board(&board, &theme, &options);
+        assert_eq!(texts(&picture), vec!["5", "3", "12", "7"]);
+
+        let given = picture.shapes.iter().find_map(|shape| match shape {
+            Shape::Text { text, color, .. } if text == "5" => Some(*color),
+            _ => None,
+        });
+        assert_eq!(given, Some(theme.cell_text_initial));
+    }
+}
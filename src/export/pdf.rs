@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use super::{Picture, Shape, DIGIT_HEIGHT};
+
+// the widths of helvetica from its afm file, in thousandths of an em, for
+// the printable ascii characters. helvetica is one of the fonts every pdf
+// reader has, so nothing needs to be embedded
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+fn char_width(character: char) -> f32 {
+    let width = match character as usize {
+        code @ 32..=126 => HELVETICA_WIDTHS[code - 32],
+        0xb7 => 278,
+        _ => 556,
+    };
+
+    width as f32 / 1000.0
+}
+
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(char_width).sum::<f32>() * size
+}
+
+// the font uses winansi, which matches latin-1 for everything
+// a label needs, anything else can't be shown
+fn encode(text: &str) -> String {
+    text.chars()
+        .map(|character| match character {
+            '(' | ')' | '\\' => format!("\\{}", character),
+            ' '..='~' => character.to_string(),
+            '\u{a0}'..='\u{ff}' => format!("\\{:03o}", character as u32),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+// the content stream of one page, pdf counts y from the bottom.
+// there is no transparency, alpha is left out
+fn page_content(picture: &Picture) -> String {
+    let mut content = String::new();
+    let height = picture.height;
+
+    for shape in picture.shapes.iter() {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height: rect_height,
+                color,
+            } => writeln!(
+                content,
+                "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f",
+                color.r,
+                color.g,
+                color.b,
+                x,
+                height - y - rect_height,
+                width,
+                rect_height
+            )
+            .unwrap(),
+            Shape::Text {
+                x,
+                y,
+                size,
+                text,
+                color,
+                ..
+            } => writeln!(
+                content,
+                "BT /F1 {:.2} Tf {:.3} {:.3} {:.3} rg {:.2} {:.2} Td ({}) Tj ET",
+                size,
+                color.r,
+                color.g,
+                color.b,
+                x - (text_width(text, *size) / 2.0),
+                height - (y + (size * DIGIT_HEIGHT / 2.0)),
+                encode(text)
+            )
+            .unwrap(),
+        }
+    }
+
+    content
+}
+
+// every picture is a page, sized in points
+pub fn to_pdf(pages: &[Picture]) -> Vec<u8> {
+    // catalog, page tree and font come first, then a page and its content for each picture
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+
+    let mut kids = vec![];
+    for picture in pages {
+        let page_id = objects.len() + 1;
+        kids.push(format!("{} 0 R", page_id));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            picture.width,
+            picture.height,
+            page_id + 1
+        ));
+
+        let content = page_content(picture);
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    );
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        writeln!(pdf, "{} 0 obj\n{}\nendobj", i + 1, object).unwrap();
+    }
+
+    let xref = pdf.len();
+    writeln!(pdf, "xref\n0 {}\n0000000000 65535 f ", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(pdf, "{:010} 00000 n ", offset).unwrap();
+    }
+    write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    )
+    .unwrap();
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::color::Color;
+
+    use super::{encode, text_width, to_pdf};
+    use crate::export::{Picture, TextFont};
+
+    #[test]
+    fn helvetica_text() {
+        assert!((text_width("10", 10.0) - 11.12).abs() < 0.001);
+        assert_eq!(encode("(a\\b)"), "\\(a\\\\b\\)");
+        assert_eq!(encode("Résolu · 1"), "R\\351solu \\267 1");
+        assert_eq!(encode("\u{661}"), "?");
+    }
+
+    #[test]
+    fn pdf_structure() {
+        let mut page = Picture {
+            width: 200.0,
+            height: 100.0,
+            shapes: vec![],
+        };
+        page.rect(10.0, 10.0, 20.0, 30.0, Color::new(1.0, 0.0, 0.0, 1.0));
+        page.text(
+            100.0,
+            50.0,
+            12.0,
+            "7",
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            TextFont::Number,
+        );
+
+        let pdf = String::from_utf8(to_pdf(&[page.clone(), page])).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Kids [4 0 R 6 0 R] /Count 2"));
+        assert!(pdf.contains("1.000 0.000 0.000 rg 10.00 60.00 20.00 30.00 re f"));
+        assert!(pdf.contains("(7) Tj"));
+
+        // every object is where the cross reference table says it is
+        let xref_start = pdf.find("xref\n").unwrap();
+        let offsets = pdf[xref_start..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse::<usize>().unwrap());
+        for (i, offset) in offsets.enumerate() {
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+        assert!(pdf.contains(&format!("startxref\n{}\n", xref_start)));
+    }
+}
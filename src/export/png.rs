@@ -0,0 +1,199 @@
+use fontdue::{Font, FontSettings};
+use macroquad::color::Color;
+
+use super::{Picture, Shape, TextFont, DIGIT_HEIGHT};
+use crate::font_files::{
+    override_bytes, GLYPH_FONT, GLYPH_FONT_FILE, NUMBER_FONT, NUMBER_FONT_FILE,
+};
+
+// a missing or broken override falls back to the built in font
+fn load_font(file_name: &str, embedded: &[u8]) -> Font {
+    override_bytes(file_name)
+        .and_then(|bytes| Font::from_bytes(bytes, FontSettings::default()).ok())
+        .unwrap_or_else(|| Font::from_bytes(embedded, FontSettings::default()).unwrap())
+}
+
+// premultiplied pixels, everything is drawn with the source over rule
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Self {
+        Raster {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width * height],
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        let alpha = color.a * coverage;
+        let pixel = &mut self.pixels[y * self.width + x];
+        for (channel, value) in pixel.iter_mut().zip([color.r, color.g, color.b, 1.0]) {
+            *channel = value * alpha + *channel * (1.0 - alpha);
+        }
+    }
+
+    // the edges are covered in proportion, so thin lines stay thin
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        let (left, right) = (x.max(0.0), (x + width).min(self.width as f32));
+        let (top, bottom) = (y.max(0.0), (y + height).min(self.height as f32));
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        for py in top.floor() as usize..bottom.ceil() as usize {
+            let coverage_y = bottom.min(py as f32 + 1.0) - top.max(py as f32);
+            for px in left.floor() as usize..right.ceil() as usize {
+                let coverage_x = right.min(px as f32 + 1.0) - left.max(px as f32);
+                self.blend(px, py, color, coverage_x * coverage_y);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, font: &Font, x: f32, y: f32, size: f32, text: &str, color: Color) {
+        let advance: f32 = text
+            .chars()
+            .map(|character| font.metrics(character, size).advance_width)
+            .sum();
+        let mut pen_x = x - (advance / 2.0);
+        let baseline = y + (size * DIGIT_HEIGHT / 2.0);
+
+        for character in text.chars() {
+            let (metrics, bitmap) = font.rasterize(character, size);
+            let left = (pen_x + metrics.xmin as f32).round() as i64;
+            let top = (baseline - (metrics.height as i32 + metrics.ymin) as f32).round() as i64;
+
+            for (i, &coverage) in bitmap.iter().enumerate() {
+                let px = left + (i % metrics.width) as i64;
+                let py = top + (i / metrics.width) as i64;
+                if coverage == 0
+                    || px < 0
+                    || py < 0
+                    || px >= self.width as i64
+                    || py >= self.height as i64
+                {
+                    continue;
+                }
+                self.blend(px as usize, py as usize, color, coverage as f32 / 255.0);
+            }
+
+            pen_x += metrics.advance_width;
+        }
+    }
+
+    fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&[r, g, b, a]| {
+                let straight = |value: f32| {
+                    let value = if a > 0.0 { value / a } else { 0.0 };
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                };
+                [
+                    straight(r),
+                    straight(g),
+                    straight(b),
+                    (a * 255.0).round() as u8,
+                ]
+            })
+            .collect()
+    }
+}
+
+pub fn to_png(picture: &Picture) -> Vec<u8> {
+    let number_font = load_font(NUMBER_FONT_FILE, NUMBER_FONT);
+    let glyph_font = load_font(GLYPH_FONT_FILE, GLYPH_FONT);
+
+    let width = picture.width.ceil() as usize;
+    let height = picture.height.ceil() as usize;
+    let mut raster = Raster::new(width, height);
+
+    for shape in picture.shapes.iter() {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => raster.fill_rect(*x, *y, *width, *height, *color),
+            Shape::Text {
+                x,
+                y,
+                size,
+                text,
+                color,
+                font,
+            } => {
+                let font = match font {
+                    TextFont::Number | TextFont::Label => &number_font,
+                    TextFont::Glyph => &glyph_font,
+                };
+                raster.draw_text(font, *x, *y, *size, text, *color);
+            }
+        }
+    }
+
+    let mut data = vec![];
+    let mut encoder = png::Encoder::new(&mut data, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&raster.to_rgba()).unwrap();
+    writer.finish().unwrap();
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::color::Color;
+
+    use super::{to_png, Raster};
+    use crate::export::{Picture, TextFont};
+
+    #[test]
+    fn fill_rect_coverage() {
+        let mut raster = Raster::new(4, 1);
+        raster.fill_rect(0.0, 0.0, 1.5, 1.0, Color::new(1.0, 1.0, 1.0, 1.0));
+        assert_eq!(raster.pixels[0], [1.0; 4]);
+        assert_eq!(raster.pixels[1], [0.5; 4]);
+        assert_eq!(raster.pixels[2], [0.0; 4]);
+    }
+
+    #[test]
+    fn png_image() {
+        let mut picture = Picture {
+            width: 40.0,
+            height: 20.0,
+            shapes: vec![],
+        };
+        picture.rect(0.0, 0.0, 40.0, 20.0, Color::new(1.0, 1.0, 1.0, 1.0));
+        picture.text(
+            20.0,
+            10.0,
+            16.0,
+            "8",
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            TextFont::Number,
+        );
+
+        let data = to_png(&picture);
+        assert_eq!(&data[1..4], b"PNG");
+
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (40, 20));
+
+        // the corner stays white and the digit darkens the middle
+        assert_eq!(&pixels[0..4], &[255, 255, 255, 255]);
+        let darkest = pixels.chunks(4).map(|pixel| pixel[0]).min();
+        assert!(darkest < Some(64));
+    }
+}
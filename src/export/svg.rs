@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use macroquad::color::Color;
+
+use super::{Picture, Shape, TextFont, DIGIT_HEIGHT};
+
+// the fonts the picture asks for, viewers without them fall back to
+// something close enough for digits
+fn font_family(font: TextFont) -> &'static str {
+    match font {
+        TextFont::Number => "Liberation Sans, Arial, Helvetica, sans-serif",
+        TextFont::Glyph => "DejaVu Sans, sans-serif",
+        TextFont::Label => "Helvetica, Arial, sans-serif",
+    }
+}
+
+// svg 1.1 has no colours with alpha, the opacity is its own attribute
+fn fill(color: Color) -> String {
+    let [r, g, b, a] = color.to_vec().to_array().map(|c| (c * 255.0).round() as u8);
+    if a == 255 {
+        format!(r#"fill="rgb({},{},{})""#, r, g, b)
+    } else {
+        format!(
+            r#"fill="rgb({},{},{})" fill-opacity="{:.3}""#,
+            r, g, b, color.a
+        )
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn to_svg(picture: &Picture) -> String {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = picture.width,
+        h = picture.height
+    )
+    .unwrap();
+
+    for shape in picture.shapes.iter() {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => writeln!(
+                svg,
+                r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" {}/>"#,
+                x,
+                y,
+                width,
+                height,
+                fill(*color)
+            )
+            .unwrap(),
+            Shape::Text {
+                x,
+                y,
+                size,
+                text,
+                color,
+                font,
+            } => writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" font-family="{}" font-size="{:.2}" text-anchor="middle" {}>{}</text>"#,
+                x,
+                // text sits on its baseline, half a digit below the centre
+                y + (size * DIGIT_HEIGHT / 2.0),
+                font_family(*font),
+                size,
+                fill(*color),
+                escape(text)
+            )
+            .unwrap(),
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::color::Color;
+
+    use super::to_svg;
+    use crate::export::{Picture, TextFont};
+
+    #[test]
+    fn svg_shapes() {
+        let mut picture = Picture {
+            width: 100.0,
+            height: 50.0,
+            shapes: vec![],
+        };
+        picture.rect(1.0, 2.0, 3.0, 4.0, Color::new(1.0, 0.0, 0.0, 1.0));
+        picture.rect(0.0, 0.0, 1.0, 1.0, Color::new(0.0, 0.0, 0.0, 0.5));
+        picture.text(
+            50.0,
+            25.0,
+            10.0,
+            "<1>",
+            Color::new(0.0, 0.0, 1.0, 1.0),
+            TextFont::Number,
+        );
+
+        let svg = to_svg(&picture);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50""#)
+        );
+        assert!(svg.contains(
+            r#"<rect x="1.00" y="2.00" width="3.00" height="4.00" fill="rgb(255,0,0)"/>"#
+        ));
+        assert!(svg.contains(r#"fill="rgb(0,0,0)" fill-opacity="0.500""#));
+        assert!(svg.contains(r#"y="28.55""#));
+        assert!(svg.contains(">&lt;1&gt;</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
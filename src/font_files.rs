@@ -0,0 +1,25 @@
+// the font files built into every binary, shared by the window and the exporters
+
+pub const NUMBER_FONT_FILE: &str = "liberation-sans-minimized.ttf";
+pub const ICON_FONT_FILE: &str = "material-font-minimized.ttf";
+pub const GLYPH_FONT_FILE: &str = "glyphs-minimized.ttf";
+
+pub const NUMBER_FONT: &[u8] = include_bytes!("../liberation-sans-minimized.ttf");
+pub const ICON_FONT: &[u8] = include_bytes!("../material-font-minimized.ttf");
+pub const GLYPH_FONT: &[u8] = include_bytes!("../glyphs-minimized.ttf");
+
+// on desktop, fonts with the same file names in this directory
+// replace the ones built into the binary
+#[cfg(not(target_arch = "wasm32"))]
+const FONT_DIR_VAR: &str = "SUDOKU_FONT_DIR";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn override_bytes(file_name: &str) -> Option<Vec<u8>> {
+    let font_dir = std::env::var_os(FONT_DIR_VAR)?;
+    std::fs::read(std::path::Path::new(&font_dir).join(file_name)).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn override_bytes(_file_name: &str) -> Option<Vec<u8>> {
+    None
+}
@@ -1,17 +1,18 @@
-use macroquad::prelude::{BLACK, WHITE};
+use macroquad::text::Font;
 
-use crate::{
-    CELL_TEXT_COLOR, CELL_TEXT_INITIAL_COLOR, MODAL_DIFFICULTY_ONE, MODAL_DIFFICULTY_THREE,
-    MODAL_DIFFICULTY_TWO, MODAL_VICTORY_GOLD, MODAL_VICTORY_RED,
-};
+use crate::locale::DigitGlyphs;
+use crate::theme::Theme;
 
+use super::font_data::FontData;
 use super::font_types::{
     CellFont, CellPencilFont, IconFont, MenuNumberFont, ModalDifficultyFont, ModalVictoryFont,
+    StatusFont,
 };
 
 pub struct FontContext {
     pub initial_font: CellFont,
     pub font: CellFont,
+    pub conflict_font: CellFont,
     pub icon_font: IconFont,
     pub icon_font_selected: IconFont,
     pub pencil_font: CellPencilFont,
@@ -23,47 +24,109 @@ pub struct FontContext {
     pub modal_difficulty_title_font: ModalDifficultyFont,
     pub modal_victory_star_font: ModalVictoryFont,
     pub modal_victory_heart_font: ModalVictoryFont,
+    pub status_font: StatusFont,
+    pub digit_glyphs: DigitGlyphs,
+    number_font: Font,
+    glyph_font: Font,
+    cell_size: f32,
 }
 
 impl FontContext {
-    pub async fn new(font_path: &str, icon_font_path: &str) -> Self {
+    pub fn new(theme: &Theme) -> Self {
+        let fonts = FontData::load();
+        let number_font = fonts.number;
+        let icon_font = fonts.icon;
+
         FontContext {
-            initial_font: CellFont::new(font_path, CELL_TEXT_INITIAL_COLOR).await,
-            font: CellFont::new(font_path, CELL_TEXT_COLOR).await,
-            icon_font: IconFont::new(icon_font_path, BLACK).await,
-            icon_font_selected: IconFont::new(icon_font_path, WHITE).await,
-            pencil_font: CellPencilFont::new(font_path).await,
-            menu_number_font: MenuNumberFont::new(font_path, BLACK).await,
-            menu_number_font_selected: MenuNumberFont::new(font_path, WHITE).await,
+            initial_font: CellFont::new(number_font, theme.cell_text_initial),
+            font: CellFont::new(number_font, theme.cell_text),
+            conflict_font: CellFont::new(number_font, theme.cell_text_conflict),
+            icon_font: IconFont::new(icon_font, theme.menu_text),
+            icon_font_selected: IconFont::new(icon_font, theme.menu_text_selected),
+            pencil_font: CellPencilFont::new(number_font, theme.cell_text_pencil),
+            menu_number_font: MenuNumberFont::new(number_font, theme.menu_text),
+            menu_number_font_selected: MenuNumberFont::new(number_font, theme.menu_text_selected),
             modal_difficulty_font_1: ModalDifficultyFont::new(
-                icon_font_path,
+                icon_font,
                 0.5,
-                MODAL_DIFFICULTY_ONE,
-            )
-            .await,
+                theme.modal_difficulty_one,
+            ),
             modal_difficulty_font_2: ModalDifficultyFont::new(
-                icon_font_path,
+                icon_font,
                 0.5,
-                MODAL_DIFFICULTY_TWO,
-            )
-            .await,
+                theme.modal_difficulty_two,
+            ),
             modal_difficulty_font_3: ModalDifficultyFont::new(
-                icon_font_path,
+                icon_font,
+                0.5,
+                theme.modal_difficulty_three,
+            ),
+            modal_difficulty_title_font: ModalDifficultyFont::new(icon_font, 0.7, theme.modal_text),
+            modal_victory_star_font: ModalVictoryFont::new(
+                icon_font,
                 0.5,
-                MODAL_DIFFICULTY_THREE,
-            )
-            .await,
-            modal_difficulty_title_font: ModalDifficultyFont::new(icon_font_path, 0.7, BLACK).await,
-            modal_victory_star_font: ModalVictoryFont::new(icon_font_path, 0.5, MODAL_VICTORY_GOLD)
-                .await,
-            modal_victory_heart_font: ModalVictoryFont::new(icon_font_path, 0.5, MODAL_VICTORY_RED)
-                .await,
+                theme.modal_victory_gold,
+            ),
+            modal_victory_heart_font: ModalVictoryFont::new(
+                icon_font,
+                0.5,
+                theme.modal_victory_red,
+            ),
+            status_font: StatusFont::new(theme.status_text),
+            digit_glyphs: DigitGlyphs::Western,
+            number_font: fonts.number,
+            glyph_font: fonts.glyph,
+            cell_size: 0.0,
         }
     }
 
+    pub fn digit(&self, number: u8) -> &'static str {
+        self.digit_glyphs.glyph(number)
+    }
+
+    // the bundled number font only has western digits, the other
+    // glyph sets come from a second font
+    pub fn set_digit_glyphs(&mut self, digit_glyphs: DigitGlyphs) {
+        self.digit_glyphs = digit_glyphs;
+
+        let font = match digit_glyphs {
+            DigitGlyphs::Western => self.number_font,
+            _ => self.glyph_font,
+        };
+        self.initial_font.set_font(font);
+        self.font.set_font(font);
+        self.conflict_font.set_font(font);
+        self.pencil_font.set_font(font);
+        self.menu_number_font.set_font(font);
+        self.menu_number_font_selected.set_font(font);
+
+        self.update(self.cell_size);
+    }
+
+    // the colours are baked into the text params, so they follow the theme here
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.initial_font.params.color = theme.cell_text_initial;
+        self.font.params.color = theme.cell_text;
+        self.conflict_font.params.color = theme.cell_text_conflict;
+        self.icon_font.params.color = theme.menu_text;
+        self.icon_font_selected.params.color = theme.menu_text_selected;
+        self.pencil_font.params.color = theme.cell_text_pencil;
+        self.menu_number_font.params.color = theme.menu_text;
+        self.menu_number_font_selected.params.color = theme.menu_text_selected;
+        self.modal_difficulty_font_1.params.color = theme.modal_difficulty_one;
+        self.modal_difficulty_font_2.params.color = theme.modal_difficulty_two;
+        self.modal_difficulty_font_3.params.color = theme.modal_difficulty_three;
+        self.modal_difficulty_title_font.params.color = theme.modal_text;
+        self.modal_victory_star_font.params.color = theme.modal_victory_gold;
+        self.modal_victory_heart_font.params.color = theme.modal_victory_red;
+        self.status_font.params.color = theme.status_text;
+    }
+
     pub fn update(&mut self, cell_size: f32) {
+        self.cell_size = cell_size;
         self.initial_font.update(cell_size);
         self.font.update(cell_size);
+        self.conflict_font.update(cell_size);
         self.pencil_font.update(cell_size);
         self.menu_number_font.update(cell_size);
         self.menu_number_font_selected.update(cell_size);
@@ -75,5 +138,6 @@ impl FontContext {
         self.modal_victory_heart_font.update(cell_size);
         self.modal_victory_star_font.update(cell_size);
         self.modal_difficulty_title_font.update(cell_size);
+        self.status_font.update(cell_size);
     }
 }
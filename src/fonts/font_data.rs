@@ -0,0 +1,31 @@
+use macroquad::text::{load_ttf_font_from_bytes, Font};
+
+use crate::font_files::{
+    override_bytes, GLYPH_FONT, GLYPH_FONT_FILE, ICON_FONT, ICON_FONT_FILE, NUMBER_FONT,
+    NUMBER_FONT_FILE,
+};
+
+// every font file is loaded once, the font types share these handles
+#[derive(Clone, Copy)]
+pub struct FontData {
+    pub number: Font,
+    pub icon: Font,
+    pub glyph: Font,
+}
+
+impl FontData {
+    pub fn load() -> Self {
+        FontData {
+            number: load_font(NUMBER_FONT_FILE, NUMBER_FONT),
+            icon: load_font(ICON_FONT_FILE, ICON_FONT),
+            glyph: load_font(GLYPH_FONT_FILE, GLYPH_FONT),
+        }
+    }
+}
+
+// a missing or broken override falls back to the built in font
+fn load_font(file_name: &str, embedded: &[u8]) -> Font {
+    override_bytes(file_name)
+        .and_then(|bytes| load_ttf_font_from_bytes(&bytes).ok())
+        .unwrap_or_else(|| load_ttf_font_from_bytes(embedded).unwrap())
+}
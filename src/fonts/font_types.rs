@@ -1,6 +1,7 @@
 use macroquad::prelude::*;
 use macroquad::text::TextParams;
 
+use crate::board_layout::pencil_box;
 use crate::{ICON_DIFFICULTY_1, ICON_PENCIL, ICON_VICTORY_STAR};
 
 use super::font_size::estimate_font_size;
@@ -15,8 +16,7 @@ pub struct CellFont {
 }
 
 impl CellFont {
-    pub async fn new(font_path: &str, color: Color) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, color: Color) -> Self {
         let measure = measure_text("9", Some(font), 48, 1.0);
         CellFont {
             font,
@@ -44,6 +44,11 @@ impl CellFont {
         self.x_offset = (cell_size / 2.0) - (self.width / 2.0);
         self.y_offset = (cell_size / 2.0) + (self.height / 2.0);
     }
+
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+        self.params.font = font;
+    }
 }
 
 pub struct CellPencilFont {
@@ -57,8 +62,7 @@ pub struct CellPencilFont {
 }
 
 impl CellPencilFont {
-    pub async fn new(font_path: &str) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, color: Color) -> Self {
         let measure = measure_text("9", Some(font), 48, 1.0);
         CellPencilFont {
             font,
@@ -68,7 +72,7 @@ impl CellPencilFont {
                 font_scale: 1.0,
                 font_scale_aspect: 1.0,
                 rotation: 0.0,
-                color: BLACK,
+                color,
             },
             x_offset: 0.0,
             y_offset: 0.0,
@@ -79,8 +83,8 @@ impl CellPencilFont {
     }
 
     pub fn update(&mut self, cell_size: f32) {
-        let padding = cell_size * 0.1;
-        self.box_size = (cell_size - padding) / 3.0;
+        let (padding, box_size) = pencil_box(cell_size);
+        self.box_size = box_size;
 
         self.params.font_size = estimate_font_size("9", Some(self.font), self.box_size, 1.0);
         let measure = measure_text("9", Some(self.font), self.params.font_size, 1.0);
@@ -90,6 +94,11 @@ impl CellPencilFont {
         self.x_offset = (self.box_size / 2.0) - (self.width / 2.0) + (padding / 2.0);
         self.y_offset = (self.box_size / 2.0) + (self.height / 2.0) + (padding / 2.0);
     }
+
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+        self.params.font = font;
+    }
 }
 
 pub struct MenuNumberFont {
@@ -100,8 +109,7 @@ pub struct MenuNumberFont {
 }
 
 impl MenuNumberFont {
-    pub async fn new(font_path: &str, color: Color) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, color: Color) -> Self {
         let measure = measure_text("9", Some(font), 48, 1.0);
         MenuNumberFont {
             font,
@@ -125,6 +133,11 @@ impl MenuNumberFont {
         self.width = measure.width;
         self.height = measure.height;
     }
+
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+        self.params.font = font;
+    }
 }
 
 pub struct IconFont {
@@ -135,8 +148,7 @@ pub struct IconFont {
 }
 
 impl IconFont {
-    pub async fn new(font_path: &str, color: Color) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, color: Color) -> Self {
         let measure = measure_text(ICON_PENCIL, Some(font), 48, 0.7);
         IconFont {
             font,
@@ -170,8 +182,7 @@ pub struct ModalDifficultyFont {
 }
 
 impl ModalDifficultyFont {
-    pub async fn new(font_path: &str, estimate_scale: f32, color: Color) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, estimate_scale: f32, color: Color) -> Self {
         let measure = measure_text(ICON_DIFFICULTY_1, Some(font), 48, estimate_scale);
         ModalDifficultyFont {
             font,
@@ -216,8 +227,7 @@ pub struct ModalVictoryFont {
 }
 
 impl ModalVictoryFont {
-    pub async fn new(font_path: &str, estimate_scale: f32, color: Color) -> Self {
-        let font = load_ttf_font(font_path).await.unwrap();
+    pub fn new(font: Font, estimate_scale: f32, color: Color) -> Self {
         let measure = measure_text(ICON_VICTORY_STAR, Some(font), 48, 1.0);
         ModalVictoryFont {
             font,
@@ -252,3 +262,39 @@ impl ModalVictoryFont {
         self.height = measure.height;
     }
 }
+
+pub struct StatusFont {
+    pub params: TextParams,
+    pub font: Font,
+    pub height: f32,
+    pub width: f32,
+}
+
+impl StatusFont {
+    // the bundled fonts only carry digits, the status line needs ':'
+    // and letters so it uses macroquad's default font, which has latin-1
+    pub fn new(color: Color) -> Self {
+        let font = Font::default();
+        let measure = measure_text("0", Some(font), 48, 1.0);
+        StatusFont {
+            font,
+            params: TextParams {
+                font,
+                font_size: 48,
+                font_scale: 1.0,
+                font_scale_aspect: 1.0,
+                rotation: 0.0,
+                color,
+            },
+            height: measure.height,
+            width: measure.width,
+        }
+    }
+
+    pub fn update(&mut self, cell_size: f32) {
+        self.params.font_size = estimate_font_size("0", Some(self.font), cell_size * 0.47, 1.0);
+        let measure = measure_text("0", Some(self.font), self.params.font_size, 1.0);
+        self.width = measure.width;
+        self.height = measure.height;
+    }
+}
@@ -1,3 +1,4 @@
 pub mod font_context;
+pub mod font_data;
 pub mod font_size;
 pub mod font_types;
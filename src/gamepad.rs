@@ -0,0 +1,184 @@
+// controllers on desktop: the d-pad or the left stick move the cursor,
+// the face buttons place, toggle pencil marks, undo and erase, and the
+// shoulder buttons step through the numbers
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadAction {
+    Move(i32, i32),
+    // a, or cross
+    Place,
+    // x, or square
+    Pencil,
+    // y, or triangle
+    Undo,
+    // b, or circle, also closes what is open
+    Back,
+    PreviousNumber,
+    NextNumber,
+    Color,
+    Options,
+    NewGame,
+}
+
+pub fn button_action(button: Button) -> Option<PadAction> {
+    match button {
+        Button::DPadUp => Some(PadAction::Move(0, -1)),
+        Button::DPadDown => Some(PadAction::Move(0, 1)),
+        Button::DPadLeft => Some(PadAction::Move(-1, 0)),
+        Button::DPadRight => Some(PadAction::Move(1, 0)),
+        Button::South => Some(PadAction::Place),
+        Button::West => Some(PadAction::Pencil),
+        Button::North => Some(PadAction::Undo),
+        Button::East => Some(PadAction::Back),
+        Button::LeftTrigger => Some(PadAction::PreviousNumber),
+        Button::RightTrigger => Some(PadAction::NextNumber),
+        Button::LeftTrigger2 | Button::RightTrigger2 => Some(PadAction::Color),
+        Button::Start => Some(PadAction::Options),
+        Button::Select => Some(PadAction::NewGame),
+        _ => None,
+    }
+}
+
+const DEAD_ZONE: f32 = 0.5;
+// a held stick moves once, then again after the delay, then at the rate
+const REPEAT_DELAY: f32 = 0.35;
+const REPEAT_RATE: f32 = 0.1;
+
+// turns the stick into steps like a held key
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StickRepeat {
+    direction: (i32, i32),
+    held: f32,
+    next: f32,
+}
+
+impl StickRepeat {
+    pub fn update(&mut self, x: f32, y: f32, seconds: f32) -> Option<(i32, i32)> {
+        let step = |value: f32| {
+            if value > DEAD_ZONE {
+                1
+            } else if value < -DEAD_ZONE {
+                -1
+            } else {
+                0
+            }
+        };
+        // gilrs has up as positive, the board has it as negative
+        let direction = (step(x), -step(y));
+
+        if direction == (0, 0) {
+            *self = Default::default();
+            return None;
+        }
+
+        if direction != self.direction {
+            self.direction = direction;
+            self.held = 0.0;
+            self.next = REPEAT_DELAY;
+            return Some(direction);
+        }
+
+        self.held += seconds;
+        if self.held >= self.next {
+            self.next += REPEAT_RATE;
+            return Some(direction);
+        }
+
+        None
+    }
+}
+
+pub struct Gamepad {
+    // none when the system has no way to read controllers
+    gilrs: Option<Gilrs>,
+    stick: (f32, f32),
+    repeat: StickRepeat,
+    // a controller was used last, rather than the mouse or keyboard
+    pub active: bool,
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        Gamepad {
+            gilrs: Gilrs::new().ok(),
+            stick: (0.0, 0.0),
+            repeat: Default::default(),
+            active: false,
+        }
+    }
+
+    pub fn poll(&mut self, seconds: f32) -> Vec<PadAction> {
+        let mut actions = vec![];
+        let gilrs = match &mut self.gilrs {
+            Some(gilrs) => gilrs,
+            None => return actions,
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) | EventType::ButtonRepeated(button, _) => {
+                    actions.extend(button_action(button));
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => self.stick.0 = value,
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => self.stick.1 = value,
+                EventType::Disconnected => self.stick = (0.0, 0.0),
+                _ => {}
+            }
+        }
+
+        if let Some((dx, dy)) = self.repeat.update(self.stick.0, self.stick.1, seconds) {
+            actions.push(PadAction::Move(dx, dy));
+        }
+
+        if !actions.is_empty() {
+            self.active = true;
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gilrs::Button;
+
+    use super::{button_action, PadAction, StickRepeat};
+
+    #[test]
+    fn buttons() {
+        assert_eq!(button_action(Button::DPadUp), Some(PadAction::Move(0, -1)));
+        assert_eq!(button_action(Button::South), Some(PadAction::Place));
+        assert_eq!(
+            button_action(Button::RightTrigger),
+            Some(PadAction::NextNumber)
+        );
+        assert_eq!(button_action(Button::Mode), None);
+    }
+
+    #[test]
+    fn stick_repeats_like_a_key() {
+        let mut stick = StickRepeat::default();
+        assert_eq!(stick.update(0.2, -0.3, 0.016), None);
+
+        // up on the stick is up on the board
+        assert_eq!(stick.update(0.0, 0.9, 0.016), Some((0, -1)));
+        assert_eq!(stick.update(0.0, 0.9, 0.2), None);
+        assert_eq!(stick.update(0.0, 0.9, 0.2), Some((0, -1)));
+        assert_eq!(stick.update(0.0, 0.9, 0.03), None);
+        assert_eq!(stick.update(0.0, 0.9, 0.05), Some((0, -1)));
+
+        // a new direction moves straight away
+        assert_eq!(stick.update(0.9, 0.9, 0.016), Some((1, -1)));
+
+        stick.update(0.0, 0.0, 0.016);
+        assert_eq!(stick.update(-0.8, 0.0, 0.016), Some((-1, 0)));
+    }
+}
@@ -1,28 +1,37 @@
-use macroquad::rand::gen_range;
+use macroquad::rand::RandGenerator;
 
-fn shuffle<T>(array: &mut [T]) {
+use crate::constraints::Constraints;
+
+fn shuffle<T>(array: &mut [T], rng: &RandGenerator) {
     let mut i = array.len();
     while i >= 2 {
         i -= 1;
-        let j = gen_range(0, i);
+        let j = rng.gen_range(0, i);
         array.swap(i, j);
     }
 }
 
-pub fn generate_board(board: &mut [[u8; 9]; 9]) {
-    fill_diagonal(board);
-    solve(board);
+// the generator is passed in so that a seed makes the same puzzle
+// whatever else draws random numbers at the same time
+pub fn generate_board(board: &mut [[u8; 9]; 9], constraints: &Constraints, rng: &RandGenerator) {
+    // the diagonal boxes can only be filled blindly in classic sudoku,
+    // chess constraints reach across box borders
+    if constraints.is_classic() {
+        fill_diagonal(board, rng);
+    }
+    let mut budget = usize::MAX;
+    solve(board, constraints, rng, &mut budget);
 }
 
-fn fill_diagonal(board: &mut [[u8; 9]; 9]) {
+fn fill_diagonal(board: &mut [[u8; 9]; 9], rng: &RandGenerator) {
     for i in (0..9).step_by(3) {
-        fill_subgrid(board, i, i);
+        fill_subgrid(board, i, i, rng);
     }
 }
 
-fn fill_subgrid(board: &mut [[u8; 9]; 9], row: usize, col: usize) {
+fn fill_subgrid(board: &mut [[u8; 9]; 9], row: usize, col: usize, rng: &RandGenerator) {
     let mut values: Vec<u8> = (1..=9).collect();
-    shuffle(&mut values);
+    shuffle(&mut values, rng);
 
     for i in 0..3 {
         for j in 0..3 {
@@ -31,16 +40,28 @@ fn fill_subgrid(board: &mut [[u8; 9]; 9], row: usize, col: usize) {
     }
 }
 
-fn solve(board: &mut [[u8; 9]; 9]) -> bool {
+// budget is how many more numbers may be tried, the search gives up
+// when it runs out
+fn solve(
+    board: &mut [[u8; 9]; 9],
+    constraints: &Constraints,
+    rng: &RandGenerator,
+    budget: &mut usize,
+) -> bool {
     if let Some((row, col)) = find_empty_cell(board) {
         let mut values: Vec<u8> = (1..=9).collect();
-        shuffle(&mut values);
+        shuffle(&mut values, rng);
 
         for value in values {
-            if is_valid(board, row, col, value) {
+            if *budget == 0 {
+                return false;
+            }
+            *budget -= 1;
+
+            if is_valid(board, row, col, value, constraints) {
                 board[row][col] = value;
 
-                if solve(board) {
+                if solve(board, constraints, rng, budget) {
                     return true;
                 }
 
@@ -54,6 +75,37 @@ fn solve(board: &mut [[u8; 9]; 9]) -> bool {
     true
 }
 
+// a puzzle from elsewhere can have few givens or a contradiction deep
+// down, and the backtracking could take practically forever on it
+const SOLVE_BUDGET: usize = 2_000_000;
+
+// solves a puzzle from elsewhere, none when its givens clash, it has no
+// solution or finding one takes too long
+pub fn solve_puzzle(puzzle: &[[u8; 9]; 9], constraints: &Constraints) -> Option<[[u8; 9]; 9]> {
+    let mut board = *puzzle;
+    for row in 0..9 {
+        for col in 0..9 {
+            let value = board[row][col];
+            if value == 0 {
+                continue;
+            }
+
+            board[row][col] = 0;
+            if value > 9 || !is_valid(&board, row, col, value, constraints) {
+                return None;
+            }
+            board[row][col] = value;
+        }
+    }
+
+    let mut budget = SOLVE_BUDGET;
+    if solve(&mut board, constraints, &RandGenerator::new(), &mut budget) {
+        Some(board)
+    } else {
+        None
+    }
+}
+
 fn find_empty_cell(board: &[[u8; 9]; 9]) -> Option<(usize, usize)> {
     for (row, row_values) in board.iter().enumerate() {
         for (col, &value) in row_values.iter().enumerate() {
@@ -65,10 +117,16 @@ fn find_empty_cell(board: &[[u8; 9]; 9]) -> Option<(usize, usize)> {
     None
 }
 
-fn is_valid(board: &[[u8; 9]; 9], row: usize, col: usize, value: u8) -> bool {
+fn is_valid(
+    board: &[[u8; 9]; 9],
+    row: usize,
+    col: usize,
+    value: u8,
+    constraints: &Constraints,
+) -> bool {
     // Check row and column
-    for i in 0..9 {
-        if board[row][i] == value || board[i][col] == value {
+    for (i, board_row) in board.iter().enumerate() {
+        if board[row][i] == value || board_row[col] == value {
             return false;
         }
     }
@@ -84,12 +142,24 @@ fn is_valid(board: &[[u8; 9]; 9], row: usize, col: usize, value: u8) -> bool {
         }
     }
 
+    // Check chess constraints
+    for (neighbour_row, neighbour_col) in constraints.neighbours(row, col) {
+        if board[neighbour_row][neighbour_col] == value {
+            return false;
+        }
+    }
+
     true
 }
 
 // 2nd part of the puzzle generation algorithm
 
-pub fn create_puzzle(board: &mut [[u8; 9]; 9], difficulty: u8) {
+pub fn create_puzzle(
+    board: &mut [[u8; 9]; 9],
+    difficulty: u8,
+    constraints: &Constraints,
+    rng: &RandGenerator,
+) {
     // Determine the number of cells to remove based on difficulty
     let num_cells_to_remove = match difficulty {
         1 => 35, // Easy
@@ -101,8 +171,8 @@ pub fn create_puzzle(board: &mut [[u8; 9]; 9], difficulty: u8) {
     // Remove cells randomly until the desired number is reached
     let mut cells_removed = 0;
     while cells_removed < num_cells_to_remove {
-        let row = gen_range(0, 9);
-        let col = gen_range(0, 9);
+        let row = rng.gen_range(0, 9);
+        let col = rng.gen_range(0, 9);
 
         if board[row][col] != 0 {
             // Backup the cell value and temporarily remove it
@@ -111,7 +181,7 @@ pub fn create_puzzle(board: &mut [[u8; 9]; 9], difficulty: u8) {
 
             // Check if the puzzle is still solvable with the current configuration
             let mut temp_board = *board;
-            if has_unique_solution(&mut temp_board) {
+            if has_unique_solution(&mut temp_board, constraints, rng) {
                 cells_removed += 1;
             } else {
                 // If the puzzle is not unique, restore the backup value
@@ -121,23 +191,32 @@ pub fn create_puzzle(board: &mut [[u8; 9]; 9], difficulty: u8) {
     }
 }
 
-fn has_unique_solution(board: &mut [[u8; 9]; 9]) -> bool {
+fn has_unique_solution(
+    board: &mut [[u8; 9]; 9],
+    constraints: &Constraints,
+    rng: &RandGenerator,
+) -> bool {
     let mut solutions = 0;
-    solve_with_unique_solution(board, &mut solutions);
+    solve_with_unique_solution(board, &mut solutions, constraints, rng);
     solutions == 1
 }
 
-fn solve_with_unique_solution(board: &mut [[u8; 9]; 9], solution_count: &mut usize) {
+fn solve_with_unique_solution(
+    board: &mut [[u8; 9]; 9],
+    solution_count: &mut usize,
+    constraints: &Constraints,
+    rng: &RandGenerator,
+) {
     if let Some((row, col)) = find_empty_cell(board) {
         let mut values: Vec<u8> = (1..=9).collect();
-        shuffle(&mut values);
+        shuffle(&mut values, rng);
 
         for value in values {
-            if is_valid(board, row, col, value) {
+            if is_valid(board, row, col, value, constraints) {
                 board[row][col] = value;
 
                 if *solution_count < 2 {
-                    solve_with_unique_solution(board, solution_count);
+                    solve_with_unique_solution(board, solution_count, constraints, rng);
                     if *solution_count > 1 {
                         return;
                     }
@@ -157,12 +236,18 @@ fn solve_with_unique_solution(board: &mut [[u8; 9]; 9], solution_count: &mut usi
 mod tests {
     use super::*;
 
+    fn rng() -> RandGenerator {
+        let rng = RandGenerator::new();
+        rng.srand(7);
+        rng
+    }
+
     #[test]
     fn test_create_easy() {
         let mut board = [[0; 9]; 9];
-        generate_board(&mut board);
+        generate_board(&mut board, &Constraints::default(), &rng());
 
-        create_puzzle(&mut board, 1); // Easy difficulty
+        create_puzzle(&mut board, 1, &Constraints::default(), &rng()); // Easy difficulty
 
         // Count the number of filled cells
         let num_filled_cells = board.iter().flatten().filter(|&&value| value != 0).count();
@@ -172,15 +257,19 @@ mod tests {
 
         // Check that the puzzle is still solvable and has a unique solution
         let mut puzzle_board = board;
-        assert!(has_unique_solution(&mut puzzle_board));
+        assert!(has_unique_solution(
+            &mut puzzle_board,
+            &Constraints::default(),
+            &rng()
+        ));
     }
 
     #[test]
     fn test_create_medium() {
         let mut board = [[0; 9]; 9];
-        generate_board(&mut board);
+        generate_board(&mut board, &Constraints::default(), &rng());
 
-        create_puzzle(&mut board, 2); // Medium difficulty
+        create_puzzle(&mut board, 2, &Constraints::default(), &rng()); // Medium difficulty
 
         // Count the number of filled cells
         let num_filled_cells = board.iter().flatten().filter(|&&value| value != 0).count();
@@ -190,15 +279,19 @@ mod tests {
 
         // Check that the puzzle is still solvable and has a unique solution
         let mut puzzle_board = board;
-        assert!(has_unique_solution(&mut puzzle_board));
+        assert!(has_unique_solution(
+            &mut puzzle_board,
+            &Constraints::default(),
+            &rng()
+        ));
     }
 
     #[test]
     fn test_create_hard() {
         let mut board = [[0; 9]; 9];
-        generate_board(&mut board);
+        generate_board(&mut board, &Constraints::default(), &rng());
 
-        create_puzzle(&mut board, 3); // Hard difficulty
+        create_puzzle(&mut board, 3, &Constraints::default(), &rng()); // Hard difficulty
 
         // Count the number of filled cells
         let num_filled_cells = board.iter().flatten().filter(|&&value| value != 0).count();
@@ -208,6 +301,108 @@ mod tests {
 
         // Check that the puzzle is still solvable and has a unique solution
         let mut puzzle_board = board;
-        assert!(has_unique_solution(&mut puzzle_board));
+        assert!(has_unique_solution(
+            &mut puzzle_board,
+            &Constraints::default(),
+            &rng()
+        ));
+    }
+
+    #[test]
+    fn test_solve_puzzle() {
+        let mut board = [[0; 9]; 9];
+        generate_board(&mut board, &Constraints::default(), &rng());
+        let solution = board;
+
+        create_puzzle(&mut board, 2, &Constraints::default(), &rng());
+        assert_eq!(
+            solve_puzzle(&board, &Constraints::default()),
+            Some(solution)
+        );
+
+        // two of the same digit in a row can't be solved
+        let mut clash = [[0; 9]; 9];
+        clash[0][0] = 4;
+        clash[0][8] = 4;
+        assert_eq!(solve_puzzle(&clash, &Constraints::default()), None);
+
+        // the last cell can't be filled, but only after trying the rest
+        let mut stuck = [[0; 9]; 9];
+        stuck[8] = [1, 2, 3, 4, 5, 6, 7, 8, 0];
+        stuck[0][8] = 9;
+        assert_eq!(solve_puzzle(&stuck, &Constraints::default()), None);
+    }
+
+    fn respects_constraints(board: &[[u8; 9]; 9], constraints: &Constraints) -> bool {
+        for (row, row_values) in board.iter().enumerate() {
+            for (col, &value) in row_values.iter().enumerate() {
+                if value == 0 {
+                    continue;
+                }
+
+                for (neighbour_row, neighbour_col) in constraints.neighbours(row, col) {
+                    if board[neighbour_row][neighbour_col] == value {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    #[test]
+    fn test_generate_anti_knight() {
+        let constraints = Constraints {
+            anti_knight: true,
+            anti_king: false,
+        };
+        let mut board = [[0; 9]; 9];
+        generate_board(&mut board, &constraints, &rng());
+
+        assert!(board.iter().flatten().all(|&value| value != 0));
+        assert!(respects_constraints(&board, &constraints));
+
+        create_puzzle(&mut board, 2, &constraints, &rng());
+
+        let num_filled_cells = board.iter().flatten().filter(|&&value| value != 0).count();
+        assert_eq!(num_filled_cells, 81 - 45);
+
+        let mut puzzle_board = board;
+        assert!(has_unique_solution(&mut puzzle_board, &constraints, &rng()));
+    }
+
+    #[test]
+    fn test_generate_anti_king() {
+        let constraints = Constraints {
+            anti_knight: false,
+            anti_king: true,
+        };
+        let mut board = [[0; 9]; 9];
+        generate_board(&mut board, &constraints, &rng());
+
+        assert!(board.iter().flatten().all(|&value| value != 0));
+        assert!(respects_constraints(&board, &constraints));
+
+        create_puzzle(&mut board, 3, &constraints, &rng());
+
+        let num_filled_cells = board.iter().flatten().filter(|&&value| value != 0).count();
+        assert_eq!(num_filled_cells, 81 - 50);
+
+        let mut puzzle_board = board;
+        assert!(has_unique_solution(&mut puzzle_board, &constraints, &rng()));
+    }
+
+    #[test]
+    fn test_generate_anti_knight_and_king() {
+        let constraints = Constraints {
+            anti_knight: true,
+            anti_king: true,
+        };
+        let mut board = [[0; 9]; 9];
+        generate_board(&mut board, &constraints, &rng());
+
+        assert!(board.iter().flatten().all(|&value| value != 0));
+        assert!(respects_constraints(&board, &constraints));
     }
 }
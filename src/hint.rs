@@ -0,0 +1,118 @@
+// the next number a player could place and why, easiest reasons first
+
+use serde_derive::Serialize;
+
+use crate::board::Board;
+use crate::{BOX_INDEXES, COLUMN_INDEXES, ROW_INDEXES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HintReason {
+    // an entered number that isn't the solution's
+    Wrong,
+    // the only number left for the cell
+    NakedSingle,
+    // the only cell left for the number in a row, column or box
+    HiddenSingle,
+    // nothing simple is left, the solution says so
+    Solution,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Hint {
+    pub index: usize,
+    pub number: u8,
+    pub reason: HintReason,
+}
+
+pub fn candidates(board: &Board, index: usize) -> Vec<u8> {
+    (1..=9)
+        .filter(|&number| board.is_candidate(index, number))
+        .collect()
+}
+
+pub fn find_hint(board: &Board) -> Option<Hint> {
+    if let Some(index) = (0..81).find(|&index| board.is_wrong(index)) {
+        return Some(Hint {
+            index,
+            number: board.solution[index],
+            reason: HintReason::Wrong,
+        });
+    }
+
+    let empty: Vec<usize> = (0..81)
+        .filter(|&index| !board.cell_state[index].has_number())
+        .collect();
+
+    for &index in empty.iter() {
+        if let [number] = candidates(board, index)[..] {
+            return Some(Hint {
+                index,
+                number,
+                reason: HintReason::NakedSingle,
+            });
+        }
+    }
+
+    for house in ROW_INDEXES.iter().chain(COLUMN_INDEXES).chain(BOX_INDEXES) {
+        for number in 1..=9 {
+            let mut cells = house
+                .iter()
+                .filter(|&&index| board.is_candidate(index, number));
+            if let (Some(&index), None) = (cells.next(), cells.next()) {
+                return Some(Hint {
+                    index,
+                    number,
+                    reason: HintReason::HiddenSingle,
+                });
+            }
+        }
+    }
+
+    // older saves have no solution
+    if board.solution.len() != 81 {
+        return None;
+    }
+
+    // the cell with the fewest candidates is the easiest to check
+    empty
+        .iter()
+        .min_by_key(|&&index| candidates(board, index).len())
+        .map(|&index| Hint {
+            index,
+            number: board.solution[index],
+            reason: HintReason::Solution,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{candidates, find_hint, HintReason};
+    use crate::board::Board;
+
+    #[test]
+    fn hints() {
+        let mut board = Board::new();
+        for (index, number) in [1, 2, 3, 4, 5, 6, 7, 8].iter().enumerate() {
+            board.cell_state[index].set_initial_number(*number);
+        }
+        assert_eq!(candidates(&board, 8), vec![9]);
+        assert_eq!(candidates(&board, 9), vec![4, 5, 6, 7, 8, 9]);
+
+        let hint = find_hint(&board).unwrap();
+        assert_eq!((hint.index, hint.number), (8, 9));
+        assert_eq!(hint.reason, HintReason::NakedSingle);
+
+        board.solution = (0..81).map(|index| (index % 9) as u8 + 1).collect();
+        board.cell_state[10].set_number(5);
+        let hint = find_hint(&board).unwrap();
+        assert_eq!((hint.index, hint.number), (10, 2));
+        assert_eq!(hint.reason, HintReason::Wrong);
+
+        // an empty board has nothing simple, only the solution
+        let mut board = Board::new();
+        assert_eq!(find_hint(&board), None);
+        board.solution = vec![1; 81];
+        assert_eq!(find_hint(&board).unwrap().reason, HintReason::Solution);
+    }
+}
@@ -0,0 +1,155 @@
+// the game engine, shared by the window and the terminal front-ends
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod api;
+pub mod board;
+pub mod board_history;
+pub mod board_layout;
+pub mod cell_location;
+pub mod cell_state;
+pub mod constraints;
+pub mod coop;
+pub mod export;
+pub mod font_files;
+#[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+pub mod gamepad;
+pub mod generate;
+pub mod hint;
+pub mod index;
+pub mod locale;
+pub mod move_log;
+pub mod net;
+pub mod puzzle_code;
+pub mod race;
+pub mod settings;
+pub mod sound;
+pub mod speech;
+pub mod summary;
+pub mod theme;
+pub mod touch;
+
+#[cfg_attr(target_arch = "wasm32", path = "save_wasm.rs")]
+#[cfg_attr(not(target_arch = "wasm32"), path = "save_win.rs")]
+pub mod save;
+
+pub const DIGIT_COUNT: u8 = 9;
+pub const MARK_COLOR_COUNT: usize = 8;
+// players in a shared game, the host is player 0
+pub const MAX_PLAYERS: usize = 4;
+
+pub const BOX_INDEXES: &[[usize; 9]; 9] = &[
+    [0, 1, 2, 9, 10, 11, 18, 19, 20],
+    [3, 4, 5, 12, 13, 14, 21, 22, 23],
+    [6, 7, 8, 15, 16, 17, 24, 25, 26],
+    [27, 28, 29, 36, 37, 38, 45, 46, 47],
+    [30, 31, 32, 39, 40, 41, 48, 49, 50],
+    [33, 34, 35, 42, 43, 44, 51, 52, 53],
+    [54, 55, 56, 63, 64, 65, 72, 73, 74],
+    [57, 58, 59, 66, 67, 68, 75, 76, 77],
+    [60, 61, 62, 69, 70, 71, 78, 79, 80],
+];
+
+pub const ROW_INDEXES: &[[usize; 9]; 9] = &[
+    [0, 1, 2, 3, 4, 5, 6, 7, 8],
+    [9, 10, 11, 12, 13, 14, 15, 16, 17],
+    [18, 19, 20, 21, 22, 23, 24, 25, 26],
+    [27, 28, 29, 30, 31, 32, 33, 34, 35],
+    [36, 37, 38, 39, 40, 41, 42, 43, 44],
+    [45, 46, 47, 48, 49, 50, 51, 52, 53],
+    [54, 55, 56, 57, 58, 59, 60, 61, 62],
+    [63, 64, 65, 66, 67, 68, 69, 70, 71],
+    [72, 73, 74, 75, 76, 77, 78, 79, 80],
+];
+
+pub const COLUMN_INDEXES: &[[usize; 9]; 9] = &[
+    [0, 9, 18, 27, 36, 45, 54, 63, 72],
+    [1, 10, 19, 28, 37, 46, 55, 64, 73],
+    [2, 11, 20, 29, 38, 47, 56, 65, 74],
+    [3, 12, 21, 30, 39, 48, 57, 66, 75],
+    [4, 13, 22, 31, 40, 49, 58, 67, 76],
+    [5, 14, 23, 32, 41, 50, 59, 68, 77],
+    [6, 15, 24, 33, 42, 51, 60, 69, 78],
+    [7, 16, 25, 34, 43, 52, 61, 70, 79],
+    [8, 17, 26, 35, 44, 53, 62, 71, 80],
+];
+
+pub fn is_legal_number(number: u8) -> bool {
+    (1..=9).contains(&number)
+}
+
+pub fn is_legal_color(color: u8) -> bool {
+    (1..=MARK_COLOR_COUNT as u8).contains(&color)
+}
+
+pub fn is_legal_index(number: usize) -> bool {
+    (0..=80).contains(&number)
+}
+
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, (seconds / 60) % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format_time, is_legal_color, is_legal_index, is_legal_number};
+
+    #[test]
+    fn legal_number_test() {
+        assert!(!is_legal_number(0));
+        assert!(is_legal_number(1));
+        assert!(is_legal_number(2));
+        assert!(is_legal_number(3));
+        assert!(is_legal_number(4));
+        assert!(is_legal_number(5));
+        assert!(is_legal_number(6));
+        assert!(is_legal_number(7));
+        assert!(is_legal_number(8));
+        assert!(is_legal_number(9));
+        assert!(!is_legal_number(10));
+        assert!(!is_legal_number(11));
+    }
+
+    #[test]
+    fn legal_color_test() {
+        assert!(!is_legal_color(0));
+        assert!(is_legal_color(1));
+        assert!(is_legal_color(8));
+        assert!(!is_legal_color(9));
+    }
+
+    #[test]
+    fn legal_index_test() {
+        assert!(is_legal_index(0));
+        assert!(is_legal_index(1));
+        assert!(is_legal_index(2));
+        assert!(is_legal_index(3));
+        assert!(is_legal_index(4));
+        assert!(is_legal_index(5));
+        assert!(is_legal_index(6));
+        assert!(is_legal_index(7));
+        assert!(is_legal_index(8));
+        assert!(is_legal_index(9));
+        assert!(is_legal_index(78));
+        assert!(is_legal_index(79));
+        assert!(is_legal_index(80));
+        assert!(!is_legal_index(81));
+        assert!(!is_legal_index(82));
+    }
+
+    #[test]
+    fn format_time_test() {
+        assert_eq!(format_time(0.0), "00:00");
+        assert_eq!(format_time(59.9), "00:59");
+        assert_eq!(format_time(61.0), "01:01");
+        assert_eq!(format_time(3599.0), "59:59");
+        assert_eq!(format_time(3600.0), "1:00:00");
+        assert_eq!(format_time(7322.0), "2:02:02");
+    }
+}
@@ -0,0 +1,406 @@
+use serde_derive::{Deserialize, Serialize};
+
+// text shown with the status font has to stay within latin-1, the
+// characters macroquad's default font has
+const ENGLISH: &[(&str, &str)] = &[
+    ("options", "Options"),
+    ("highlight", "Highlight"),
+    ("highlight.full", "Full"),
+    ("highlight.same_digit", "Same digit"),
+    ("highlight.houses", "Houses"),
+    ("highlight.candidates", "Candidates"),
+    ("highlight.none", "None"),
+    ("input", "Input"),
+    ("input.number_first", "Number first"),
+    ("input.cell_first", "Cell first"),
+    ("strict_entry", "Reject conflicting numbers"),
+    (
+        "auto_remove_pencil",
+        "Remove pencil marks when placing a number",
+    ),
+    ("show_timer", "Show timer"),
+    ("show_mistakes", "Show mistakes"),
+    ("show_wrong", "Mark numbers that don't match the solution"),
+    ("sound", "Sound and vibration"),
+    ("sound.volume", "Volume"),
+    ("theme", "Theme"),
+    ("cell_cues", "Patterns and borders besides colours"),
+    ("language", "Language"),
+    ("language.automatic", "Automatic"),
+    ("digits", "Digits"),
+    ("digits.western", "Western"),
+    ("digits.letters", "Letters"),
+    ("digits.arabic_indic", "Arabic-Indic"),
+    ("status.mistakes", "Mistakes"),
+    ("status.solved", "Solved!"),
+    ("menu.pencil", "Pencil"),
+    ("menu.corner", "Corner"),
+    ("menu.color", "Colour"),
+    ("menu.delete", "Delete"),
+    ("menu.undo", "Undo"),
+    ("menu.new", "New"),
+    ("menu.quit", "Quit"),
+    ("new_game.prompt", "New game, pick a difficulty: 1 2 3"),
+    ("too_small", "The terminal is too small"),
+    ("difficulty.1", "Easy"),
+    ("difficulty.2", "Medium"),
+    ("difficulty.3", "Hard"),
+    ("booklet", "Printable booklet"),
+    ("booklet.count", "Puzzles"),
+    ("booklet.export", "Export PDF"),
+    ("booklet.saved", "Booklet saved to"),
+    ("booklet.working", "Making the booklet..."),
+    ("booklet.solutions", "Solutions"),
+    ("share", "Puzzle code, opens with ?p=CODE or as an argument"),
+    ("share.copy", "Copy"),
+    ("victory.new", "New game"),
+    ("victory.replay", "Replay"),
+    ("victory.copy", "Copy result"),
+    ("victory.copied", "Copied"),
+    ("replay", "Replay"),
+    ("replay.watch", "Watch this game"),
+    ("replay.export", "Export replay.json"),
+    ("replay.open", "Open replay.json"),
+    ("replay.saved", "Replay saved to"),
+    ("replay.play", "Play"),
+    ("replay.pause", "Pause"),
+    ("replay.speed", "Speed"),
+    ("replay.moves", "Moves"),
+    ("replay.close", "Close"),
+    ("network", "Play on the local network"),
+    ("network.address", "Address"),
+    ("network.host", "Host"),
+    ("network.join", "Join"),
+    ("network.leave", "Leave"),
+    ("network.hosting", "Waiting for players on port"),
+    ("network.connected", "Connected to"),
+    ("race", "Race"),
+    ("race.waiting", "Waiting for a player"),
+    ("race.opponent", "Opponent"),
+    ("race.won", "You won the race"),
+    ("race.lost", "Your opponent won"),
+    ("race.left", "The other player left"),
+    ("coop", "Play together"),
+    ("coop.joined", "A player joined"),
+    ("coop.left", "A player left"),
+    ("coop.disconnected", "The host left"),
+    (
+        "gamepad.prompts",
+        "A place  X pencil  Y undo  B erase  LB RB number",
+    ),
+    ("speech.row", "Row"),
+    ("speech.column", "column"),
+    ("speech.empty", "empty"),
+    ("speech.candidates", "candidates"),
+    ("speech.given", "given"),
+    ("speech.conflict", "conflict"),
+    ("speech.placed", "placed"),
+    ("speech.rejected", "conflicts, not placed"),
+    ("speech.number", "Number"),
+    ("speech.on", "on"),
+    ("speech.off", "off"),
+    ("constraint.anti_knight", "Anti-knight"),
+    ("constraint.anti_king", "Anti-king"),
+];
+
+const FRENCH: &[(&str, &str)] = &[
+    ("options", "Options"),
+    ("highlight", "Surlignage"),
+    ("highlight.full", "Complet"),
+    ("highlight.same_digit", "Même chiffre"),
+    ("highlight.houses", "Ligne, colonne et carré"),
+    ("highlight.candidates", "Candidats"),
+    ("highlight.none", "Aucun"),
+    ("input", "Saisie"),
+    ("input.number_first", "Chiffre d'abord"),
+    ("input.cell_first", "Case d'abord"),
+    ("strict_entry", "Refuser les chiffres en conflit"),
+    (
+        "auto_remove_pencil",
+        "Effacer les annotations en plaçant un chiffre",
+    ),
+    ("show_timer", "Afficher le chrono"),
+    ("show_mistakes", "Afficher les erreurs"),
+    (
+        "show_wrong",
+        "Marquer les chiffres différents de la solution",
+    ),
+    ("sound", "Son et vibrations"),
+    ("sound.volume", "Volume"),
+    ("theme", "Thème"),
+    ("cell_cues", "Motifs et bordures en plus des couleurs"),
+    ("language", "Langue"),
+    ("language.automatic", "Automatique"),
+    ("digits", "Chiffres"),
+    ("digits.western", "Occidentaux"),
+    ("digits.letters", "Lettres"),
+    ("digits.arabic_indic", "Arabes orientaux"),
+    ("status.mistakes", "Erreurs"),
+    ("status.solved", "Résolu !"),
+    ("menu.pencil", "Crayon"),
+    ("menu.corner", "Coin"),
+    ("menu.color", "Couleur"),
+    ("menu.delete", "Effacer"),
+    ("menu.undo", "Annuler"),
+    ("menu.new", "Nouveau"),
+    ("menu.quit", "Quitter"),
+    (
+        "new_game.prompt",
+        "Nouvelle partie, choisir la difficulté : 1 2 3",
+    ),
+    ("too_small", "Le terminal est trop petit"),
+    ("difficulty.1", "Facile"),
+    ("difficulty.2", "Moyen"),
+    ("difficulty.3", "Difficile"),
+    ("booklet", "Livret à imprimer"),
+    ("booklet.count", "Grilles"),
+    ("booklet.export", "Exporter en PDF"),
+    ("booklet.saved", "Livret enregistré dans"),
+    ("booklet.working", "Préparation du livret..."),
+    ("booklet.solutions", "Solutions"),
+    (
+        "share",
+        "Code de la grille, s'ouvre avec ?p=CODE ou en argument",
+    ),
+    ("share.copy", "Copier"),
+    ("victory.new", "Nouvelle partie"),
+    ("victory.replay", "Rejouer"),
+    ("victory.copy", "Copier"),
+    ("victory.copied", "Fait"),
+    ("replay", "Revoir la partie"),
+    ("replay.watch", "Regarder cette partie"),
+    ("replay.export", "Exporter replay.json"),
+    ("replay.open", "Ouvrir replay.json"),
+    ("replay.saved", "Partie enregistrée dans"),
+    ("replay.play", "Lecture"),
+    ("replay.pause", "Pause"),
+    ("replay.speed", "Vitesse"),
+    ("replay.moves", "Coups"),
+    ("replay.close", "Fermer"),
+    ("network", "Jouer en réseau local"),
+    ("network.address", "Adresse"),
+    ("network.host", "Héberger"),
+    ("network.join", "Rejoindre"),
+    ("network.leave", "Quitter"),
+    ("network.hosting", "En attente de joueurs sur le port"),
+    ("network.connected", "Connecté à"),
+    ("race", "Course"),
+    ("race.waiting", "En attente d'un joueur"),
+    ("race.opponent", "Adversaire"),
+    ("race.won", "Vous avez gagné la course"),
+    ("race.lost", "Votre adversaire a gagné"),
+    ("race.left", "L'autre joueur est parti"),
+    ("coop", "Jouer ensemble"),
+    ("coop.joined", "Un joueur est arrivé"),
+    ("coop.left", "Un joueur est parti"),
+    ("coop.disconnected", "L'hôte est parti"),
+    (
+        "gamepad.prompts",
+        "A placer  X crayon  Y annuler  B effacer  LB RB chiffre",
+    ),
+    ("speech.row", "Ligne"),
+    ("speech.column", "colonne"),
+    ("speech.empty", "vide"),
+    ("speech.candidates", "candidats"),
+    ("speech.given", "donné"),
+    ("speech.conflict", "conflit"),
+    ("speech.placed", "placé"),
+    ("speech.rejected", "en conflit, non placé"),
+    ("speech.number", "Chiffre"),
+    ("speech.on", "activé"),
+    ("speech.off", "désactivé"),
+    ("constraint.anti_knight", "Anti-cavalier"),
+    ("constraint.anti_king", "Anti-roi"),
+];
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::French];
+
+    // accepts "fr", "fr-CA", "fr_FR.UTF-8" and the like
+    pub fn from_locale(locale: &str) -> Option<Language> {
+        let code = locale
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match code.as_str() {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            _ => None,
+        }
+    }
+
+    // languages are always listed in their own language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => ENGLISH,
+            Language::French => FRENCH,
+        }
+    }
+
+    pub fn text(&self, key: &'static str) -> &'static str {
+        translate(self.catalog(), key)
+    }
+}
+
+// falls back to english, then to the key itself
+fn translate(catalog: &'static [(&'static str, &'static str)], key: &'static str) -> &'static str {
+    lookup(catalog, key)
+        .or_else(|| lookup(ENGLISH, key))
+        .unwrap_or(key)
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog
+        .iter()
+        .find(|(catalog_key, _)| *catalog_key == key)
+        .map(|(_, text)| *text)
+}
+
+// the catalog key for the name of a difficulty level
+pub fn difficulty_key(difficulty: u8) -> &'static str {
+    match difficulty {
+        1 => "difficulty.1",
+        2 => "difficulty.2",
+        _ => "difficulty.3",
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum DigitGlyphs {
+    #[default]
+    Western,
+    Letters,
+    ArabicIndic,
+}
+
+const WESTERN_GLYPHS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+const LETTER_GLYPHS: [&str; 9] = ["A", "B", "C", "D", "E", "F", "G", "H", "I"];
+const ARABIC_INDIC_GLYPHS: [&str; 9] = [
+    "\u{661}", "\u{662}", "\u{663}", "\u{664}", "\u{665}", "\u{666}", "\u{667}", "\u{668}",
+    "\u{669}",
+];
+
+impl DigitGlyphs {
+    pub const ALL: [DigitGlyphs; 3] = [
+        DigitGlyphs::Western,
+        DigitGlyphs::Letters,
+        DigitGlyphs::ArabicIndic,
+    ];
+
+    pub fn glyph(&self, number: u8) -> &'static str {
+        let glyphs = match self {
+            DigitGlyphs::Western => &WESTERN_GLYPHS,
+            DigitGlyphs::Letters => &LETTER_GLYPHS,
+            DigitGlyphs::ArabicIndic => &ARABIC_INDIC_GLYPHS,
+        };
+
+        glyphs[number as usize - 1]
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            DigitGlyphs::Western => "digits.western",
+            DigitGlyphs::Letters => "digits.letters",
+            DigitGlyphs::ArabicIndic => "digits.arabic_indic",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn sudoku_locale() -> sapp_jsutils::JsObject;
+}
+
+// navigator.language, through sudoku-bridge.js
+#[cfg(target_arch = "wasm32")]
+fn system_locale() -> Option<String> {
+    let mut locale = String::new();
+    unsafe { sudoku_locale() }.to_string(&mut locale);
+    Some(locale)
+}
+
+pub fn detect_language() -> Language {
+    system_locale()
+        .and_then(|locale| Language::from_locale(&locale))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup, translate, DigitGlyphs, Language, ENGLISH, FRENCH};
+
+    #[test]
+    fn from_locale() {
+        assert_eq!(Language::from_locale("fr"), Some(Language::French));
+        assert_eq!(Language::from_locale("fr-CA"), Some(Language::French));
+        assert_eq!(Language::from_locale("fr_FR.UTF-8"), Some(Language::French));
+        assert_eq!(Language::from_locale("EN-us"), Some(Language::English));
+        assert_eq!(Language::from_locale("de_DE"), None);
+        assert_eq!(Language::from_locale("C"), None);
+        assert_eq!(Language::from_locale(""), None);
+    }
+
+    #[test]
+    fn text_fallback() {
+        assert_eq!(Language::English.text("language"), "Language");
+        assert_eq!(Language::French.text("language"), "Langue");
+        assert_eq!(Language::French.text("missing.key"), "missing.key");
+
+        let partial: &'static [(&str, &str)] = &[("theme", "Thema")];
+        assert_eq!(translate(partial, "theme"), "Thema");
+        assert_eq!(translate(partial, "language"), "Language");
+        assert_eq!(translate(partial, "missing.key"), "missing.key");
+    }
+
+    #[test]
+    fn latin_1_only() {
+        for (key, text) in ENGLISH.iter().chain(FRENCH) {
+            assert!(
+                text.chars().all(|c| c as u32 <= 0xff),
+                "{} isn't latin-1",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn catalogs_match() {
+        for (key, _) in FRENCH {
+            assert!(lookup(ENGLISH, key).is_some(), "{} not in english", key);
+        }
+        for (key, _) in ENGLISH {
+            assert!(lookup(FRENCH, key).is_some(), "{} not in french", key);
+        }
+    }
+
+    #[test]
+    fn digit_glyphs() {
+        assert_eq!(DigitGlyphs::Western.glyph(1), "1");
+        assert_eq!(DigitGlyphs::Letters.glyph(9), "I");
+        assert_eq!(DigitGlyphs::ArabicIndic.glyph(5), "\u{665}");
+    }
+}
@@ -16,6 +16,11 @@ pub enum MenuActions {
     Undo = 11,
     New = 12,
     Delete = 13,
+    InputStyle = 14,
+    Color = 15,
+    ClearColors = 16,
+    PencilLayer = 17,
+    Options = 18,
 }
 
 fn menu_action_from_u8(value: u8) -> Option<MenuActions> {
@@ -52,6 +57,12 @@ pub struct Menu {
     pub undo: MenuItem,
     pub new: MenuItem,
     pub delete: MenuItem,
+    pub input_style: MenuItem,
+    pub color: MenuItem,
+    pub clear_colors: MenuItem,
+    pub pencil_layer: MenuItem,
+    pub options: MenuItem,
+    pub status: MenuItem,
 }
 
 impl Menu {
@@ -70,6 +81,12 @@ impl Menu {
             undo: Default::default(),
             new: Default::default(),
             delete: Default::default(),
+            input_style: Default::default(),
+            color: Default::default(),
+            clear_colors: Default::default(),
+            pencil_layer: Default::default(),
+            options: Default::default(),
+            status: Default::default(),
         }
     }
 
@@ -106,11 +123,48 @@ impl Menu {
             self.item_size,
         );
 
+        self.input_style.update(
+            self.game_padding + (3.0 * self.item_size),
+            self.menu_start_y + second_row_y,
+            self.item_size,
+        );
+
+        self.color.update(
+            self.game_padding + (4.0 * self.item_size),
+            self.menu_start_y + second_row_y,
+            self.item_size,
+        );
+
+        self.clear_colors.update(
+            self.game_padding + (5.0 * self.item_size),
+            self.menu_start_y + second_row_y,
+            self.item_size,
+        );
+
+        self.pencil_layer.update(
+            self.game_padding + (6.0 * self.item_size),
+            self.menu_start_y + second_row_y,
+            self.item_size,
+        );
+
+        self.options.update(
+            self.game_padding + (7.0 * self.item_size),
+            self.menu_start_y + second_row_y,
+            self.item_size,
+        );
+
         self.new.update(
             self.game_padding + (8.0 * self.item_size),
             self.menu_start_y + second_row_y,
             self.item_size,
         );
+
+        // timer and mistakes go on a thin line under the second row
+        self.status.update(
+            self.game_padding,
+            self.menu_start_y + second_row_y + (1.1 * self.item_size),
+            self.item_size * 0.35,
+        );
     }
 
     fn update_landscape(&mut self) {
@@ -146,11 +200,48 @@ impl Menu {
             self.item_size,
         );
 
+        self.input_style.update(
+            self.menu_start_x + second_row_x,
+            self.game_padding + (3.0 * self.item_size),
+            self.item_size,
+        );
+
+        self.color.update(
+            self.menu_start_x + second_row_x,
+            self.game_padding + (4.0 * self.item_size),
+            self.item_size,
+        );
+
+        self.clear_colors.update(
+            self.menu_start_x + second_row_x,
+            self.game_padding + (5.0 * self.item_size),
+            self.item_size,
+        );
+
+        self.pencil_layer.update(
+            self.menu_start_x + second_row_x,
+            self.game_padding + (6.0 * self.item_size),
+            self.item_size,
+        );
+
+        self.options.update(
+            self.menu_start_x + second_row_x,
+            self.game_padding + (7.0 * self.item_size),
+            self.item_size,
+        );
+
         self.new.update(
             self.menu_start_x + second_row_x,
             self.game_padding + (8.0 * self.item_size),
             self.item_size,
         );
+
+        // timer and mistakes go on a thin column next to the second one
+        self.status.update(
+            self.menu_start_x + second_row_x + (1.1 * self.item_size),
+            self.game_padding,
+            self.item_size * 0.35,
+        );
     }
 
     pub fn update(&mut self, board_size: f32, game_padding: f32, portrait: bool) {
@@ -185,6 +276,26 @@ impl Menu {
             return Some(MenuActions::Undo);
         }
 
+        if self.input_style.click(x, y) {
+            return Some(MenuActions::InputStyle);
+        }
+
+        if self.color.click(x, y) {
+            return Some(MenuActions::Color);
+        }
+
+        if self.clear_colors.click(x, y) {
+            return Some(MenuActions::ClearColors);
+        }
+
+        if self.pencil_layer.click(x, y) {
+            return Some(MenuActions::PencilLayer);
+        }
+
+        if self.options.click(x, y) {
+            return Some(MenuActions::Options);
+        }
+
         if self.new.click(x, y) {
             return Some(MenuActions::New);
         }
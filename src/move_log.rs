@@ -0,0 +1,449 @@
+// every change to a game in order, with the time it was made, so the
+// solve can be watched again. each move keeps the cells it changed as
+// they were afterwards, so playing it back doesn't depend on the settings
+// the game was played with
+//
+// the board fills the log itself when it is saved, comparing the cells
+// and the input mode to how they were at the last save
+
+use serde_big_array::BigArray;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::board::{Board, BoardMode, PencilLayer};
+use crate::cell_state::CellState;
+use crate::puzzle_code::PuzzleCode;
+use crate::{is_legal_color, is_legal_index, is_legal_number};
+
+pub const REPLAY_VERSION: u32 = 1;
+
+pub const REPLAY_SPEEDS: [f64; 6] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveKind {
+    Number,
+    Delete,
+    Pencil,
+    Color,
+    Undo,
+    Mode,
+}
+
+// what a cell shows, without the highlighting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellRecord {
+    pub index: usize,
+    pub number: Option<u8>,
+    pub pencil: Vec<u8>,
+    pub corner: Vec<u8>,
+    pub color: Option<u8>,
+}
+
+impl CellRecord {
+    pub fn from_cell(index: usize, cell: &CellState) -> Self {
+        CellRecord {
+            index,
+            number: cell.number,
+            pencil: cell.pencil.iter().flatten().copied().collect(),
+            corner: cell.corner.iter().flatten().copied().collect(),
+            color: cell.color,
+        }
+    }
+
+    // whether the record could be on a board, records from a file or
+    // from another player are checked before they're used
+    pub fn is_legal(&self) -> bool {
+        is_legal_index(self.index)
+            && self.number.is_none_or(is_legal_number)
+            && self
+                .pencil
+                .iter()
+                .chain(self.corner.iter())
+                .all(|&number| is_legal_number(number))
+            && self.color.is_none_or(is_legal_color)
+    }
+
+    // givens never change, a record can't overwrite one
+    pub fn apply(&self, cell: &mut CellState) {
+        if cell.initial {
+            return;
+        }
+
+        cell.number = self.number;
+        cell.pencil = [None; 9];
+        cell.corner = [None; 9];
+        // a mark lives in the slot of its number, like set_pencil puts it
+        for &number in self
+            .pencil
+            .iter()
+            .filter(|&&number| is_legal_number(number))
+        {
+            cell.pencil[number as usize - 1] = Some(number);
+        }
+        for &number in self
+            .corner
+            .iter()
+            .filter(|&&number| is_legal_number(number))
+        {
+            cell.corner[number as usize - 1] = Some(number);
+        }
+        cell.color = self.color;
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeRecord {
+    pub pencil: bool,
+    pub corner: bool,
+    pub color: bool,
+    pub delete: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedMove {
+    // seconds of play, the same clock as the timer
+    pub time: f64,
+    pub kind: MoveKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<CellRecord>,
+    // the input mode after the move, when it changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModeRecord>,
+}
+
+// the state the last move was worked out from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSnapshot {
+    #[serde(with = "BigArray")]
+    cells: [CellState; 81],
+    mode: ModeRecord,
+    undo_points: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MoveLog {
+    pub moves: Vec<LoggedMove>,
+    #[serde(default)]
+    snapshot: Option<LogSnapshot>,
+}
+
+fn mode_record(board: &Board) -> ModeRecord {
+    ModeRecord {
+        pencil: board.mode == BoardMode::Pencil,
+        corner: board.pencil_layer == PencilLayer::Corner,
+        color: board.color_mode,
+        delete: board.delete_mode,
+    }
+}
+
+fn snapshot(board: &Board) -> LogSnapshot {
+    LogSnapshot {
+        cells: board.cell_state,
+        mode: mode_record(board),
+        undo_points: board.history.cell_state_history.len(),
+    }
+}
+
+impl MoveLog {
+    // forgets the moves, the board as it is now is where the log starts
+    pub fn start(&mut self, board: &Board) {
+        self.moves.clear();
+        self.snapshot = Some(snapshot(board));
+    }
+
+    // adds a move for whatever changed since the last call
+    pub fn record(&mut self, board: &Board) {
+        let after = snapshot(board);
+        let before = match self.snapshot.replace(after.clone()) {
+            Some(before) => before,
+            // a save from before there was a log
+            None => return,
+        };
+
+        let cells: Vec<CellRecord> = (0..81)
+            .map(|index| {
+                (
+                    CellRecord::from_cell(index, &before.cells[index]),
+                    CellRecord::from_cell(index, &after.cells[index]),
+                )
+            })
+            .filter(|(old, new)| old != new)
+            .map(|(_, new)| new)
+            .collect();
+        let mode = (before.mode != after.mode).then_some(after.mode);
+
+        let number_placed = cells
+            .iter()
+            .any(|cell| cell.number.is_some() && cell.number != before.cells[cell.index].number);
+        let number_removed = cells
+            .iter()
+            .any(|cell| cell.number.is_none() && before.cells[cell.index].number.is_some());
+        let color_changed = cells
+            .iter()
+            .any(|cell| cell.color != before.cells[cell.index].color);
+
+        let kind = if after.undo_points < before.undo_points {
+            MoveKind::Undo
+        } else if number_placed {
+            MoveKind::Number
+        } else if number_removed {
+            MoveKind::Delete
+        } else if color_changed {
+            MoveKind::Color
+        } else if !cells.is_empty() {
+            MoveKind::Pencil
+        } else if mode.is_some() {
+            MoveKind::Mode
+        } else {
+            return;
+        };
+
+        self.moves.push(LoggedMove {
+            time: board.elapsed,
+            kind,
+            cells,
+            mode,
+        });
+    }
+}
+
+// the log on its own, to be written to a file and watched somewhere else
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub version: u32,
+    pub puzzle: String,
+    pub moves: Vec<LoggedMove>,
+}
+
+impl ReplayFile {
+    pub fn from_board(board: &Board) -> Self {
+        ReplayFile {
+            version: REPLAY_VERSION,
+            puzzle: PuzzleCode::from_board(board).encode(),
+            moves: board.move_log.moves.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: ReplayFile = serde_json::from_str(json).map_err(|error| error.to_string())?;
+        if file.version != REPLAY_VERSION {
+            return Err(format!("replay version {} is not supported", file.version));
+        }
+
+        Ok(file)
+    }
+}
+
+// a recorded game and how far into it the playback is
+pub struct Replay {
+    pub file: ReplayFile,
+    start: Board,
+    pub time: f64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+impl Replay {
+    pub fn new(file: ReplayFile) -> Result<Self, String> {
+        let start = PuzzleCode::decode(&file.puzzle)?.to_board()?;
+        if !file
+            .moves
+            .iter()
+            .flat_map(|m| m.cells.iter())
+            .all(CellRecord::is_legal)
+        {
+            return Err("the replay has a cell that can't be on a board".to_string());
+        }
+
+        Ok(Replay {
+            file,
+            start,
+            time: 0.0,
+            playing: true,
+            speed: 1.0,
+        })
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.file.moves.last().map_or(0.0, |last| last.time)
+    }
+
+    // how many moves have been played at the current time
+    pub fn moves_played(&self) -> usize {
+        self.file
+            .moves
+            .iter()
+            .take_while(|logged| logged.time <= self.time)
+            .count()
+    }
+
+    pub fn advance(&mut self, seconds: f64) {
+        if !self.playing {
+            return;
+        }
+
+        self.seek(self.time + (seconds * self.speed));
+        if self.time >= self.duration() {
+            self.playing = false;
+        }
+    }
+
+    pub fn seek(&mut self, time: f64) {
+        self.time = time.clamp(0.0, self.duration());
+    }
+
+    // playing again from the end starts over
+    pub fn toggle_playing(&mut self) {
+        if !self.playing && self.time >= self.duration() {
+            self.time = 0.0;
+        }
+        self.playing = !self.playing;
+    }
+
+    pub fn cells(&self) -> [CellState; 81] {
+        let mut cells = self.start.cell_state;
+        for logged in self.file.moves.iter().take(self.moves_played()) {
+            for record in logged.cells.iter() {
+                record.apply(&mut cells[record.index]);
+            }
+        }
+
+        cells
+    }
+
+    // a board of the game at the current time, for drawing
+    pub fn board(&self) -> Board {
+        let mut board = Board::new();
+        board.constraints = self.start.constraints;
+        board.solution = self.start.solution.clone();
+        board.cell_state = self.cells();
+        board.elapsed = self.time;
+        board.update_number_count();
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellRecord, MoveKind, Replay, ReplayFile};
+    use crate::board::Board;
+    use crate::cell_state::CellState;
+    use crate::puzzle_code::test_puzzle;
+
+    fn board() -> Board {
+        test_puzzle().to_board().unwrap()
+    }
+
+    fn play(board: &mut Board, time: f64, index: usize, number: u8, pencil: bool) {
+        board.elapsed = time;
+        board.cursor_index = Some(index);
+        board.set_selected_number(number);
+        board.enter_number(pencil);
+        board.record_moves();
+    }
+
+    #[test]
+    fn record_moves() {
+        let mut board = board();
+        play(&mut board, 1.0, 2, 4, false);
+        play(&mut board, 2.0, 3, 6, true);
+
+        board.elapsed = 3.0;
+        board.toggle_pencil_mode();
+        board.record_moves();
+
+        board.elapsed = 4.0;
+        board.undo();
+        board.record_moves();
+
+        // nothing changed, nothing is logged
+        board.record_moves();
+
+        let kinds: Vec<MoveKind> = board.move_log.moves.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MoveKind::Number,
+                MoveKind::Pencil,
+                MoveKind::Mode,
+                MoveKind::Undo
+            ]
+        );
+        assert_eq!(board.move_log.moves[0].time, 1.0);
+        assert_eq!(board.move_log.moves[0].cells[0].number, Some(4));
+        assert_eq!(board.move_log.moves[1].cells[0].pencil, vec![6]);
+        assert!(board.move_log.moves[2].mode.unwrap().pencil);
+        assert!(board.move_log.moves[3].cells[0].pencil.is_empty());
+    }
+
+    #[test]
+    fn cell_record_round_trip() {
+        let mut cell = CellState::new();
+        cell.set_pencil(3);
+        cell.set_pencil(7);
+        cell.set_corner(9);
+
+        let mut restored = CellState::new();
+        CellRecord::from_cell(0, &cell).apply(&mut restored);
+        assert_eq!(restored.pencil, cell.pencil);
+        assert_eq!(restored.corner, cell.corner);
+        assert!(restored.has_this_pencil(3));
+        assert!(!restored.has_this_pencil(1));
+        assert!(restored.has_this_corner(9));
+
+        restored.remove_pencil(3);
+        assert!(!restored.has_this_pencil(3));
+        assert!(restored.has_this_pencil(7));
+        assert!(restored.pencil.iter().flatten().eq([7].iter()));
+    }
+
+    #[test]
+    fn play_back() {
+        let mut board = board();
+        play(&mut board, 1.0, 2, 4, false);
+        play(&mut board, 5.0, 3, 6, false);
+
+        let json = ReplayFile::from_board(&board).to_json();
+        let file = ReplayFile::from_json(&json).unwrap();
+        let mut replay = Replay::new(file).unwrap();
+        assert_eq!(replay.duration(), 5.0);
+        assert!(replay.board().cell_state[0].initial);
+        assert_eq!(replay.board().cell_state[2].number, None);
+
+        replay.advance(1.0);
+        assert_eq!(replay.moves_played(), 1);
+        assert_eq!(replay.board().cell_state[2].number, Some(4));
+
+        replay.speed = 4.0;
+        replay.advance(2.0);
+        assert_eq!(replay.time, 5.0);
+        assert!(!replay.playing);
+        assert_eq!(replay.board().cell_state[3].number, Some(6));
+
+        replay.seek(2.0);
+        assert_eq!(replay.board().cell_state[3].number, None);
+
+        replay.seek(5.0);
+        replay.toggle_playing();
+        assert_eq!(replay.time, 0.0);
+        assert!(replay.playing);
+    }
+
+    #[test]
+    fn bad_files() {
+        assert!(ReplayFile::from_json("{}").is_err());
+
+        let mut file = ReplayFile::from_board(&board());
+        file.version = 2;
+        assert!(ReplayFile::from_json(&file.to_json()).is_err());
+
+        file.version = 1;
+        file.puzzle = "nope".to_string();
+        assert!(Replay::new(file).is_err());
+    }
+}
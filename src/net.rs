@@ -0,0 +1,246 @@
+// json messages over tcp, one a line, read and written without blocking
+// so a frame never waits on the network. connecting happens on a thread
+// for the same reason
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// no message comes near this, a longer line closes the connection
+const MAX_LINE: usize = 64 * 1024;
+// a peer that stops reading is dropped rather than queued for forever
+const MAX_OUTGOING: usize = 16 * 1024 * 1024;
+
+pub fn listen(address: impl ToSocketAddrs) -> std::io::Result<TcpListener> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+
+    Ok(listener)
+}
+
+// whoever is waiting to connect, if anyone is
+pub fn accept(listener: &TcpListener) -> Option<Connection> {
+    let (stream, _) = listener.accept().ok()?;
+    Connection::new(stream).ok()
+}
+
+pub struct Connection {
+    // none while the connection is still being made
+    stream: Option<TcpStream>,
+    connecting: Option<Receiver<std::io::Result<TcpStream>>>,
+    buffer: Vec<u8>,
+    // sent but not yet taken by the socket
+    outgoing: Vec<u8>,
+    // the other side went away, nothing more will come
+    pub closed: bool,
+}
+
+fn open(address: &str) -> std::io::Result<TcpStream> {
+    let address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "no such address"))?;
+
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_nonblocking(true)?;
+    stream.set_nodelay(true)?;
+
+    Ok(stream)
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Connection {
+            stream: Some(stream),
+            connecting: None,
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+            closed: false,
+        })
+    }
+
+    // the address is looked up and connected to on another thread,
+    // messages sent meanwhile wait until it's made
+    pub fn connect(address: &str) -> std::io::Result<Self> {
+        let address = address.to_string();
+        let (sender, receiver) = channel();
+        std::thread::Builder::new().spawn(move || {
+            // nobody is listening any more if the connection was dropped
+            let _ = sender.send(open(&address));
+        })?;
+
+        Ok(Connection {
+            stream: None,
+            connecting: Some(receiver),
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+            closed: false,
+        })
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) {
+        let mut line = serde_json::to_string(message).unwrap();
+        line.push('\n');
+
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.flush();
+        if self.outgoing.len() > MAX_OUTGOING {
+            self.close();
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        self.buffer = Vec::new();
+        self.outgoing = Vec::new();
+    }
+
+    fn poll_connect(&mut self) {
+        let result = match &self.connecting {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => Err(ErrorKind::ConnectionAborted.into()),
+            },
+            None => return,
+        };
+
+        self.connecting = None;
+        match result {
+            Ok(stream) => self.stream = Some(stream),
+            Err(_) => self.closed = true,
+        }
+    }
+
+    // writes as much as the socket takes, the rest goes on the next call
+    fn flush(&mut self) {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        while !self.outgoing.is_empty() && !self.closed {
+            match stream.write(&self.outgoing) {
+                Ok(0) => self.closed = true,
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+
+    // the messages that arrived since the last call
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Vec<T> {
+        self.poll_connect();
+        self.flush();
+
+        let mut messages = vec![];
+        let mut chunk = [0; 4096];
+        while let (Some(stream), false) = (&mut self.stream, self.closed) {
+            match stream.read(&mut chunk) {
+                Ok(0) => self.closed = true,
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                    self.take_lines(&mut messages);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.closed = true,
+            }
+        }
+
+        messages
+    }
+
+    // parses the whole lines in the buffer and leaves the last partial one
+    fn take_lines<T: DeserializeOwned>(&mut self, messages: &mut Vec<T>) {
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            if end > MAX_LINE {
+                self.close();
+                return;
+            }
+
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            // a line that doesn't parse is skipped, not fatal
+            if let Ok(message) = serde_json::from_slice(&line) {
+                messages.push(message);
+            }
+        }
+
+        if self.buffer.len() > MAX_LINE {
+            self.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{accept, listen, Connection, MAX_LINE};
+
+    #[test]
+    fn send_before_connected_and_more_than_the_socket_takes() {
+        let listener = listen("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = Connection::connect(&address).unwrap();
+
+        let line = "x".repeat(1000);
+        for _ in 0..2000 {
+            client.send(&line);
+        }
+
+        let start = Instant::now();
+        let mut server = None;
+        let mut received: Vec<String> = vec![];
+        while received.len() < 2000 && start.elapsed() < Duration::from_secs(10) {
+            server = server.or_else(|| accept(&listener));
+            client.receive::<String>();
+            if let Some(server) = &mut server {
+                received.extend(server.receive::<String>());
+            }
+        }
+
+        assert!(!client.closed);
+        assert_eq!(received.len(), 2000);
+        assert!(received.iter().all(|message| *message == line));
+    }
+
+    #[test]
+    fn a_line_too_long_closes_the_connection() {
+        let listener = listen("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = Connection::connect(&address).unwrap();
+
+        client.send(&"short");
+        client.send(&"x".repeat(MAX_LINE));
+
+        let start = Instant::now();
+        let mut server = None;
+        let mut received: Vec<String> = vec![];
+        while start.elapsed() < Duration::from_secs(10) {
+            server = server.or_else(|| accept(&listener));
+            client.receive::<String>();
+            if let Some(server) = &mut server {
+                received.extend(server.receive::<String>());
+                if server.closed {
+                    break;
+                }
+            }
+        }
+
+        assert!(server.unwrap().closed);
+        assert_eq!(received, ["short"]);
+    }
+}
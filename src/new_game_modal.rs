@@ -1,3 +1,4 @@
+use crate::constraints::Constraints;
 use crate::menu_item::MenuItem;
 
 pub struct NewGameModal {
@@ -9,9 +10,15 @@ pub struct NewGameModal {
     pub font_width: f32,
     pub width: f32,
     pub height: f32,
+    pub row_height: f32,
     pub difficulty_1: MenuItem,
     pub difficulty_2: MenuItem,
     pub difficulty_3: MenuItem,
+    pub anti_knight: MenuItem,
+    pub anti_king: MenuItem,
+    pub constraints: Constraints,
+    // the difficulty a controller is on
+    pub focus: Option<u8>,
 }
 
 impl Default for NewGameModal {
@@ -31,9 +38,14 @@ impl NewGameModal {
             font_width: 0.0,
             width: 0.0,
             height: 0.0,
+            row_height: 0.0,
             difficulty_1: Default::default(),
             difficulty_2: Default::default(),
             difficulty_3: Default::default(),
+            anti_knight: Default::default(),
+            anti_king: Default::default(),
+            constraints: Default::default(),
+            focus: None,
         }
     }
 
@@ -45,6 +57,21 @@ impl NewGameModal {
 
     pub fn hide(&mut self) {
         self.show = false;
+        self.focus = None;
+    }
+
+    pub fn move_focus(&mut self, dx: i32) {
+        let focus = self.focus.unwrap_or(2) as i32 + dx;
+        self.focus = Some(focus.clamp(1, 3) as u8);
+    }
+
+    pub fn focused_item(&self) -> Option<&MenuItem> {
+        match self.focus? {
+            1 => Some(&self.difficulty_1),
+            2 => Some(&self.difficulty_2),
+            3 => Some(&self.difficulty_3),
+            _ => None,
+        }
     }
 
     pub fn update(&mut self, game_square: f32, font_width: f32, font_height: f32) {
@@ -61,10 +88,11 @@ impl NewGameModal {
         let cell_width = self.game_square / 9.0;
         self.width = cell_width * 4.0;
         self.x = self.game_square / 2.0 - (self.width / 2.0);
-        self.height = cell_width * 1.5;
+        self.row_height = cell_width * 1.5;
+        self.height = self.row_height + cell_width;
         self.y = self.game_square / 2.0 - (self.height / 2.0);
 
-        let button_y = self.y + self.height / 2.0 - self.font_height / 2.0;
+        let button_y = self.y + self.row_height / 2.0 - self.font_height / 2.0;
 
         let button_2_x = (self.game_square / 2.0) - self.font_width;
         self.difficulty_2.update(button_2_x, button_y, cell_width);
@@ -74,6 +102,31 @@ impl NewGameModal {
 
         let button_3_x = button_2_x + (cell_width * 1.25);
         self.difficulty_3.update(button_3_x, button_y, cell_width);
+
+        let toggle_size = cell_width * 0.8;
+        let toggle_y = self.y + self.row_height;
+        let center_x = self.x + (self.width / 2.0);
+        self.anti_knight.update(
+            center_x - toggle_size - (cell_width * 0.1),
+            toggle_y,
+            toggle_size,
+        );
+        self.anti_king
+            .update(center_x + (cell_width * 0.1), toggle_y, toggle_size);
+    }
+
+    pub fn click_constraints(&mut self, x: f32, y: f32) -> bool {
+        if self.anti_knight.click(x, y) {
+            self.constraints.toggle_anti_knight();
+            return true;
+        }
+
+        if self.anti_king.click(x, y) {
+            self.constraints.toggle_anti_king();
+            return true;
+        }
+
+        false
     }
 
     pub fn click(&mut self, x: f32, y: f32) -> Option<u8> {
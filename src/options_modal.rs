@@ -0,0 +1,302 @@
+use egui_macroquad::egui;
+
+use crate::locale::{difficulty_key, DigitGlyphs, Language};
+use crate::settings::{HighlightMode, InputStyle, Settings};
+use crate::theme::Theme;
+
+pub struct OptionsModal {
+    pub show: bool,
+    pub booklet_count: usize,
+    pub booklet_difficulty: u8,
+    // set by the export button, the context writes the file
+    pub booklet_request: Option<(usize, u8)>,
+    // a booklet is being made, set by the context
+    #[cfg(not(target_arch = "wasm32"))]
+    pub booklet_working: bool,
+    pub replay_request: Option<ReplayRequest>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub network_mode: NetworkMode,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub network_address: String,
+    // whether a race or a shared game is on, set by the context
+    #[cfg(not(target_arch = "wasm32"))]
+    pub networking: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub network_request: Option<NetworkRequest>,
+    // how the last export went
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayRequest {
+    Watch,
+    #[cfg(not(target_arch = "wasm32"))]
+    Export,
+    #[cfg(not(target_arch = "wasm32"))]
+    Open,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkMode {
+    Race,
+    Coop,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkRequest {
+    Host(NetworkMode),
+    Join(NetworkMode, String),
+    Leave,
+}
+
+impl Default for OptionsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptionsModal {
+    pub fn new() -> Self {
+        OptionsModal {
+            show: false,
+            booklet_count: 6,
+            booklet_difficulty: 2,
+            booklet_request: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            booklet_working: false,
+            replay_request: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            network_mode: NetworkMode::Race,
+            #[cfg(not(target_arch = "wasm32"))]
+            network_address: String::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            networking: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            network_request: None,
+            message: None,
+        }
+    }
+
+    pub fn show(&mut self) {
+        self.show = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.show = false;
+        self.message = None;
+    }
+
+    // returns true when one of the settings was changed
+    pub fn ui(
+        &mut self,
+        egui_ctx: &egui::Context,
+        settings: &mut Settings,
+        themes: &[Theme],
+        dark: bool,
+        language: Language,
+        share_code: &str,
+    ) -> bool {
+        let text = |key| language.text(key);
+        let old_settings = settings.clone();
+        let mut open = true;
+
+        egui_ctx.set_visuals(if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        });
+
+        egui::Window::new(text("options"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx, |ui| {
+                // the code is only there to be copied, edits are thrown away
+                ui.label(text("share"));
+                ui.horizontal(|ui| {
+                    let mut code = share_code.to_string();
+                    ui.add(egui::TextEdit::singleline(&mut code).desired_width(240.0));
+                    if ui.button(text("share.copy")).clicked() {
+                        ui.output_mut(|output| output.copied_text = share_code.to_string());
+                    }
+                });
+                ui.separator();
+
+                ui.label(text("highlight"));
+                ui.horizontal_wrapped(|ui| {
+                    for (mode, label) in [
+                        (HighlightMode::Full, "highlight.full"),
+                        (HighlightMode::SameDigit, "highlight.same_digit"),
+                        (HighlightMode::Houses, "highlight.houses"),
+                        (HighlightMode::Candidates, "highlight.candidates"),
+                        (HighlightMode::None, "highlight.none"),
+                    ] {
+                        ui.radio_value(&mut settings.highlight_mode, mode, text(label));
+                    }
+                });
+                ui.separator();
+
+                ui.label(text("input"));
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut settings.input_style,
+                        InputStyle::NumberFirst,
+                        text("input.number_first"),
+                    );
+                    ui.radio_value(
+                        &mut settings.input_style,
+                        InputStyle::CellFirst,
+                        text("input.cell_first"),
+                    );
+                });
+                ui.checkbox(&mut settings.strict_entry, text("strict_entry"));
+                ui.checkbox(&mut settings.auto_remove_pencil, text("auto_remove_pencil"));
+                ui.separator();
+
+                ui.checkbox(&mut settings.show_timer, text("show_timer"));
+                ui.checkbox(&mut settings.show_mistakes, text("show_mistakes"));
+                ui.checkbox(&mut settings.show_wrong, text("show_wrong"));
+                ui.separator();
+
+                ui.checkbox(&mut settings.sound, text("sound"));
+                ui.add_enabled(
+                    settings.sound,
+                    egui::Slider::new(&mut settings.volume, 0.0..=1.0)
+                        .step_by(0.1)
+                        .text(text("sound.volume")),
+                );
+                ui.separator();
+
+                egui::ComboBox::from_label(text("theme"))
+                    .selected_text(settings.theme.as_str())
+                    .show_ui(ui, |ui| {
+                        for theme in themes {
+                            ui.selectable_value(
+                                &mut settings.theme,
+                                theme.name.clone(),
+                                theme.name.as_str(),
+                            );
+                        }
+                    });
+                ui.checkbox(&mut settings.cell_cues, text("cell_cues"));
+                ui.separator();
+
+                let language_name = settings
+                    .language
+                    .map_or(text("language.automatic"), |language| language.name());
+                egui::ComboBox::from_label(text("language"))
+                    .selected_text(language_name)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut settings.language,
+                            None,
+                            text("language.automatic"),
+                        );
+                        for language in Language::ALL {
+                            ui.selectable_value(
+                                &mut settings.language,
+                                Some(language),
+                                language.name(),
+                            );
+                        }
+                    });
+
+                ui.label(text("digits"));
+                ui.horizontal_wrapped(|ui| {
+                    for digit_glyphs in DigitGlyphs::ALL {
+                        ui.radio_value(
+                            &mut settings.digit_glyphs,
+                            digit_glyphs,
+                            text(digit_glyphs.label_key()),
+                        );
+                    }
+                });
+
+                ui.separator();
+                ui.label(text("replay"));
+                ui.horizontal_wrapped(|ui| {
+                    if ui.button(text("replay.watch")).clicked() {
+                        self.replay_request = Some(ReplayRequest::Watch);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button(text("replay.export")).clicked() {
+                        self.replay_request = Some(ReplayRequest::Export);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button(text("replay.open")).clicked() {
+                        self.replay_request = Some(ReplayRequest::Open);
+                    }
+                });
+
+                // the browser can't open sockets
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.label(text("network"));
+                    if self.networking {
+                        if ui.button(text("network.leave")).clicked() {
+                            self.network_request = Some(NetworkRequest::Leave);
+                        }
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.network_mode, NetworkMode::Race, text("race"));
+                            ui.radio_value(&mut self.network_mode, NetworkMode::Coop, text("coop"));
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button(text("network.host")).clicked() {
+                                self.network_request =
+                                    Some(NetworkRequest::Host(self.network_mode));
+                            }
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.network_address)
+                                    .hint_text(text("network.address"))
+                                    .desired_width(160.0),
+                            );
+                            if ui.button(text("network.join")).clicked() {
+                                let address = self.network_address.trim().to_string();
+                                self.network_request =
+                                    Some(NetworkRequest::Join(self.network_mode, address));
+                            }
+                        });
+                    }
+                }
+
+                // only desktop has somewhere to write the file
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.label(text("booklet"));
+                    ui.add(
+                        egui::Slider::new(&mut self.booklet_count, 1..=48)
+                            .text(text("booklet.count")),
+                    );
+                    ui.horizontal(|ui| {
+                        for difficulty in 1..=3 {
+                            ui.radio_value(
+                                &mut self.booklet_difficulty,
+                                difficulty,
+                                text(difficulty_key(difficulty)),
+                            );
+                        }
+                    });
+                    let export = egui::Button::new(text("booklet.export"));
+                    if ui.add_enabled(!self.booklet_working, export).clicked() {
+                        self.booklet_request = Some((self.booklet_count, self.booklet_difficulty));
+                    }
+                }
+
+                if let Some(message) = &self.message {
+                    ui.label(message.as_str());
+                }
+            });
+
+        if !open {
+            self.hide();
+        }
+
+        *settings != old_settings
+    }
+}
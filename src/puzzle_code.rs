@@ -0,0 +1,330 @@
+// short codes for sending someone the exact same puzzle. the givens, the
+// variant and the seed are packed into bytes and written as url safe base64,
+// so a code fits in a link like index.html?p=CODE
+//
+// byte 0 is the version, byte 1 the flags, then the seed when there is one,
+// then a bit for each cell that has a given and half a byte for each given
+
+use crate::board::Board;
+use crate::constraints::Constraints;
+use crate::generate::solve_puzzle;
+use crate::index::xy_to_index;
+
+const VERSION: u8 = 1;
+
+const FLAG_ANTI_KNIGHT: u8 = 1;
+const FLAG_ANTI_KING: u8 = 2;
+const FLAG_SEED: u8 = 4;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleCode {
+    pub givens: [u8; 81],
+    pub constraints: Constraints,
+    pub seed: Option<u64>,
+}
+
+impl PuzzleCode {
+    pub fn from_board(board: &Board) -> Self {
+        let mut givens = [0; 81];
+        for (i, cell) in board.cell_state.iter().enumerate() {
+            if cell.initial {
+                givens[i] = cell.number.unwrap_or(0);
+            }
+        }
+
+        PuzzleCode {
+            givens,
+            constraints: board.constraints,
+            seed: board.seed,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut flags = 0;
+        if self.constraints.anti_knight {
+            flags |= FLAG_ANTI_KNIGHT;
+        }
+        if self.constraints.anti_king {
+            flags |= FLAG_ANTI_KING;
+        }
+        if self.seed.is_some() {
+            flags |= FLAG_SEED;
+        }
+
+        let mut bytes = vec![VERSION, flags];
+        if let Some(seed) = self.seed {
+            bytes.extend_from_slice(&seed.to_be_bytes());
+        }
+
+        let mut mask = [0; 11];
+        for (i, given) in self.givens.iter().enumerate() {
+            if *given != 0 {
+                mask[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        bytes.extend_from_slice(&mask);
+
+        let values: Vec<u8> = self
+            .givens
+            .iter()
+            .filter(|given| **given != 0)
+            .map(|given| given - 1)
+            .collect();
+        for pair in values.chunks(2) {
+            bytes.push((pair[0] << 4) | pair.get(1).copied().unwrap_or(0));
+        }
+
+        to_base64(&bytes)
+    }
+
+    pub fn decode(code: &str) -> Result<Self, String> {
+        let bytes = from_base64(code.trim()).ok_or("the code has characters it can't have")?;
+        let mut bytes = bytes.iter().copied();
+
+        if bytes.next() != Some(VERSION) {
+            return Err("the code is from another version".to_string());
+        }
+        let flags = bytes.next().ok_or("the code is too short")?;
+        let constraints = Constraints {
+            anti_knight: flags & FLAG_ANTI_KNIGHT != 0,
+            anti_king: flags & FLAG_ANTI_KING != 0,
+        };
+
+        let mut take = |count: usize| -> Result<Vec<u8>, String> {
+            let taken: Vec<u8> = bytes.by_ref().take(count).collect();
+            if taken.len() == count {
+                Ok(taken)
+            } else {
+                Err("the code is too short".to_string())
+            }
+        };
+
+        let seed = if flags & FLAG_SEED != 0 {
+            let seed = take(8)?;
+            Some(u64::from_be_bytes(seed.try_into().unwrap()))
+        } else {
+            None
+        };
+
+        let mask = take(11)?;
+        let given_count: usize = mask.iter().map(|byte| byte.count_ones() as usize).sum();
+        let values = take(given_count.div_ceil(2))?;
+        if bytes.next().is_some() {
+            return Err("the code is too long".to_string());
+        }
+
+        let mut givens = [0; 81];
+        let mut value_index = 0;
+        for (i, given) in givens.iter_mut().enumerate() {
+            if mask[i / 8] & (0x80 >> (i % 8)) == 0 {
+                continue;
+            }
+
+            let byte = values[value_index / 2];
+            let value = if value_index % 2 == 0 {
+                byte >> 4
+            } else {
+                byte & 0xf
+            };
+            if value > 8 {
+                return Err("the code has a cell that isn't a digit".to_string());
+            }
+            *given = value + 1;
+            value_index += 1;
+        }
+
+        Ok(PuzzleCode {
+            givens,
+            constraints,
+            seed,
+        })
+    }
+
+    // a fresh game of the puzzle, as long as it can be solved
+    pub fn to_board(&self) -> Result<Board, String> {
+        let mut grid = [[0; 9]; 9];
+        for (i, given) in self.givens.iter().enumerate() {
+            grid[i / 9][i % 9] = *given;
+        }
+        let solution = solve_puzzle(&grid, &self.constraints)
+            .ok_or_else(|| "the puzzle has no solution".to_string())?;
+
+        let mut board = Board::new();
+        board.constraints = self.constraints;
+        board.seed = self.seed;
+        board.solution = solution.iter().flatten().copied().collect();
+        for (y, row) in grid.iter().enumerate() {
+            for (x, given) in row.iter().enumerate() {
+                board.cell_state[xy_to_index(x, y, 9)].set_initial_number(*given);
+            }
+        }
+        board.update_number_count();
+        board.start_move_log();
+
+        Ok(board)
+    }
+}
+
+// takes a bare code, or pulls p out of a query or fragment like
+// ?p=CODE, #p=CODE or a whole link
+pub fn find_code(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let params = match text.find(['?', '#']) {
+        Some(start) => &text[start + 1..],
+        None => return (!text.is_empty()).then_some(text),
+    };
+
+    params
+        .split(['&', '?', '#'])
+        .find_map(|param| param.strip_prefix("p="))
+        .filter(|code| !code.is_empty())
+}
+
+// the shared puzzle when there is one, the saved game when it's the same
+// puzzle so a reload doesn't lose the progress, or else the saved game
+pub fn startup_board(shared: Option<&str>) -> Board {
+    let saved = Board::load();
+
+    let code = match shared.and_then(find_code).map(PuzzleCode::decode) {
+        Some(Ok(code)) => Some(code),
+        Some(Err(error)) => {
+            eprintln!("ignoring the shared puzzle, {}", error);
+            None
+        }
+        None => None,
+    };
+
+    let fallback =
+        |saved: Option<Board>| saved.unwrap_or_else(|| Board::generate(2, Default::default()));
+
+    match (code, saved) {
+        (Some(code), Some(saved)) if PuzzleCode::from_board(&saved).givens == code.givens => saved,
+        (Some(code), saved) => code.to_board().unwrap_or_else(|error| {
+            eprintln!("ignoring the shared puzzle, {}", error);
+            fallback(saved)
+        }),
+        (None, saved) => fallback(saved),
+    }
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | ((*byte as u32) << (16 - (8 * i)))
+        });
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[((bits >> (18 - (6 * i))) & 0x3f) as usize] as char);
+        }
+    }
+
+    text
+}
+
+fn from_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+
+    for character in text.bytes() {
+        let value = ALPHABET.iter().position(|c| *c == character)? as u32;
+        bits = (bits << 6) | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+// the same well known puzzle for every test that needs one
+#[cfg(test)]
+pub(crate) fn test_puzzle() -> PuzzleCode {
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    let mut givens = [0; 81];
+    for (i, character) in PUZZLE.chars().enumerate() {
+        givens[i] = character as u8 - b'0';
+    }
+
+    PuzzleCode {
+        givens,
+        constraints: Default::default(),
+        seed: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_code, from_base64, test_puzzle, to_base64, PuzzleCode};
+    use crate::constraints::Constraints;
+
+    #[test]
+    fn base64() {
+        for bytes in [&b""[..], b"a", b"ab", b"abc", b"\xff\x00\xfe\x01"] {
+            assert_eq!(from_base64(&to_base64(bytes)).unwrap(), bytes);
+        }
+        assert_eq!(to_base64(b"\xfb\xff"), "-_8");
+        assert_eq!(from_base64("a+b"), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let code = test_puzzle();
+        let text = code.encode();
+        assert!(text.len() < 50);
+        assert_eq!(PuzzleCode::decode(&text), Ok(code.clone()));
+
+        let variant = PuzzleCode {
+            constraints: Constraints {
+                anti_knight: true,
+                anti_king: false,
+            },
+            seed: Some(0x0123_4567_89ab_cdef),
+            ..code
+        };
+        assert_eq!(PuzzleCode::decode(&variant.encode()), Ok(variant));
+    }
+
+    #[test]
+    fn bad_codes() {
+        let text = test_puzzle().encode();
+        assert!(PuzzleCode::decode(&text[..text.len() - 4]).is_err());
+        assert!(PuzzleCode::decode(&format!("{}AAAA", text)).is_err());
+        assert!(PuzzleCode::decode("AgA").is_err());
+        assert!(PuzzleCode::decode("not a code").is_err());
+    }
+
+    #[test]
+    fn board_from_code() {
+        let board = test_puzzle().to_board().unwrap();
+        assert!(board.cell_state[0].initial);
+        assert_eq!(board.cell_state[0].number, Some(5));
+        assert_eq!(board.cell_state[2].number, None);
+        assert_eq!(&board.solution[..9], &[5, 3, 4, 6, 7, 8, 9, 1, 2]);
+        assert_eq!(PuzzleCode::from_board(&board), test_puzzle());
+
+        let mut broken = test_puzzle();
+        broken.givens[1] = 5;
+        assert!(broken.to_board().is_err());
+    }
+
+    #[test]
+    fn codes_in_links() {
+        assert_eq!(find_code("abc"), Some("abc"));
+        assert_eq!(find_code("?p=abc"), Some("abc"));
+        assert_eq!(find_code("#p=abc"), Some("abc"));
+        assert_eq!(
+            find_code("https://example.com/index.html?lang=fr&p=abc#top"),
+            Some("abc")
+        );
+        assert_eq!(find_code("?lang=fr"), None);
+        assert_eq!(find_code(""), None);
+    }
+}
@@ -0,0 +1,332 @@
+// two players racing on the same puzzle over the local network. one side
+// hosts and waits for the other to join, then picks a seed and both make
+// the puzzle from it. the sides tell each other how far they've got, one
+// json message a line, and the first to finish wins
+//
+// everything is non-blocking so the game can poll once a frame
+
+use std::net::{TcpListener, ToSocketAddrs};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::constraints::Constraints;
+use crate::net::{accept, listen, Connection};
+
+pub const DEFAULT_RACE_PORT: u16 = 7879;
+
+// how far a player has got, without giving away where the numbers are
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RaceProgress {
+    pub number_count: [u8; 9],
+    pub mistakes: u32,
+}
+
+impl RaceProgress {
+    pub fn from_board(board: &Board) -> Self {
+        RaceProgress {
+            number_count: board.number_count,
+            mistakes: board.mistakes,
+        }
+    }
+
+    pub fn filled(&self) -> u32 {
+        self.number_count.iter().map(|&count| count as u32).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RaceMessage {
+    Start {
+        difficulty: u8,
+        constraints: Constraints,
+        seed: u64,
+    },
+    Progress(RaceProgress),
+    Solved {
+        elapsed: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaceOutcome {
+    Won,
+    Lost,
+}
+
+// the faster time wins, the host wins a tie so both sides agree
+pub fn race_outcome(mine: Option<f64>, theirs: Option<f64>, host: bool) -> Option<RaceOutcome> {
+    match (mine, theirs) {
+        (Some(mine), Some(theirs)) if mine < theirs || (mine == theirs && host) => {
+            Some(RaceOutcome::Won)
+        }
+        (Some(_), Some(_)) => Some(RaceOutcome::Lost),
+        (Some(_), None) => Some(RaceOutcome::Won),
+        (None, Some(_)) => Some(RaceOutcome::Lost),
+        (None, None) => None,
+    }
+}
+
+#[derive(Debug)]
+pub enum RaceEvent {
+    // a guest joined, the host should start a puzzle
+    Joined,
+    // the host started a puzzle, the guest should play it
+    Start(Box<Board>),
+    OpponentSolved,
+    Disconnected,
+}
+
+pub struct Race {
+    pub host: bool,
+    listener: Option<TcpListener>,
+    connection: Option<Connection>,
+    sent: Option<RaceProgress>,
+    pub opponent: Option<RaceProgress>,
+    pub solved: Option<f64>,
+    pub opponent_solved: Option<f64>,
+}
+
+impl Race {
+    fn new(host: bool, listener: Option<TcpListener>, connection: Option<Connection>) -> Self {
+        Race {
+            host,
+            listener,
+            connection,
+            sent: None,
+            opponent: None,
+            solved: None,
+            opponent_solved: None,
+        }
+    }
+
+    // listens on every interface, the other player is on another machine
+    pub fn host(port: u16) -> std::io::Result<Self> {
+        Self::host_on(("0.0.0.0", port))
+    }
+
+    pub fn host_on(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        Ok(Self::new(true, Some(listen(address)?), None))
+    }
+
+    pub fn join(address: &str) -> std::io::Result<Self> {
+        Ok(Self::new(false, None, Some(Connection::connect(address)?)))
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.listener
+            .as_ref()
+            .and_then(|listener| listener.local_addr().ok())
+            .map(|address| address.port())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    pub fn outcome(&self) -> Option<RaceOutcome> {
+        race_outcome(self.solved, self.opponent_solved, self.host)
+    }
+
+    fn send(&mut self, message: &RaceMessage) {
+        if let Some(connection) = &mut self.connection {
+            connection.send(message);
+        }
+    }
+
+    // the host makes the puzzle and hands the seed to the guest
+    pub fn start(&mut self, board: &Board) {
+        if let (Some(difficulty), Some(seed)) = (board.difficulty, board.seed) {
+            self.begin();
+            self.send(&RaceMessage::Start {
+                difficulty,
+                constraints: board.constraints,
+                seed,
+            });
+        }
+    }
+
+    fn begin(&mut self) {
+        self.sent = None;
+        self.opponent = Some(Default::default());
+        self.solved = None;
+        self.opponent_solved = None;
+    }
+
+    // only sends when something changed, so it can be called every frame
+    pub fn send_progress(&mut self, board: &Board) {
+        let progress = RaceProgress::from_board(board);
+        if self.sent == Some(progress) || self.opponent.is_none() {
+            return;
+        }
+
+        self.sent = Some(progress);
+        self.send(&RaceMessage::Progress(progress));
+    }
+
+    pub fn send_solved(&mut self, elapsed: f64) {
+        if self.solved.is_some() {
+            return;
+        }
+
+        self.solved = Some(elapsed);
+        self.send(&RaceMessage::Solved { elapsed });
+    }
+
+    pub fn poll(&mut self) -> Vec<RaceEvent> {
+        let mut events = vec![];
+
+        if self.connection.is_none() {
+            if let Some(connection) = self.listener.as_ref().and_then(accept) {
+                self.connection = Some(connection);
+                events.push(RaceEvent::Joined);
+            }
+        }
+
+        let (messages, closed) = match &mut self.connection {
+            Some(connection) => (connection.receive(), connection.closed),
+            None => (vec![], false),
+        };
+        for message in messages {
+            events.extend(self.receive(message));
+        }
+
+        if closed {
+            self.connection = None;
+            events.push(RaceEvent::Disconnected);
+        }
+
+        events
+    }
+
+    fn receive(&mut self, message: RaceMessage) -> Option<RaceEvent> {
+        match message {
+            // only the host starts puzzles
+            RaceMessage::Start {
+                difficulty,
+                constraints,
+                seed,
+            } if !self.host && (1..=3).contains(&difficulty) => {
+                self.begin();
+                Some(RaceEvent::Start(Box::new(Board::generate_seeded(
+                    difficulty,
+                    constraints,
+                    seed,
+                ))))
+            }
+            RaceMessage::Start { .. } => None,
+            RaceMessage::Progress(progress) => {
+                if self.opponent.is_some() {
+                    self.opponent = Some(progress);
+                }
+                None
+            }
+            RaceMessage::Solved { elapsed } => {
+                if self.opponent_solved.is_some() || self.opponent.is_none() {
+                    return None;
+                }
+                self.opponent_solved = Some(elapsed);
+                Some(RaceEvent::OpponentSolved)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{race_outcome, Race, RaceEvent, RaceOutcome};
+    use crate::board::Board;
+    use crate::puzzle_code::PuzzleCode;
+
+    fn host_board_filled(board: &Board) -> u32 {
+        board.number_count.iter().map(|&count| count as u32).sum()
+    }
+
+    // polls until the side has something to say
+    fn wait(race: &mut Race) -> Vec<RaceEvent> {
+        let start = Instant::now();
+        loop {
+            let events = race.poll();
+            if !events.is_empty() || start.elapsed() > Duration::from_secs(5) {
+                return events;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn outcome() {
+        assert_eq!(race_outcome(None, None, true), None);
+        assert_eq!(
+            race_outcome(Some(90.0), None, false),
+            Some(RaceOutcome::Won)
+        );
+        assert_eq!(
+            race_outcome(None, Some(90.0), true),
+            Some(RaceOutcome::Lost)
+        );
+        assert_eq!(
+            race_outcome(Some(80.0), Some(90.0), false),
+            Some(RaceOutcome::Won)
+        );
+        assert_eq!(
+            race_outcome(Some(95.0), Some(90.0), true),
+            Some(RaceOutcome::Lost)
+        );
+        // a tie goes to the host, on both sides
+        assert_eq!(
+            race_outcome(Some(90.0), Some(90.0), true),
+            Some(RaceOutcome::Won)
+        );
+        assert_eq!(
+            race_outcome(Some(90.0), Some(90.0), false),
+            Some(RaceOutcome::Lost)
+        );
+    }
+
+    #[test]
+    fn race_on_localhost() {
+        let mut host = Race::host_on("127.0.0.1:0").unwrap();
+        let port = host.port().unwrap();
+        let mut guest = Race::join(&format!("127.0.0.1:{}", port)).unwrap();
+        assert!(guest.is_connected());
+
+        assert!(matches!(wait(&mut host)[..], [RaceEvent::Joined]));
+        let host_board = Board::generate_seeded(1, Default::default(), 42);
+        host.start(&host_board);
+
+        let mut guest_board = match wait(&mut guest).pop() {
+            Some(RaceEvent::Start(board)) => board,
+            event => panic!("expected a start, got {:?}", event),
+        };
+        assert_eq!(guest_board.seed, Some(42));
+        assert_eq!(
+            PuzzleCode::from_board(&guest_board),
+            PuzzleCode::from_board(&host_board)
+        );
+
+        // progress only goes out when it changes
+        let index = (0..81)
+            .find(|&index| !guest_board.cell_state[index].has_number())
+            .unwrap();
+        guest_board.cell_state[index].set_number(guest_board.solution[index]);
+        guest_board.update_number_count();
+        guest.send_progress(&guest_board);
+        guest.send_progress(&guest_board);
+        guest.send_solved(120.0);
+
+        assert!(matches!(wait(&mut host)[..], [RaceEvent::OpponentSolved]));
+        let progress = host.opponent.unwrap();
+        assert_eq!(progress.number_count, guest_board.number_count);
+        assert_eq!(progress.filled(), host_board_filled(&host_board) + 1);
+        assert_eq!(host.outcome(), Some(RaceOutcome::Lost));
+        assert_eq!(guest.outcome(), Some(RaceOutcome::Won));
+
+        drop(guest);
+        assert!(matches!(wait(&mut host)[..], [RaceEvent::Disconnected]));
+        assert!(!host.is_connected());
+    }
+}
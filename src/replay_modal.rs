@@ -0,0 +1,111 @@
+use egui_macroquad::egui;
+
+use crate::board::Board;
+use crate::format_time;
+use crate::locale::Language;
+use crate::move_log::{Replay, REPLAY_SPEEDS};
+
+// while a replay plays the board on screen is the replay's,
+// the game waits here until the replay is closed
+pub struct ReplayModal {
+    pub replay: Option<Replay>,
+    pub game: Option<Board>,
+}
+
+impl Default for ReplayModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayModal {
+    pub fn new() -> Self {
+        ReplayModal {
+            replay: None,
+            game: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn open(&mut self, replay: Replay, game: Board) {
+        self.replay = Some(replay);
+        self.game = Some(game);
+    }
+
+    // hands the game back
+    pub fn close(&mut self) -> Option<Board> {
+        self.replay = None;
+        self.game.take()
+    }
+
+    // returns true when the replay should be closed
+    pub fn ui(&mut self, egui_ctx: &egui::Context, dark: bool, language: Language) -> bool {
+        let text = |key| language.text(key);
+        let mut close = false;
+        let replay = match &mut self.replay {
+            Some(replay) => replay,
+            None => return false,
+        };
+
+        egui_ctx.set_visuals(if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        });
+
+        egui::Window::new(text("replay"))
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+            .show(egui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if replay.playing {
+                        text("replay.pause")
+                    } else {
+                        text("replay.play")
+                    };
+                    if ui.button(label).clicked() {
+                        replay.toggle_playing();
+                    }
+
+                    let mut time = replay.time;
+                    let duration = replay.duration();
+                    let slider =
+                        egui::Slider::new(&mut time, 0.0..=duration.max(1.0)).show_value(false);
+                    if ui.add(slider).changed() {
+                        replay.seek(time);
+                    }
+                    ui.label(format!(
+                        "{} / {}",
+                        format_time(replay.time),
+                        format_time(duration)
+                    ));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(text("replay.speed"));
+                    for speed in REPLAY_SPEEDS {
+                        ui.radio_value(&mut replay.speed, speed, format!("{}x", speed));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{}: {} / {}",
+                        text("replay.moves"),
+                        replay.moves_played(),
+                        replay.file.moves.len()
+                    ));
+                    if ui.button(text("replay.close")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        close
+    }
+}
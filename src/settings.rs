@@ -0,0 +1,126 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::locale::{DigitGlyphs, Language};
+use crate::save::{load, save};
+use crate::theme::DEFAULT_THEME;
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum InputStyle {
+    #[default]
+    NumberFirst,
+    CellFirst,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum HighlightMode {
+    #[default]
+    Full,
+    SameDigit,
+    Houses,
+    Candidates,
+    None,
+}
+
+// preferences shared by every game, saved apart from the board
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub highlight_mode: HighlightMode,
+    pub strict_entry: bool,
+    pub auto_remove_pencil: bool,
+    pub show_timer: bool,
+    pub show_mistakes: bool,
+    pub input_style: InputStyle,
+    pub theme: String,
+    pub cell_cues: bool,
+    pub show_wrong: bool,
+    // none follows the system language
+    pub language: Option<Language>,
+    pub digit_glyphs: DigitGlyphs,
+    // sound effects, and vibration where there is any
+    pub sound: bool,
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            highlight_mode: HighlightMode::Full,
+            strict_entry: true,
+            auto_remove_pencil: true,
+            show_timer: true,
+            show_mistakes: true,
+            input_style: InputStyle::NumberFirst,
+            theme: DEFAULT_THEME.to_string(),
+            cell_cues: false,
+            show_wrong: false,
+            language: None,
+            digit_glyphs: DigitGlyphs::Western,
+            sound: true,
+            volume: 0.5,
+        }
+    }
+
+    pub fn load() -> Self {
+        load("settings")
+            .and_then(|settings_str| serde_json::from_str::<Settings>(&settings_str).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let save_data = serde_json::to_string(self).unwrap();
+        save("settings", save_data.as_str());
+    }
+
+    pub fn toggle_input_style(&mut self) {
+        match self.input_style {
+            InputStyle::NumberFirst => self.input_style = InputStyle::CellFirst,
+            InputStyle::CellFirst => self.input_style = InputStyle::NumberFirst,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HighlightMode, InputStyle, Settings, DEFAULT_THEME};
+
+    #[test]
+    fn default_settings() {
+        let settings = Settings::default();
+        assert_eq!(settings, Settings::new());
+        assert_eq!(settings.highlight_mode, HighlightMode::Full);
+        assert!(settings.strict_entry);
+        assert!(settings.auto_remove_pencil);
+        assert_eq!(settings.input_style, InputStyle::NumberFirst);
+        assert_eq!(settings.theme, DEFAULT_THEME);
+    }
+
+    #[test]
+    fn toggle_input_style() {
+        let mut settings = Settings::new();
+        settings.toggle_input_style();
+        assert_eq!(settings.input_style, InputStyle::CellFirst);
+        settings.toggle_input_style();
+        assert_eq!(settings.input_style, InputStyle::NumberFirst);
+        assert_eq!(settings.theme, DEFAULT_THEME);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let settings = serde_json::from_str::<Settings>(r#"{"strict_entry":false}"#).unwrap();
+        assert!(!settings.strict_entry);
+        assert!(settings.show_timer);
+        assert!(settings.sound);
+        assert_eq!(settings.volume, 0.5);
+        assert_eq!(settings.highlight_mode, HighlightMode::Full);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+    }
+}
@@ -0,0 +1,216 @@
+// the game's sounds, made here as wav files rather than shipped, and
+// which of them the last move calls for
+
+use crate::board::Board;
+use crate::{BOX_INDEXES, COLUMN_INDEXES, DIGIT_COUNT, ROW_INDEXES};
+
+const SAMPLE_RATE: u32 = 22050;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEvent {
+    Place,
+    Reject,
+    // the last of a number was placed
+    NumberDone,
+    // a row, column or box was filled without conflicts
+    HouseDone,
+    Victory,
+}
+
+pub const SOUND_EVENTS: [SoundEvent; 5] = [
+    SoundEvent::Place,
+    SoundEvent::Reject,
+    SoundEvent::NumberDone,
+    SoundEvent::HouseDone,
+    SoundEvent::Victory,
+];
+
+// (frequency, seconds) one after the other
+fn notes(event: SoundEvent) -> &'static [(f32, f32)] {
+    match event {
+        SoundEvent::Place => &[(880.0, 0.06)],
+        SoundEvent::Reject => &[(220.0, 0.1), (175.0, 0.16)],
+        SoundEvent::NumberDone => &[(659.25, 0.08), (987.77, 0.12)],
+        SoundEvent::HouseDone => &[(523.25, 0.07), (659.25, 0.07), (783.99, 0.14)],
+        SoundEvent::Victory => &[
+            (523.25, 0.12),
+            (659.25, 0.12),
+            (783.99, 0.12),
+            (1046.5, 0.4),
+        ],
+    }
+}
+
+// a soft bell for each note, 16 bit mono pcm
+pub fn wav(notes: &[(f32, f32)]) -> Vec<u8> {
+    let mut samples: Vec<i16> = vec![];
+    for &(frequency, seconds) in notes {
+        let count = (seconds * SAMPLE_RATE as f32) as usize;
+        for i in 0..count {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // a quick fade in and a longer fade out so the notes don't click
+            let envelope = (t / 0.005).min(1.0) * (1.0 - (i as f32 / count as f32)).powi(2);
+            let wave = (t * frequency * std::f32::consts::TAU).sin()
+                + (0.3 * (t * frequency * 2.0 * std::f32::consts::TAU).sin());
+            samples.push((wave * envelope * 0.6 * i16::MAX as f32 / 1.3) as i16);
+        }
+    }
+
+    let data_size = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // pcm, one channel
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    bytes
+}
+
+pub fn event_wav(event: SoundEvent) -> Vec<u8> {
+    wav(notes(event))
+}
+
+// rows, then columns, then boxes
+fn houses() -> impl Iterator<Item = &'static [usize; 9]> {
+    ROW_INDEXES.iter().chain(COLUMN_INDEXES).chain(BOX_INDEXES)
+}
+
+fn is_house_done(board: &Board, house: &[usize; 9]) -> bool {
+    let mut seen = [false; 9];
+    house
+        .iter()
+        .all(|&index| match board.cell_state[index].number {
+            Some(number) if !seen[(number - 1) as usize] => {
+                seen[(number - 1) as usize] = true;
+                true
+            }
+            _ => false,
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    numbers: Vec<Option<u8>>,
+    mistakes: u32,
+    numbers_done: usize,
+    houses_done: usize,
+    solved: bool,
+}
+
+impl Snapshot {
+    fn from_board(board: &Board) -> Self {
+        Snapshot {
+            numbers: board.cell_state.iter().map(|cell| cell.number).collect(),
+            mistakes: board.mistakes,
+            numbers_done: (1..=DIGIT_COUNT)
+                .filter(|&number| board.is_number_done(number))
+                .count(),
+            houses_done: houses().filter(|house| is_house_done(board, house)).count(),
+            solved: board.is_victory(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SoundCues {
+    last: Option<Snapshot>,
+}
+
+impl SoundCues {
+    // the sound for what changed since the last call, the biggest event wins
+    pub fn update(&mut self, board: &Board) -> Option<SoundEvent> {
+        let now = Snapshot::from_board(board);
+        let last = match self.last.replace(now.clone()) {
+            Some(last) if last != now => last,
+            _ => return None,
+        };
+
+        let placed = (0..81)
+            .filter(|&index| {
+                now.numbers[index].is_some() && now.numbers[index] != last.numbers[index]
+            })
+            .count();
+
+        if now.solved && !last.solved {
+            Some(SoundEvent::Victory)
+        } else if now.mistakes > last.mistakes {
+            Some(SoundEvent::Reject)
+        } else if placed != 1 {
+            // a new board or an undo, nothing was played
+            None
+        } else if now.houses_done > last.houses_done {
+            Some(SoundEvent::HouseDone)
+        } else if now.numbers_done > last.numbers_done {
+            Some(SoundEvent::NumberDone)
+        } else {
+            Some(SoundEvent::Place)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{event_wav, SoundCues, SoundEvent, SAMPLE_RATE, SOUND_EVENTS};
+    use crate::board::Board;
+
+    #[test]
+    fn wav_header() {
+        for event in SOUND_EVENTS {
+            let bytes = event_wav(event);
+            assert_eq!(&bytes[..4], b"RIFF");
+            assert_eq!(&bytes[8..16], b"WAVEfmt ");
+            assert_eq!(&bytes[36..40], b"data");
+            let size = u32::from_le_bytes(bytes[40..44].try_into().unwrap());
+            assert_eq!(bytes.len(), 44 + size as usize);
+        }
+
+        // 0.06 seconds of 16 bit samples
+        let place = event_wav(SoundEvent::Place);
+        assert_eq!(place.len(), 44 + (2 * (0.06 * SAMPLE_RATE as f32) as usize));
+    }
+
+    #[test]
+    fn cues() {
+        let mut board = Board::new();
+        let mut cues = SoundCues::default();
+        assert_eq!(cues.update(&board), None);
+
+        // most of the first row, and all the eights but one
+        for i in 0..7 {
+            board.cell_state[i].set_number((i + 1) as u8);
+        }
+        for row in 1..9 {
+            board.cell_state[(row * 9) + ((row + 3) % 9)].set_number(8);
+        }
+        board.update_number_count();
+        assert_eq!(cues.update(&board), None);
+
+        board.cell_state[7].set_number(8);
+        board.update_number_count();
+        assert_eq!(cues.update(&board), Some(SoundEvent::NumberDone));
+
+        board.cell_state[8].set_number(9);
+        board.update_number_count();
+        assert_eq!(cues.update(&board), Some(SoundEvent::HouseDone));
+
+        board.cell_state[20].set_number(4);
+        board.update_number_count();
+        assert_eq!(cues.update(&board), Some(SoundEvent::Place));
+
+        board.mistakes += 1;
+        assert_eq!(cues.update(&board), Some(SoundEvent::Reject));
+        assert_eq!(cues.update(&board), None);
+    }
+}
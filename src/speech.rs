@@ -0,0 +1,229 @@
+// what a screen reader says: the focused cell, and what just happened on
+// the board. the announcer compares the board with the last frame so
+// every way of playing, mouse, keys, touch or another player, is covered
+
+use crate::board::Board;
+use crate::cell_state::CellState;
+use crate::index::index_to_xy;
+use crate::locale::Language;
+use crate::summary::GameSummary;
+use crate::DIGIT_COUNT;
+
+fn cell_name(index: usize, language: Language) -> String {
+    let (x, y) = index_to_xy(index, DIGIT_COUNT as usize);
+    format!(
+        "{} {}, {} {}",
+        language.text("speech.row"),
+        y + 1,
+        language.text("speech.column"),
+        x + 1
+    )
+}
+
+// the centre and corner pencil marks together, in order
+fn marks(cell: &CellState) -> Vec<u8> {
+    let mut marks: Vec<u8> = cell
+        .pencil
+        .iter()
+        .chain(cell.corner.iter())
+        .flatten()
+        .copied()
+        .collect();
+    marks.sort_unstable();
+    marks.dedup();
+    marks
+}
+
+fn numbers(marks: &[u8]) -> String {
+    marks
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// "Row 3, column 5, empty, candidates 1 4 7"
+pub fn describe_cell(board: &Board, index: usize, language: Language) -> String {
+    let cell = &board.cell_state[index];
+    let mut parts = vec![cell_name(index, language)];
+
+    match cell.number {
+        Some(number) => {
+            parts.push(number.to_string());
+            if cell.initial {
+                parts.push(language.text("speech.given").to_string());
+            }
+            if board.is_conflicting(index) {
+                parts.push(language.text("speech.conflict").to_string());
+            }
+        }
+        None => {
+            parts.push(language.text("speech.empty").to_string());
+            let marks = marks(cell);
+            if !marks.is_empty() {
+                parts.push(format!(
+                    "{} {}",
+                    language.text("speech.candidates"),
+                    numbers(&marks)
+                ));
+            }
+        }
+    }
+
+    parts.join(", ")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    focus: Option<usize>,
+    numbers: Vec<Option<u8>>,
+    marks: Vec<Vec<u8>>,
+    selected_number: Option<u8>,
+    mistakes: u32,
+    solved: bool,
+}
+
+impl Snapshot {
+    fn from_board(board: &Board) -> Self {
+        Snapshot {
+            focus: board.cursor_index.or(board.selected_index),
+            numbers: board.cell_state.iter().map(|cell| cell.number).collect(),
+            marks: board.cell_state.iter().map(marks).collect(),
+            selected_number: board.selected_number,
+            mistakes: board.mistakes,
+            solved: board.is_victory(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Announcer {
+    last: Option<Snapshot>,
+}
+
+impl Announcer {
+    // what changed since the last call, if it's worth saying
+    pub fn update(&mut self, board: &Board, language: Language) -> Option<String> {
+        let now = Snapshot::from_board(board);
+        let last = match self.last.replace(now.clone()) {
+            Some(last) if last != now => last,
+            // nothing to compare with on the first call
+            _ => return None,
+        };
+
+        let placed: Vec<usize> = (0..81)
+            .filter(|&index| {
+                now.numbers[index].is_some() && now.numbers[index] != last.numbers[index]
+            })
+            .collect();
+
+        if now.solved && !last.solved {
+            let [first, second] = GameSummary::from_board(board).lines(language);
+            return Some(format!(
+                "{} {}, {}",
+                language.text("status.solved"),
+                first,
+                second
+            ));
+        }
+
+        // strict entry takes a conflicting number straight back out
+        if now.mistakes > last.mistakes && placed.is_empty() {
+            return now
+                .selected_number
+                .map(|number| format!("{} {}", number, language.text("speech.rejected")));
+        }
+
+        // a new board fills many cells at once, that isn't a placement
+        if let [index] = placed[..] {
+            let mut text = format!(
+                "{}, {} {}",
+                cell_name(index, language),
+                language.text("speech.placed"),
+                now.numbers[index].unwrap()
+            );
+            if board.is_conflicting(index) {
+                text = format!("{}, {}", text, language.text("speech.conflict"));
+            }
+            return Some(text);
+        }
+
+        if let Some(focus) = now.focus {
+            if now.focus != last.focus
+                || now.numbers[focus] != last.numbers[focus]
+                || now.marks[focus] != last.marks[focus]
+            {
+                return Some(describe_cell(board, focus, language));
+            }
+        }
+
+        match now.selected_number {
+            Some(number) if now.selected_number != last.selected_number => {
+                Some(format!("{} {}", language.text("speech.number"), number))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_cell, Announcer};
+    use crate::board::Board;
+    use crate::locale::Language;
+
+    #[test]
+    fn describe() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(5);
+        board.cell_state[1].set_number(5);
+        board.cell_state[22].set_pencil(7);
+        board.cell_state[22].set_pencil(1);
+        board.cell_state[22].set_corner(4);
+
+        assert_eq!(
+            describe_cell(&board, 0, Language::English),
+            "Row 1, column 1, 5, given, conflict"
+        );
+        assert_eq!(
+            describe_cell(&board, 22, Language::English),
+            "Row 3, column 5, empty, candidates 1 4 7"
+        );
+        assert_eq!(
+            describe_cell(&board, 80, Language::French),
+            "Ligne 9, colonne 9, vide"
+        );
+    }
+
+    #[test]
+    fn announcements() {
+        let mut board = Board::new();
+        let mut announcer = Announcer::default();
+        assert_eq!(announcer.update(&board, Language::English), None);
+
+        board.cursor_index = Some(10);
+        assert_eq!(
+            announcer.update(&board, Language::English).as_deref(),
+            Some("Row 2, column 2, empty")
+        );
+        assert_eq!(announcer.update(&board, Language::English), None);
+
+        board.selected_number = Some(3);
+        assert_eq!(
+            announcer.update(&board, Language::English).as_deref(),
+            Some("Number 3")
+        );
+
+        board.cell_state[10].set_number(3);
+        assert_eq!(
+            announcer.update(&board, Language::English).as_deref(),
+            Some("Row 2, column 2, placed 3")
+        );
+
+        board.mistakes += 1;
+        assert_eq!(
+            announcer.update(&board, Language::English).as_deref(),
+            Some("3 conflicts, not placed")
+        );
+    }
+}
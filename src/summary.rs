@@ -0,0 +1,104 @@
+// what a finished game looks like afterwards, for the victory screen and
+// as a short text to paste in a chat, a square for each box coloured by
+// the mistakes made in it
+
+use crate::board::Board;
+use crate::format_time;
+use crate::locale::{difficulty_key, Language};
+use crate::puzzle_code::PuzzleCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSummary {
+    pub difficulty: Option<u8>,
+    pub elapsed: f64,
+    pub mistakes: u32,
+    pub box_mistakes: [u32; 9],
+    pub code: String,
+}
+
+fn box_square(mistakes: u32) -> &'static str {
+    match mistakes {
+        0 => "\u{1f7e9}",
+        1 => "\u{1f7e8}",
+        _ => "\u{1f7e5}",
+    }
+}
+
+impl GameSummary {
+    pub fn from_board(board: &Board) -> Self {
+        GameSummary {
+            difficulty: board.difficulty,
+            elapsed: board.elapsed,
+            mistakes: board.mistakes,
+            box_mistakes: board.box_mistakes,
+            code: PuzzleCode::from_board(board).encode(),
+        }
+    }
+
+    // the difficulty and time, then the mistakes
+    pub fn lines(&self, language: Language) -> [String; 2] {
+        let time = format_time(self.elapsed);
+        let first = match self.difficulty {
+            Some(difficulty) => format!("{}   {}", language.text(difficulty_key(difficulty)), time),
+            None => time,
+        };
+
+        [
+            first,
+            format!("{}: {}", language.text("status.mistakes"), self.mistakes),
+        ]
+    }
+
+    pub fn share_text(&self, language: Language) -> String {
+        let [first, second] = self.lines(language);
+        let grid: Vec<String> = self
+            .box_mistakes
+            .chunks(3)
+            .map(|row| row.iter().map(|mistakes| box_square(*mistakes)).collect())
+            .collect();
+
+        format!(
+            "Sudoku   {}\n{}\n{}\n?p={}\n",
+            first,
+            second,
+            grid.join("\n"),
+            self.code
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameSummary;
+    use crate::board::Board;
+    use crate::locale::Language;
+
+    #[test]
+    fn share_text() {
+        let mut board = Board::new();
+        board.cell_state[0].set_initial_number(5);
+        board.difficulty = Some(3);
+        board.elapsed = 754.0;
+        board.mistakes = 3;
+        board.box_mistakes[1] = 1;
+        board.box_mistakes[8] = 2;
+
+        let summary = GameSummary::from_board(&board);
+        assert_eq!(
+            summary.lines(Language::French),
+            ["Difficile   12:34".to_string(), "Erreurs: 3".to_string()]
+        );
+
+        let text = summary.share_text(Language::English);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Sudoku   Hard   12:34");
+        assert_eq!(lines[1], "Mistakes: 3");
+        assert_eq!(lines[2], "\u{1f7e9}\u{1f7e8}\u{1f7e9}");
+        assert_eq!(lines[4], "\u{1f7e9}\u{1f7e9}\u{1f7e5}");
+        assert_eq!(lines[5], format!("?p={}", summary.code));
+
+        board.difficulty = None;
+        let [first, _] = GameSummary::from_board(&board).lines(Language::English);
+        assert_eq!(first, "12:34");
+    }
+}
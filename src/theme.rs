@@ -0,0 +1,502 @@
+use macroquad::color::Color;
+use macroquad::color_u8;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{MARK_COLOR_COUNT, MAX_PLAYERS};
+
+pub const DEFAULT_THEME: &str = "Light";
+
+// custom themes are read from this file next to the save on desktop
+#[cfg(not(target_arch = "wasm32"))]
+const CUSTOM_THEMES_PATH: &str = "themes.json";
+
+// the Okabe-Ito palette, told apart with every kind of colour blindness
+const OKABE_ITO_MARKS: [Color; MARK_COLOR_COUNT] = [
+    color_u8!(230, 159, 0, 255),
+    color_u8!(86, 180, 233, 255),
+    color_u8!(0, 158, 115, 255),
+    color_u8!(240, 228, 66, 255),
+    color_u8!(0, 114, 178, 255),
+    color_u8!(213, 94, 0, 255),
+    color_u8!(204, 121, 167, 255),
+    color_u8!(120, 120, 120, 255),
+];
+
+// the players of a shared game, from the same palette and dark enough
+// for numbers on light cells
+pub const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    color_u8!(0, 114, 178, 255),
+    color_u8!(213, 94, 0, 255),
+    color_u8!(0, 158, 115, 255),
+    color_u8!(204, 121, 167, 255),
+];
+
+// every colour the game draws with, fields missing from a custom
+// theme fall back to the light theme
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    pub dark: bool,
+    #[serde(with = "hex_color")]
+    pub background: Color,
+    #[serde(with = "hex_color")]
+    pub cell_normal: Color,
+    #[serde(with = "hex_color")]
+    pub cell_emphasize: Color,
+    #[serde(with = "hex_color")]
+    pub cell_pencil_emphasize: Color,
+    #[serde(with = "hex_color")]
+    pub cell_highlighted: Color,
+    #[serde(with = "hex_color")]
+    pub cell_multi_selected: Color,
+    #[serde(with = "hex_colors")]
+    pub cell_marks: [Color; MARK_COLOR_COUNT],
+    #[serde(with = "hex_color")]
+    pub cell_text: Color,
+    #[serde(with = "hex_color")]
+    pub cell_text_initial: Color,
+    #[serde(with = "hex_color")]
+    pub cell_text_conflict: Color,
+    #[serde(with = "hex_color")]
+    pub cell_text_pencil: Color,
+    #[serde(with = "hex_color")]
+    pub cell_line: Color,
+    #[serde(with = "hex_color")]
+    pub box_line: Color,
+    #[serde(with = "hex_color")]
+    pub menu_text: Color,
+    #[serde(with = "hex_color")]
+    pub menu_text_selected: Color,
+    #[serde(with = "hex_color")]
+    pub menu_number_background_normal: Color,
+    #[serde(with = "hex_color")]
+    pub menu_number_background_pencil: Color,
+    #[serde(with = "hex_color")]
+    pub menu_delete_background: Color,
+    #[serde(with = "hex_color")]
+    pub modal_background: Color,
+    #[serde(with = "hex_color")]
+    pub modal_panel: Color,
+    #[serde(with = "hex_color")]
+    pub modal_text: Color,
+    #[serde(with = "hex_color")]
+    pub modal_difficulty_one: Color,
+    #[serde(with = "hex_color")]
+    pub modal_difficulty_two: Color,
+    #[serde(with = "hex_color")]
+    pub modal_difficulty_three: Color,
+    #[serde(with = "hex_color")]
+    pub modal_victory_gold: Color,
+    #[serde(with = "hex_color")]
+    pub modal_victory_red: Color,
+    #[serde(with = "hex_color")]
+    pub status_text: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Theme {
+            name: DEFAULT_THEME.to_string(),
+            dark: false,
+            background: color_u8!(255, 255, 255, 255),
+            cell_normal: color_u8!(255, 255, 255, 255),
+            cell_emphasize: color_u8!(186, 209, 255, 255),
+            cell_pencil_emphasize: color_u8!(255, 193, 140, 255),
+            cell_highlighted: color_u8!(219, 219, 219, 255),
+            cell_multi_selected: color_u8!(120, 200, 140, 140),
+            cell_marks: [
+                color_u8!(255, 99, 99, 255),
+                color_u8!(255, 170, 60, 255),
+                color_u8!(250, 220, 60, 255),
+                color_u8!(110, 200, 90, 255),
+                color_u8!(70, 190, 200, 255),
+                color_u8!(80, 120, 230, 255),
+                color_u8!(170, 100, 220, 255),
+                color_u8!(150, 150, 150, 255),
+            ],
+            cell_text: color_u8!(41, 91, 135, 255),
+            cell_text_initial: color_u8!(0, 0, 0, 255),
+            cell_text_conflict: color_u8!(214, 40, 40, 255),
+            cell_text_pencil: color_u8!(0, 0, 0, 255),
+            cell_line: color_u8!(130, 130, 130, 255),
+            box_line: color_u8!(0, 0, 0, 255),
+            menu_text: color_u8!(0, 0, 0, 255),
+            menu_text_selected: color_u8!(255, 255, 255, 255),
+            menu_number_background_normal: color_u8!(56, 76, 107, 255),
+            menu_number_background_pencil: color_u8!(226, 138, 43, 255),
+            menu_delete_background: color_u8!(255, 0, 0, 255),
+            modal_background: color_u8!(0, 0, 0, 128),
+            modal_panel: color_u8!(255, 255, 255, 255),
+            modal_text: color_u8!(0, 0, 0, 255),
+            modal_difficulty_one: color_u8!(0, 128, 0, 255),
+            modal_difficulty_two: color_u8!(255, 128, 0, 255),
+            modal_difficulty_three: color_u8!(255, 0, 0, 255),
+            modal_victory_gold: color_u8!(242, 183, 5, 255),
+            modal_victory_red: color_u8!(239, 50, 50, 255),
+            status_text: color_u8!(0, 0, 0, 255),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Theme {
+            name: "Dark".to_string(),
+            dark: true,
+            background: color_u8!(24, 26, 31, 255),
+            cell_normal: color_u8!(36, 39, 46, 255),
+            cell_emphasize: color_u8!(52, 84, 140, 255),
+            cell_pencil_emphasize: color_u8!(130, 82, 40, 255),
+            cell_highlighted: color_u8!(55, 59, 68, 255),
+            cell_multi_selected: color_u8!(80, 170, 110, 140),
+            cell_marks: [
+                color_u8!(170, 60, 60, 255),
+                color_u8!(175, 110, 40, 255),
+                color_u8!(165, 145, 40, 255),
+                color_u8!(70, 135, 60, 255),
+                color_u8!(45, 125, 135, 255),
+                color_u8!(55, 80, 160, 255),
+                color_u8!(115, 65, 150, 255),
+                color_u8!(100, 100, 100, 255),
+            ],
+            cell_text: color_u8!(130, 185, 240, 255),
+            cell_text_initial: color_u8!(230, 230, 230, 255),
+            cell_text_conflict: color_u8!(255, 95, 95, 255),
+            cell_text_pencil: color_u8!(190, 190, 190, 255),
+            cell_line: color_u8!(75, 80, 90, 255),
+            box_line: color_u8!(170, 175, 185, 255),
+            menu_text: color_u8!(230, 230, 230, 255),
+            menu_text_selected: color_u8!(255, 255, 255, 255),
+            menu_number_background_normal: color_u8!(80, 115, 170, 255),
+            menu_number_background_pencil: color_u8!(200, 120, 40, 255),
+            menu_delete_background: color_u8!(210, 50, 50, 255),
+            modal_background: color_u8!(0, 0, 0, 160),
+            modal_panel: color_u8!(44, 48, 56, 255),
+            modal_text: color_u8!(230, 230, 230, 255),
+            modal_difficulty_one: color_u8!(80, 190, 80, 255),
+            modal_difficulty_two: color_u8!(255, 150, 50, 255),
+            modal_difficulty_three: color_u8!(255, 80, 80, 255),
+            modal_victory_gold: color_u8!(242, 183, 5, 255),
+            modal_victory_red: color_u8!(239, 70, 70, 255),
+            status_text: color_u8!(230, 230, 230, 255),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "High contrast".to_string(),
+            dark: true,
+            background: color_u8!(0, 0, 0, 255),
+            cell_normal: color_u8!(0, 0, 0, 255),
+            cell_emphasize: color_u8!(0, 90, 255, 255),
+            cell_pencil_emphasize: color_u8!(255, 120, 0, 255),
+            cell_highlighted: color_u8!(70, 70, 70, 255),
+            cell_multi_selected: color_u8!(0, 255, 120, 150),
+            cell_marks: [
+                color_u8!(255, 0, 0, 255),
+                color_u8!(255, 140, 0, 255),
+                color_u8!(255, 255, 0, 255),
+                color_u8!(0, 200, 0, 255),
+                color_u8!(0, 255, 255, 255),
+                color_u8!(0, 80, 255, 255),
+                color_u8!(200, 0, 255, 255),
+                color_u8!(160, 160, 160, 255),
+            ],
+            cell_text: color_u8!(255, 255, 0, 255),
+            cell_text_initial: color_u8!(255, 255, 255, 255),
+            cell_text_conflict: color_u8!(255, 60, 60, 255),
+            cell_text_pencil: color_u8!(255, 255, 255, 255),
+            cell_line: color_u8!(200, 200, 200, 255),
+            box_line: color_u8!(255, 255, 255, 255),
+            menu_text: color_u8!(255, 255, 255, 255),
+            menu_text_selected: color_u8!(0, 0, 0, 255),
+            menu_number_background_normal: color_u8!(255, 255, 0, 255),
+            menu_number_background_pencil: color_u8!(255, 140, 0, 255),
+            menu_delete_background: color_u8!(255, 0, 0, 255),
+            modal_background: color_u8!(0, 0, 0, 200),
+            modal_panel: color_u8!(0, 0, 0, 255),
+            modal_text: color_u8!(255, 255, 255, 255),
+            modal_difficulty_one: color_u8!(0, 255, 0, 255),
+            modal_difficulty_two: color_u8!(255, 160, 0, 255),
+            modal_difficulty_three: color_u8!(255, 0, 0, 255),
+            modal_victory_gold: color_u8!(255, 215, 0, 255),
+            modal_victory_red: color_u8!(255, 0, 0, 255),
+            status_text: color_u8!(255, 255, 255, 255),
+        }
+    }
+
+    pub fn sepia() -> Self {
+        Theme {
+            name: "Sepia".to_string(),
+            background: color_u8!(244, 236, 216, 255),
+            cell_normal: color_u8!(251, 245, 230, 255),
+            cell_emphasize: color_u8!(222, 200, 150, 255),
+            cell_highlighted: color_u8!(232, 222, 196, 255),
+            cell_text: color_u8!(120, 70, 30, 255),
+            cell_text_initial: color_u8!(60, 40, 20, 255),
+            cell_text_pencil: color_u8!(60, 40, 20, 255),
+            cell_line: color_u8!(180, 160, 120, 255),
+            box_line: color_u8!(90, 60, 30, 255),
+            menu_text: color_u8!(60, 40, 20, 255),
+            menu_number_background_normal: color_u8!(120, 85, 50, 255),
+            modal_panel: color_u8!(251, 245, 230, 255),
+            modal_text: color_u8!(60, 40, 20, 255),
+            status_text: color_u8!(60, 40, 20, 255),
+            ..Self::light()
+        }
+    }
+
+    // red and green look alike, emphasis and pencil emphasis are
+    // blue and yellow and also differ in brightness
+    pub fn deuteranopia() -> Self {
+        Theme {
+            name: "Deuteranopia".to_string(),
+            cell_emphasize: color_u8!(140, 190, 255, 255),
+            cell_pencil_emphasize: color_u8!(255, 236, 150, 255),
+            cell_highlighted: color_u8!(225, 225, 225, 255),
+            cell_multi_selected: color_u8!(0, 114, 178, 110),
+            cell_marks: OKABE_ITO_MARKS,
+            cell_text: color_u8!(0, 90, 160, 255),
+            cell_text_conflict: color_u8!(213, 94, 0, 255),
+            menu_number_background_pencil: color_u8!(200, 160, 0, 255),
+            menu_delete_background: color_u8!(213, 94, 0, 255),
+            ..Self::light()
+        }
+    }
+
+    pub fn protanopia() -> Self {
+        Theme {
+            name: "Protanopia".to_string(),
+            cell_text_conflict: color_u8!(190, 120, 0, 255),
+            menu_delete_background: color_u8!(190, 120, 0, 255),
+            ..Self::deuteranopia()
+        }
+    }
+
+    // blue and yellow look alike, the emphasis colours are red and teal
+    pub fn tritanopia() -> Self {
+        Theme {
+            name: "Tritanopia".to_string(),
+            cell_emphasize: color_u8!(255, 170, 180, 255),
+            cell_pencil_emphasize: color_u8!(130, 215, 205, 255),
+            cell_highlighted: color_u8!(225, 225, 225, 255),
+            cell_multi_selected: color_u8!(200, 0, 80, 90),
+            cell_marks: OKABE_ITO_MARKS,
+            cell_text: color_u8!(0, 120, 110, 255),
+            cell_text_conflict: color_u8!(200, 0, 0, 255),
+            menu_number_background_normal: color_u8!(150, 30, 60, 255),
+            menu_number_background_pencil: color_u8!(0, 140, 130, 255),
+            ..Self::light()
+        }
+    }
+
+    pub fn built_in() -> Vec<Theme> {
+        vec![
+            Self::light(),
+            Self::dark(),
+            Self::high_contrast(),
+            Self::sepia(),
+            Self::deuteranopia(),
+            Self::protanopia(),
+            Self::tritanopia(),
+        ]
+    }
+
+    // built in themes first, a custom theme can't replace one of them
+    pub fn all() -> Vec<Theme> {
+        let mut themes = Self::built_in();
+        for theme in load_custom_themes() {
+            if !themes.iter().any(|t| t.name == theme.name) {
+                themes.push(theme);
+            }
+        }
+
+        themes
+    }
+
+    pub fn find(themes: &[Theme], name: &str) -> Theme {
+        themes
+            .iter()
+            .find(|theme| theme.name == name)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+// each theme is read on its own so one mistake doesn't lose the others.
+// every other field has a default but the name has to be given
+fn parse_themes(themes_str: &str) -> Vec<Theme> {
+    let entries = match serde_json::from_str::<Vec<serde_json::Value>>(themes_str) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("ignoring the custom themes, {}", error);
+            return vec![];
+        }
+    };
+
+    let mut themes = vec![];
+    for (i, entry) in entries.into_iter().enumerate() {
+        if !entry.get("name").is_some_and(serde_json::Value::is_string) {
+            eprintln!(
+                "ignoring theme {} in {}, it has no name",
+                i, CUSTOM_THEMES_PATH
+            );
+            continue;
+        }
+
+        match serde_json::from_value::<Theme>(entry) {
+            Ok(theme) => themes.push(theme),
+            Err(error) => eprintln!("ignoring custom theme {}, {}", i, error),
+        }
+    }
+
+    themes
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_custom_themes() -> Vec<Theme> {
+    std::fs::read_to_string(CUSTOM_THEMES_PATH)
+        .map(|themes_str| parse_themes(&themes_str))
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_custom_themes() -> Vec<Theme> {
+    vec![]
+}
+
+fn color_to_hex(color: &Color) -> String {
+    let [r, g, b, a] = color.to_vec().to_array().map(|c| (c * 255.0).round() as u8);
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+// accepts "#rrggbb" and "#rrggbbaa"
+fn hex_to_color(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+    Some(color_u8!(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+mod hex_color {
+    use macroquad::color::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::color_to_hex(color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        super::hex_to_color(&hex).ok_or_else(|| D::Error::custom(format!("bad colour {}", hex)))
+    }
+}
+
+mod hex_colors {
+    use macroquad::color::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::MARK_COLOR_COUNT;
+
+    pub fn serialize<S: Serializer>(
+        colors: &[Color; MARK_COLOR_COUNT],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(super::color_to_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[Color; MARK_COLOR_COUNT], D::Error> {
+        let hexes = Vec::<String>::deserialize(deserializer)?;
+        if hexes.len() != MARK_COLOR_COUNT {
+            return Err(D::Error::custom(format!(
+                "expected {} mark colours",
+                MARK_COLOR_COUNT
+            )));
+        }
+
+        let mut colors = [Color::default(); MARK_COLOR_COUNT];
+        for (color, hex) in colors.iter_mut().zip(hexes) {
+            *color = super::hex_to_color(&hex)
+                .ok_or_else(|| D::Error::custom(format!("bad colour {}", hex)))?;
+        }
+
+        Ok(colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::{color::Color, color_u8};
+
+    use super::{color_to_hex, hex_to_color, parse_themes, Theme, DEFAULT_THEME};
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(hex_to_color("#ff8000"), Some(color_u8!(255, 128, 0, 255)));
+        assert_eq!(hex_to_color("#00000080"), Some(color_u8!(0, 0, 0, 128)));
+        assert_eq!(hex_to_color("ff8000"), None);
+        assert_eq!(hex_to_color("#ff80"), None);
+        assert_eq!(hex_to_color("#gg8000"), None);
+
+        assert_eq!(color_to_hex(&color_u8!(255, 128, 0, 255)), "#ff8000");
+        assert_eq!(color_to_hex(&color_u8!(0, 0, 0, 128)), "#00000080");
+    }
+
+    #[test]
+    fn built_in_themes() {
+        let themes = Theme::built_in();
+        assert_eq!(themes[0].name, DEFAULT_THEME);
+        assert!(themes.iter().any(|theme| theme.dark));
+
+        for (i, theme) in themes.iter().enumerate() {
+            assert!(!themes[i + 1..].iter().any(|t| t.name == theme.name));
+        }
+
+        assert_eq!(Theme::find(&themes, "Dark"), Theme::dark());
+        assert_eq!(Theme::find(&themes, "missing"), Theme::light());
+    }
+
+    #[test]
+    fn custom_themes() {
+        let themes = parse_themes(
+            r##"[{"name": "Mint", "background": "#e0fff0", "cell_marks": ["#000000", "#111111",
+            "#222222", "#333333", "#444444", "#555555", "#666666", "#777777"]}]"##,
+        );
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].name, "Mint");
+        assert_eq!(themes[0].background, color_u8!(224, 255, 240, 255));
+        assert_eq!(themes[0].cell_marks[7], color_u8!(119, 119, 119, 255));
+        assert_eq!(themes[0].cell_text, Theme::light().cell_text);
+
+        // a saved theme reads back the same
+        let json = serde_json::to_string(&vec![Theme::dark()]).unwrap();
+        assert_eq!(parse_themes(&json), vec![Theme::dark()]);
+
+        assert!(parse_themes(r#"[{"background": "white"}]"#).is_empty());
+        assert!(parse_themes("not json").is_empty());
+
+        // the broken and nameless ones are skipped, not the whole file
+        let themes = parse_themes(
+            r##"[{"name": "Broken", "background": "white"}, {"background": "#000000"},
+            {"name": "Night", "background": "#000000"}]"##,
+        );
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].name, "Night");
+    }
+}
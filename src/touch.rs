@@ -0,0 +1,334 @@
+// touch gestures for phones. macroquad hands over the fingers that are down
+// each frame, these turn them into taps, long presses, swipes and pinches
+
+use macroquad::camera::Camera2D;
+use macroquad::input::{Touch, TouchPhase};
+use macroquad::math::{vec2, Rect, Vec2};
+
+// a finger that moves less than this is still a tap
+const MOVE_THRESHOLD: f32 = 12.0;
+const LONG_PRESS: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Tap(Vec2),
+    LongPress(Vec2),
+    // one finger moving, every frame until it lifts
+    Drag { start: Vec2, position: Vec2 },
+    // the finger lifted after a drag
+    Swipe { start: Vec2, end: Vec2 },
+    TwoFingerTap,
+    // two fingers moving, the scale and the move since the last frame
+    Pinch { center: Vec2, scale: f32, pan: Vec2 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Finger {
+    id: u64,
+    start: Vec2,
+    position: Vec2,
+}
+
+#[derive(Debug, Default)]
+pub struct Gestures {
+    fingers: Vec<Finger>,
+    // the gesture is decided by the most fingers that were down at once
+    most_fingers: usize,
+    held: f32,
+    moved: bool,
+    long_pressed: bool,
+    // the distance and center of the two fingers last frame
+    pinch: Option<(f32, Vec2)>,
+}
+
+impl Gestures {
+    pub fn update(&mut self, touches: &[Touch], seconds: f32) -> Vec<Gesture> {
+        let mut gestures = vec![];
+
+        // fingers that went away without saying so are dropped
+        self.fingers
+            .retain(|finger| touches.iter().any(|touch| touch.id == finger.id));
+        if touches.is_empty() {
+            *self = Default::default();
+            return gestures;
+        }
+
+        for touch in touches {
+            match self.fingers.iter_mut().find(|finger| finger.id == touch.id) {
+                Some(finger) => finger.position = touch.position,
+                None => self.fingers.push(Finger {
+                    id: touch.id,
+                    start: touch.position,
+                    position: touch.position,
+                }),
+            }
+        }
+
+        self.most_fingers = self.most_fingers.max(self.fingers.len());
+        self.held += seconds;
+        if self
+            .fingers
+            .iter()
+            .any(|finger| finger.start.distance(finger.position) > MOVE_THRESHOLD)
+        {
+            self.moved = true;
+        }
+
+        match self.fingers[..] {
+            [finger] if self.most_fingers == 1 => {
+                if self.moved {
+                    gestures.push(Gesture::Drag {
+                        start: finger.start,
+                        position: finger.position,
+                    });
+                } else if !self.long_pressed && self.held >= LONG_PRESS {
+                    self.long_pressed = true;
+                    gestures.push(Gesture::LongPress(finger.start));
+                }
+            }
+            [first, second] => {
+                let distance = first.position.distance(second.position);
+                let center = (first.position + second.position) / 2.0;
+                if let (Some((last_distance, last_center)), true) = (self.pinch, self.moved) {
+                    if last_distance > 0.0 {
+                        gestures.push(Gesture::Pinch {
+                            center,
+                            scale: distance / last_distance,
+                            pan: center - last_center,
+                        });
+                    }
+                }
+                self.pinch = Some((distance, center));
+            }
+            _ => {}
+        }
+
+        let ended = |phase| matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled);
+        if touches.iter().all(|touch| ended(touch.phase)) {
+            let cancelled = touches
+                .iter()
+                .any(|touch| touch.phase == TouchPhase::Cancelled);
+
+            match (self.most_fingers, &self.fingers[..]) {
+                _ if cancelled || self.long_pressed => {}
+                (1, [finger]) if self.moved => gestures.push(Gesture::Swipe {
+                    start: finger.start,
+                    end: finger.position,
+                }),
+                (1, [finger]) => gestures.push(Gesture::Tap(finger.start)),
+                (2, _) if !self.moved && self.held < LONG_PRESS => {
+                    gestures.push(Gesture::TwoFingerTap)
+                }
+                _ => {}
+            }
+
+            *self = Default::default();
+        }
+
+        gestures
+    }
+}
+
+// the board as pinched, a point on the board is drawn at
+// point * scale + offset. area is where the board sits on the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    pub scale: f32,
+    pub offset: Vec2,
+    pub area: Rect,
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self::new(Rect::new(0.0, 0.0, 0.0, 0.0))
+    }
+}
+
+impl Zoom {
+    pub fn new(area: Rect) -> Self {
+        Zoom {
+            scale: 1.0,
+            offset: vec2(0.0, 0.0),
+            area,
+        }
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.scale > 1.0
+    }
+
+    // points outside the board aren't zoomed, the menu stays where it is
+    pub fn to_board(&self, point: Vec2) -> Vec2 {
+        if !self.area.contains(point) {
+            return point;
+        }
+        (point - self.offset) / self.scale
+    }
+
+    // keeps the point under the fingers under them, and the board
+    // covering its area
+    pub fn pinch(&mut self, center: Vec2, scale: f32, pan: Vec2) {
+        let point = (center - self.offset) / self.scale;
+        self.scale = (self.scale * scale).clamp(1.0, MAX_ZOOM);
+        self.offset = center + pan - (point * self.scale);
+
+        let low = self.area.point() + self.area.size();
+        let high = self.area.point();
+        self.offset = self
+            .offset
+            .clamp(low * (1.0 - self.scale), high * (1.0 - self.scale));
+    }
+
+    // draws the visible part of the board onto its area, none when
+    // the board isn't zoomed
+    pub fn camera(&self, screen_height: f32) -> Option<Camera2D> {
+        if !self.is_zoomed() {
+            return None;
+        }
+
+        let corner = (self.area.point() - self.offset) / self.scale;
+        let size = self.area.size() / self.scale;
+        let mut camera = Camera2D::from_display_rect(Rect::new(corner.x, corner.y, size.x, size.y));
+        // the viewport counts from the bottom of the screen
+        camera.viewport = Some((
+            self.area.x as i32,
+            (screen_height - self.area.bottom()) as i32,
+            self.area.w as i32,
+            self.area.h as i32,
+        ));
+
+        Some(camera)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::input::{Touch, TouchPhase};
+    use macroquad::math::{vec2, Rect, Vec2};
+
+    use super::{Gesture, Gestures, Zoom};
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> Touch {
+        Touch {
+            id,
+            phase,
+            position: vec2(x, y),
+        }
+    }
+
+    #[test]
+    fn tap_and_long_press() {
+        let mut gestures = Gestures::default();
+        assert!(gestures
+            .update(&[touch(1, TouchPhase::Started, 10.0, 10.0)], 0.016)
+            .is_empty());
+        assert_eq!(
+            gestures.update(&[touch(1, TouchPhase::Ended, 12.0, 11.0)], 0.1),
+            vec![Gesture::Tap(vec2(10.0, 10.0))]
+        );
+
+        gestures.update(&[touch(2, TouchPhase::Started, 50.0, 50.0)], 0.016);
+        assert_eq!(
+            gestures.update(&[touch(2, TouchPhase::Stationary, 50.0, 50.0)], 0.6),
+            vec![Gesture::LongPress(vec2(50.0, 50.0))]
+        );
+        // a long press isn't also a tap
+        assert!(gestures
+            .update(&[touch(2, TouchPhase::Ended, 50.0, 50.0)], 0.1)
+            .is_empty());
+    }
+
+    #[test]
+    fn drag_then_swipe() {
+        let mut gestures = Gestures::default();
+        gestures.update(&[touch(1, TouchPhase::Started, 10.0, 10.0)], 0.016);
+        assert_eq!(
+            gestures.update(&[touch(1, TouchPhase::Moved, 60.0, 12.0)], 0.016),
+            vec![Gesture::Drag {
+                start: vec2(10.0, 10.0),
+                position: vec2(60.0, 12.0)
+            }]
+        );
+        assert_eq!(
+            gestures.update(&[touch(1, TouchPhase::Ended, 80.0, 12.0)], 0.016)[1],
+            Gesture::Swipe {
+                start: vec2(10.0, 10.0),
+                end: vec2(80.0, 12.0)
+            }
+        );
+    }
+
+    #[test]
+    fn two_fingers() {
+        let mut gestures = Gestures::default();
+        gestures.update(
+            &[
+                touch(1, TouchPhase::Started, 10.0, 10.0),
+                touch(2, TouchPhase::Started, 30.0, 10.0),
+            ],
+            0.016,
+        );
+        // lifting one finger first is still a two finger tap
+        gestures.update(
+            &[
+                touch(1, TouchPhase::Ended, 10.0, 10.0),
+                touch(2, TouchPhase::Stationary, 30.0, 10.0),
+            ],
+            0.05,
+        );
+        assert_eq!(
+            gestures.update(&[touch(2, TouchPhase::Ended, 30.0, 10.0)], 0.05),
+            vec![Gesture::TwoFingerTap]
+        );
+
+        gestures.update(
+            &[
+                touch(1, TouchPhase::Started, 100.0, 100.0),
+                touch(2, TouchPhase::Started, 200.0, 100.0),
+            ],
+            0.016,
+        );
+        let pinch = gestures.update(
+            &[
+                touch(1, TouchPhase::Moved, 50.0, 100.0),
+                touch(2, TouchPhase::Moved, 250.0, 100.0),
+            ],
+            0.016,
+        );
+        assert_eq!(
+            pinch,
+            vec![Gesture::Pinch {
+                center: vec2(150.0, 100.0),
+                scale: 2.0,
+                pan: Vec2::ZERO
+            }]
+        );
+    }
+
+    #[test]
+    fn zoom() {
+        let mut zoom = Zoom::new(Rect::new(10.0, 10.0, 90.0, 90.0));
+        assert!(zoom.camera(100.0).is_none());
+        assert_eq!(zoom.to_board(vec2(50.0, 50.0)), vec2(50.0, 50.0));
+
+        // the point between the fingers stays put
+        zoom.pinch(vec2(50.0, 50.0), 2.0, Vec2::ZERO);
+        assert_eq!(zoom.scale, 2.0);
+        assert_eq!(zoom.to_board(vec2(50.0, 50.0)), vec2(50.0, 50.0));
+        assert_eq!(zoom.to_board(vec2(60.0, 50.0)), vec2(55.0, 50.0));
+        assert!(zoom.camera(100.0).is_some());
+        // the menu isn't zoomed
+        assert_eq!(zoom.to_board(vec2(150.0, 50.0)), vec2(150.0, 50.0));
+
+        // the board can't be pulled away from its edges
+        zoom.pinch(vec2(50.0, 50.0), 1.0, vec2(500.0, 0.0));
+        assert_eq!(zoom.to_board(vec2(10.0, 50.0)), vec2(10.0, 50.0));
+
+        zoom.pinch(vec2(50.0, 50.0), 10.0, Vec2::ZERO);
+        assert_eq!(zoom.scale, 3.0);
+        zoom.pinch(vec2(50.0, 50.0), 0.1, Vec2::ZERO);
+        assert!(!zoom.is_zoomed());
+        assert_eq!(zoom.offset, Vec2::ZERO);
+    }
+}
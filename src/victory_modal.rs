@@ -1,4 +1,27 @@
 use crate::menu_item::MenuItem;
+use crate::summary::GameSummary;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VictoryAction {
+    NewGame,
+    Replay,
+    Copy,
+}
+
+// a text button, wider than it is tall unlike the menu items
+#[derive(Clone, Copy, Default)]
+pub struct ModalButton {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ModalButton {
+    pub fn click(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+}
 
 pub struct VictoryModal {
     pub show: bool,
@@ -9,9 +32,18 @@ pub struct VictoryModal {
     pub font_width: f32,
     pub width: f32,
     pub height: f32,
+    pub star_row_height: f32,
+    pub line_height: f32,
     pub heart: MenuItem,
     pub star_1: MenuItem,
     pub star_2: MenuItem,
+    pub summary: Option<GameSummary>,
+    // a line above the summary, the locale key of who won a race
+    pub headline: Option<&'static str>,
+    pub copied: bool,
+    pub new_game: ModalButton,
+    pub replay: ModalButton,
+    pub copy: ModalButton,
 }
 
 impl Default for VictoryModal {
@@ -31,18 +63,36 @@ impl VictoryModal {
             font_width: 0.0,
             width: 0.0,
             height: 0.0,
+            star_row_height: 0.0,
+            line_height: 0.0,
             heart: Default::default(),
             star_1: Default::default(),
             star_2: Default::default(),
+            summary: None,
+            headline: None,
+            copied: false,
+            new_game: Default::default(),
+            replay: Default::default(),
+            copy: Default::default(),
         }
     }
 
-    pub fn show(&mut self) {
+    pub fn show(&mut self, summary: GameSummary) {
         self.show = true;
+        self.summary = Some(summary);
+        self.copied = false;
 
         self.update_victory();
     }
 
+    pub fn set_headline(&mut self, headline: Option<&'static str>) {
+        self.headline = headline;
+
+        if self.show {
+            self.update_victory();
+        }
+    }
+
     pub fn hide(&mut self) {
         self.show = false;
     }
@@ -57,14 +107,20 @@ impl VictoryModal {
         }
     }
 
+    // the stars on top, the headline and two lines of summary, then the buttons
     pub fn update_victory(&mut self) {
         let cell_width = self.game_square / 9.0;
-        self.width = cell_width * 4.0;
+        self.width = cell_width * 6.0;
         self.x = self.game_square / 2.0 - (self.width / 2.0);
-        self.height = cell_width * 1.5;
+        self.star_row_height = cell_width * 1.5;
+        self.line_height = cell_width * 0.6;
+        let button_height = cell_width * 0.8;
+        let lines = if self.headline.is_some() { 3.0 } else { 2.0 };
+        self.height =
+            self.star_row_height + (lines * self.line_height) + button_height + (cell_width * 0.6);
         self.y = self.game_square / 2.0 - (self.height / 2.0);
 
-        let button_y = self.y + self.height / 2.0 - self.font_height;
+        let button_y = self.y + self.star_row_height / 2.0 - self.font_height;
 
         let button_2_x = (self.game_square / 2.0) - self.font_width;
         self.heart.update(button_2_x, button_y, cell_width);
@@ -74,9 +130,71 @@ impl VictoryModal {
 
         let button_3_x = button_2_x + (cell_width * 1.25);
         self.star_2.update(button_3_x, button_y, cell_width);
+
+        let gap = cell_width * 0.2;
+        let button_width = (self.width - (4.0 * gap)) / 3.0;
+        let buttons_y = self.y + self.height - button_height - (cell_width * 0.3);
+        for (i, button) in [&mut self.new_game, &mut self.replay, &mut self.copy]
+            .into_iter()
+            .enumerate()
+        {
+            *button = ModalButton {
+                x: self.x + gap + (i as f32 * (button_width + gap)),
+                y: buttons_y,
+                width: button_width,
+                height: button_height,
+            };
+        }
+    }
+
+    pub fn click(&self, x: f32, y: f32) -> Option<VictoryAction> {
+        [
+            (self.new_game, VictoryAction::NewGame),
+            (self.replay, VictoryAction::Replay),
+            (self.copy, VictoryAction::Copy),
+        ]
+        .into_iter()
+        .find(|(button, _)| button.click(x, y))
+        .map(|(_, action)| action)
     }
 
     pub fn click_outside(&mut self, x: f32, y: f32) -> bool {
         x < self.x || x > self.x + self.width || y < self.y || y > self.y + self.height
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{VictoryAction, VictoryModal};
+    use crate::board::Board;
+    use crate::summary::GameSummary;
+
+    #[test]
+    fn victory_buttons() {
+        let mut modal = VictoryModal::new();
+        modal.update(900.0, 10.0, 10.0);
+        modal.show(GameSummary::from_board(&Board::new()));
+
+        let centre = |button: super::ModalButton| {
+            (
+                button.x + (button.width / 2.0),
+                button.y + (button.height / 2.0),
+            )
+        };
+        let (x, y) = centre(modal.new_game);
+        assert_eq!(modal.click(x, y), Some(VictoryAction::NewGame));
+        let (x, y) = centre(modal.replay);
+        assert_eq!(modal.click(x, y), Some(VictoryAction::Replay));
+        let (x, y) = centre(modal.copy);
+        assert_eq!(modal.click(x, y), Some(VictoryAction::Copy));
+
+        // the buttons are inside the panel
+        assert!(!modal.click_outside(x, y));
+        assert_eq!(modal.click(modal.x + 1.0, modal.y + 1.0), None);
+        assert!(modal.click_outside(0.0, 0.0));
+
+        let height = modal.height;
+        modal.set_headline(Some("race.won"));
+        assert!(modal.height > height);
+    }
+}
@@ -0,0 +1,24 @@
+// browser side of the calls the game makes from wasm
+params_register_js_plugin = function (importObject) {
+    importObject.env.sudoku_locale = function () {
+        return js_object(navigator.language || "en");
+    }
+    importObject.env.sudoku_location = function () {
+        return js_object(window.location.search + window.location.hash);
+    }
+    importObject.env.sudoku_vibrate = function (milliseconds) {
+        if (navigator.vibrate) {
+            navigator.vibrate(milliseconds);
+        }
+    }
+    // what the game says for screen readers goes into the live region
+    importObject.env.sudoku_announce = function (text) {
+        document.getElementById("announcer").textContent = consume_js_object(text);
+    }
+}
+
+miniquad_add_plugin({
+    register_plugin: params_register_js_plugin,
+    name: "sudoku_bridge",
+    version: "0.1.0"
+});